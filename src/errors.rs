@@ -1,5 +1,8 @@
 //! Error types and traits for SMS verification operations.
 
+use crate::types::TaskId;
+use std::time::Duration;
+
 /// Trait for errors that can be classified as retryable or permanent.
 ///
 /// This trait provides two levels of retryability classification:
@@ -62,4 +65,40 @@ pub trait RetryableError {
     fn should_retry_operation(&self) -> bool {
         self.is_retryable()
     }
+
+    /// Returns the delay requested by the service before the next attempt.
+    ///
+    /// When `Some`, retry wrappers wait this long instead of using their
+    /// backoff delay. Default implementation returns `None`.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns true if the error suggests the provider itself is failing,
+    /// e.g. it is unreachable or erroring internally, rather than rejecting
+    /// this particular request.
+    ///
+    /// Circuit breakers count only these errors, so that a shortage of
+    /// numbers for one country does not take the provider out of use.
+    ///
+    /// Default implementation returns the same as `is_retryable()`.
+    fn is_provider_failure(&self) -> bool {
+        self.is_retryable()
+    }
+
+    /// Returns a short, stable snake_case label for metric attributes.
+    ///
+    /// Default implementation returns `"provider"`.
+    fn metric_label(&self) -> &'static str {
+        "provider"
+    }
+
+    /// Returns the task ID of an activation that is still live despite this
+    /// error, e.g. when polling failed permanently but the number is still
+    /// held.
+    ///
+    /// Default implementation returns `None`.
+    fn live_task_id(&self) -> Option<&TaskId> {
+        None
+    }
 }
@@ -65,6 +65,7 @@
 //!
 //! - `hero-sms` - Hero SMS provider support (enabled by default)
 //! - `tracing` - OpenTelemetry tracing instrumentation (enabled by default)
+//! - `testing` - `testing::MockProvider` for testing code that uses this crate
 
 mod errors;
 mod providers;
@@ -72,16 +73,28 @@ mod service;
 mod types;
 mod utils;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 // Re-export error types
 pub use errors::RetryableError;
 
 // Re-export provider types
-pub use providers::{Provider, SmsRetryableProvider};
+pub use providers::{
+    CircuitBreakerError, CircuitBreakerProvider, CircuitState, DropCancelError, DropCancelProvider,
+    DropGuard, DropGuardFuture, FallbackProvider, NumberPool, NumberPoolError, OperationSnapshot,
+    PhoneBookEntry, PhoneBookError, PhoneBookProvider, Provider, ProviderStatistics,
+    RateLimitedProvider, RetryPredicate, SmsRetryableError, SmsRetryableProvider,
+    StatisticsSnapshot, SupportsIdempotencyKey,
+};
 
 // Re-export service types
 pub use service::{
-    ConfigError, SmsSolverService, SmsSolverServiceBuilder, SmsSolverServiceConfig,
-    SmsSolverServiceConfigBuilder, SmsSolverServiceError, SmsSolverServiceTrait,
+    ActivationGuard, CancelResult, ConfigError, HookError, HookedSmsSolverService,
+    OwnedActivationGuard, PollProgress, ServiceStatistics, SmsSolverService,
+    SmsSolverServiceBuildError, SmsSolverServiceBuilder, SmsSolverServiceConfig,
+    SmsSolverServiceConfigBuilder, SmsSolverServiceConfigPatch, SmsSolverServiceError,
+    SmsSolverServiceTrait,
 };
 
 // Re-export CancellationToken for cancellable operations
@@ -89,11 +102,16 @@ pub use tokio_util::sync::CancellationToken;
 
 // Re-export core types
 pub use types::{
-    DialCode, DialCodeError, FullNumber, Number, NumberError, SmsCode, SmsTaskResult, TaskId,
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, DialCodeError, FullNumber,
+    FullNumberError, HealthReport, Number, NumberError, PriceEntry, ReputationLevel, SmsCode,
+    SmsTaskResult, TaskId,
 };
 
 // Re-export utility types
-pub use utils::RetryConfig;
+pub use utils::{
+    CountryDialCode, NumberValidationError, ProviderKind, RetryBudget, RetryConfig,
+    is_sanctioned_country, keshvar_country_to_dial_code,
+};
 
 // Re-export keshvar so users don't need to add it as a separate dependency
 pub use keshvar::{Alpha2, Country};
@@ -121,7 +139,15 @@ pub use types::DialCodeToCountryError;
 /// ```
 #[cfg(feature = "hero-sms")]
 pub mod hero_sms {
+    pub use crate::providers::hero_sms::types::{
+        ConnectionStats, CountryInfo, CountryOperatorEntry, OperatorInfo, ServicePrice,
+        SupportedService,
+    };
     pub use crate::providers::hero_sms::{
-        HeroSms, HeroSmsError, HeroSmsProvider, Service, SmsCountryExt,
+        ApiVersion, ContextualHeroSmsError, CountryMap, CountryPreferenceStrategy, ErrorCategory,
+        HeroSms, HeroSmsCountryMapBuilder, HeroSmsError, HeroSmsErrorCode, HeroSmsProvider,
+        HeroSmsServiceError, ResultExt, Service, SmsCountryExt,
     };
+    #[cfg(feature = "v1-compat")]
+    pub use crate::providers::hero_sms::{HeroSmsV1, HeroSmsV1Provider};
 }
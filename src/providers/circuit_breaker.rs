@@ -0,0 +1,699 @@
+//! Provider wrapper that stops calling a provider while it keeps failing.
+
+use super::traits::{Provider, SupportsIdempotencyKey};
+use crate::errors::RetryableError;
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[cfg(feature = "tracing")]
+use tracing::{info, warn};
+
+/// Errors returned by [`CircuitBreakerProvider`].
+#[derive(Debug, Error)]
+pub enum CircuitBreakerError<E> {
+    /// The circuit is open, the call was not sent to the provider.
+    #[error("Circuit breaker is open, provider calls are rejected")]
+    Open {
+        /// When the next probe call will be let through.
+        until: Instant,
+    },
+
+    /// The wrapped provider returned an error.
+    #[error(transparent)]
+    Provider(E),
+}
+
+impl<E> CircuitBreakerError<E> {
+    /// Returns true if the call was rejected by the open circuit.
+    pub fn is_open(&self) -> bool {
+        matches!(self, CircuitBreakerError::Open { .. })
+    }
+
+    /// Returns the provider error, if the call reached the provider.
+    pub fn into_provider_error(self) -> Option<E> {
+        match self {
+            CircuitBreakerError::Open { .. } => None,
+            CircuitBreakerError::Provider(e) => Some(e),
+        }
+    }
+}
+
+impl<E: RetryableError> RetryableError for CircuitBreakerError<E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            CircuitBreakerError::Open { .. } => false,
+            CircuitBreakerError::Provider(e) => e.is_retryable(),
+        }
+    }
+
+    fn should_retry_operation(&self) -> bool {
+        match self {
+            // Another provider, or this one later, may still succeed
+            CircuitBreakerError::Open { .. } => true,
+            CircuitBreakerError::Provider(e) => e.should_retry_operation(),
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            CircuitBreakerError::Open { until } => {
+                Some(until.saturating_duration_since(Instant::now()))
+            }
+            CircuitBreakerError::Provider(e) => e.retry_after(),
+        }
+    }
+
+    fn is_provider_failure(&self) -> bool {
+        match self {
+            CircuitBreakerError::Open { .. } => false,
+            CircuitBreakerError::Provider(e) => e.is_provider_failure(),
+        }
+    }
+
+    fn metric_label(&self) -> &'static str {
+        match self {
+            CircuitBreakerError::Open { .. } => "circuit_open",
+            CircuitBreakerError::Provider(e) => e.metric_label(),
+        }
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            CircuitBreakerError::Open { .. } => None,
+            CircuitBreakerError::Provider(e) => e.live_task_id(),
+        }
+    }
+}
+
+/// State of a [`CircuitBreakerProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through to the provider.
+    Closed {
+        /// Consecutive failed calls so far.
+        failures: u32,
+    },
+    /// Calls are rejected without reaching the provider.
+    Open {
+        /// When the circuit turns half-open.
+        until: Instant,
+    },
+    /// One probe call at a time goes through to check for recovery.
+    HalfOpen {
+        /// Consecutive successful probes so far.
+        successes: u32,
+        /// Whether a probe call is in flight.
+        probing: bool,
+    },
+}
+
+/// Wrapper that temporarily stops calling a provider that keeps failing.
+///
+/// After `failure_threshold` consecutive failed calls the circuit opens, and
+/// every call fails right away with [`CircuitBreakerError::Open`] instead of
+/// waiting on a provider that is down. Once `reset_timeout` has passed, the
+/// circuit turns half-open and lets one probe call through at a time. After
+/// `success_threshold` successful probes it closes again; a failed probe
+/// opens it for another `reset_timeout`.
+///
+/// Only errors for which [`RetryableError::is_provider_failure`] is true
+/// count as failures. Other errors, e.g. no numbers in stock for a country,
+/// show the provider is up and count as successes. Wrap the breaker in
+/// [`SmsRetryableProvider`] rather than the other way around if transient
+/// errors should be retried first. Clones share the circuit state.
+///
+/// `finish_activation` and `cancel_activation` bypass the circuit and do not
+/// count towards it, so activations that are already paid for can still be
+/// cleaned up while it is open.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::{CircuitBreakerProvider, SmsRetryableProvider};
+/// use std::time::Duration;
+///
+/// let provider = CircuitBreakerProvider::new(
+///     SmsRetryableProvider::new(base_provider),
+///     5,
+///     2,
+///     Duration::from_secs(30),
+/// );
+/// ```
+///
+/// [`SmsRetryableProvider`]: crate::SmsRetryableProvider
+#[derive(Debug)]
+pub struct CircuitBreakerProvider<P: Provider> {
+    inner: Arc<P>,
+    failure_threshold: u32,
+    success_threshold: u32,
+    reset_timeout: Duration,
+    state: Arc<Mutex<CircuitState>>,
+}
+
+impl<P: Provider> Clone for CircuitBreakerProvider<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            failure_threshold: self.failure_threshold,
+            success_threshold: self.success_threshold,
+            reset_timeout: self.reset_timeout,
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<P: Provider> CircuitBreakerProvider<P> {
+    /// Wrap a provider with a closed circuit.
+    ///
+    /// Thresholds of 0 are treated as 1.
+    pub fn new(
+        inner: P,
+        failure_threshold: u32,
+        success_threshold: u32,
+        reset_timeout: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            failure_threshold: failure_threshold.max(1),
+            success_threshold: success_threshold.max(1),
+            reset_timeout,
+            state: Arc::new(Mutex::new(CircuitState::Closed { failures: 0 })),
+        }
+    }
+
+    /// Get reference to the inner provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        *self.lock()
+    }
+
+    /// Close the circuit and clear the failure count.
+    pub fn reset(&self) {
+        *self.lock() = CircuitState::Closed { failures: 0 };
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CircuitState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Let a call through, or reject it if the circuit is open.
+    fn acquire(&self) -> Result<Permit<'_, P>, CircuitBreakerError<P::Error>> {
+        self.try_acquire()
+            .map_err(|until| CircuitBreakerError::Open { until })
+    }
+
+    /// Let a call through the circuit, or return when it opens again.
+    fn try_acquire(&self) -> Result<Permit<'_, P>, Instant> {
+        let mut state = self.lock();
+        match *state {
+            CircuitState::Closed { .. } => {}
+            CircuitState::Open { until } if Instant::now() < until => {
+                return Err(until);
+            }
+            CircuitState::Open { .. } => {
+                #[cfg(feature = "tracing")]
+                info!("Circuit breaker half-open, probing provider");
+
+                *state = CircuitState::HalfOpen {
+                    successes: 0,
+                    probing: true,
+                };
+            }
+            CircuitState::HalfOpen { probing: true, .. } => {
+                // Another probe is in flight, try again once it completes
+                return Err(Instant::now());
+            }
+            CircuitState::HalfOpen { successes, .. } => {
+                *state = CircuitState::HalfOpen {
+                    successes,
+                    probing: true,
+                };
+            }
+        }
+
+        Ok(Permit {
+            breaker: self,
+            recorded: false,
+        })
+    }
+
+    /// Update the state with the outcome of a call.
+    fn record(&self, success: bool) {
+        let mut state = self.lock();
+        *state = match (*state, success) {
+            (CircuitState::Closed { .. }, true) => CircuitState::Closed { failures: 0 },
+            (CircuitState::Closed { failures }, false) if failures + 1 < self.failure_threshold => {
+                CircuitState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (CircuitState::HalfOpen { successes, .. }, true)
+                if successes + 1 < self.success_threshold =>
+            {
+                CircuitState::HalfOpen {
+                    successes: successes + 1,
+                    probing: false,
+                }
+            }
+            (CircuitState::HalfOpen { .. }, true) => {
+                #[cfg(feature = "tracing")]
+                info!("Circuit breaker closed, provider recovered");
+
+                CircuitState::Closed { failures: 0 }
+            }
+            (CircuitState::Closed { .. } | CircuitState::HalfOpen { .. }, false) => {
+                #[cfg(feature = "tracing")]
+                warn!(
+                    reset_timeout_ms = self.reset_timeout.as_millis() as u64,
+                    "Circuit breaker opened, rejecting provider calls"
+                );
+
+                CircuitState::Open {
+                    until: Instant::now() + self.reset_timeout,
+                }
+            }
+            // Calls started before the circuit opened do not change it
+            (open @ CircuitState::Open { .. }, _) => open,
+        };
+    }
+
+    /// Run a provider call through the circuit.
+    async fn call<T>(
+        &self,
+        call: impl Future<Output = Result<T, P::Error>>,
+    ) -> Result<T, CircuitBreakerError<P::Error>> {
+        let permit = self.acquire()?;
+        let result = call.await;
+        permit.complete(!result.as_ref().is_err_and(P::Error::is_provider_failure));
+        result.map_err(CircuitBreakerError::Provider)
+    }
+}
+
+/// A call let through the circuit.
+///
+/// If dropped before completing, e.g. because the call was cancelled, a
+/// half-open circuit lets the next probe through.
+struct Permit<'a, P: Provider> {
+    breaker: &'a CircuitBreakerProvider<P>,
+    recorded: bool,
+}
+
+impl<P: Provider> Permit<'_, P> {
+    fn complete(mut self, success: bool) {
+        self.recorded = true;
+        self.breaker.record(success);
+    }
+}
+
+impl<P: Provider> Drop for Permit<'_, P> {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+        let mut state = self.breaker.lock();
+        if let CircuitState::HalfOpen { successes, .. } = *state {
+            *state = CircuitState::HalfOpen {
+                successes,
+                probing: false,
+            };
+        }
+    }
+}
+
+impl<P: Provider> Provider for CircuitBreakerProvider<P> {
+    type Error = CircuitBreakerError<P::Error>;
+    type Service = P::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        self.call(self.inner.get_phone_number(country, service))
+            .await
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.call(self.inner.get_phone_number_with_metadata(country, service))
+            .await
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.call(self.inner.get_phone_number_with_idempotency_key(
+            country,
+            service,
+            idempotency_key,
+        ))
+        .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        self.call(self.inner.get_phone_number_for_any_country(service))
+            .await
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        self.call(self.inner.get_sms_code(task_id)).await
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        self.call(self.inner.get_sms_code_with_text(task_id)).await
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        self.call(self.inner.get_all_sms_codes(task_id)).await
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        self.call(self.inner.get_all_sms_codes_with_text(task_id))
+            .await
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        self.call(self.inner.get_activation_status(task_id)).await
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.call(self.inner.request_another_code(task_id)).await
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.inner
+            .finish_activation(task_id)
+            .await
+            .map_err(CircuitBreakerError::Provider)
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.inner
+            .cancel_activation(task_id)
+            .await
+            .map_err(CircuitBreakerError::Provider)
+    }
+
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        let permit = match self.try_acquire() {
+            Ok(permit) => permit,
+            Err(until) => {
+                return task_ids
+                    .iter()
+                    .map(|_| Err(CircuitBreakerError::Open { until }))
+                    .collect();
+            }
+        };
+        let results = self.inner.get_sms_codes_batch(task_ids).await;
+        permit.complete(
+            !results
+                .iter()
+                .any(|result| result.as_ref().is_err_and(P::Error::is_provider_failure)),
+        );
+        results
+            .into_iter()
+            .map(|result| result.map_err(CircuitBreakerError::Provider))
+            .collect()
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        self.inner
+            .cancel_all_pending(task_ids)
+            .await
+            .into_iter()
+            .map(|result| result.map_err(CircuitBreakerError::Provider))
+            .collect()
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.call(self.inner.is_task_alive(task_id)).await
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.call(self.inner.warmup()).await
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.call(self.inner.account_reputation()).await
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        self.call(self.inner.country_prices(service)).await
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.call(self.inner.available_countries_with_counts(service))
+            .await
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.inner.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.inner.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.inner.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        self.inner.available_countries(service)
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.inner.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.inner.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+impl<P: SupportsIdempotencyKey> SupportsIdempotencyKey for CircuitBreakerProvider<P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockError, MockProvider, MockService};
+    use keshvar::Alpha2;
+
+    const RESET_TIMEOUT: Duration = Duration::from_millis(30);
+
+    /// Make the next `polls` SMS polls fail as if the provider were down.
+    fn fail_polls(mock: &MockProvider, polls: usize) {
+        mock.set_sms_code_responses(vec![Err(MockError::Transient); polls]);
+    }
+
+    /// Number of calls that reached the mock.
+    fn calls(mock: &MockProvider) -> usize {
+        (mock.call_count_get_number() + mock.call_count_get_sms_code()) as usize
+            + mock.cancelled_tasks().len()
+            + mock.finished_tasks().len()
+    }
+
+    fn breaker(mock: &MockProvider) -> CircuitBreakerProvider<MockProvider> {
+        CircuitBreakerProvider::new(mock.clone(), 2, 2, RESET_TIMEOUT)
+    }
+
+    async fn poll(provider: &CircuitBreakerProvider<MockProvider>) -> bool {
+        provider.get_sms_code(&TaskId::from("1")).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_failure_threshold() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        fail_polls(&mock, 2);
+
+        assert!(!poll(&provider).await);
+        assert_eq!(provider.state(), CircuitState::Closed { failures: 1 });
+        assert!(!poll(&provider).await);
+        assert!(matches!(provider.state(), CircuitState::Open { .. }));
+
+        let err = provider
+            .get_phone_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+        assert!(err.is_open());
+        assert!(!err.is_retryable());
+        assert!(err.should_retry_operation());
+        assert_eq!(err.metric_label(), "circuit_open");
+        assert_eq!(calls(&mock), 2);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_lets_cleanup_through() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        fail_polls(&mock, 2);
+        poll(&provider).await;
+        poll(&provider).await;
+        assert!(matches!(provider.state(), CircuitState::Open { .. }));
+
+        let task_id = TaskId::from("1");
+        provider.cancel_activation(&task_id).await.unwrap();
+        provider.finish_activation(&task_id).await.unwrap();
+        assert!(provider.cancel_all_pending(&[task_id]).await[0].is_ok());
+
+        assert_eq!(calls(&mock), 5);
+        assert!(matches!(provider.state(), CircuitState::Open { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_batch_poll_counts_as_one_call() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        let task_ids = [TaskId::from("1"), TaskId::from("2")];
+        fail_polls(&mock, 4);
+
+        let results = provider.get_sms_codes_batch(&task_ids).await;
+        assert!(results.iter().all(Result::is_err));
+        assert_eq!(provider.state(), CircuitState::Closed { failures: 1 });
+        provider.get_sms_codes_batch(&task_ids).await;
+        assert!(matches!(provider.state(), CircuitState::Open { .. }));
+
+        let results = provider.get_sms_codes_batch(&task_ids).await;
+        assert_eq!(results.len(), 2);
+        assert!(
+            results
+                .iter()
+                .all(|r| r.as_ref().is_err_and(|e| e.is_open()))
+        );
+        assert_eq!(calls(&mock), 4);
+    }
+
+    #[tokio::test]
+    async fn test_business_errors_do_not_open_circuit() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        mock.set_get_number_response(Err(MockError::Permanent("NO_NUMBERS".into())));
+
+        for _ in 0..5 {
+            assert!(
+                provider
+                    .get_phone_number(Alpha2::UA.to_country(), MockService)
+                    .await
+                    .is_err()
+            );
+        }
+
+        assert_eq!(provider.state(), CircuitState::Closed { failures: 0 });
+        assert_eq!(calls(&mock), 5);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+
+        fail_polls(&mock, 1);
+        assert!(!poll(&provider).await);
+        assert!(poll(&provider).await);
+        fail_polls(&mock, 1);
+        assert!(!poll(&provider).await);
+
+        assert_eq!(provider.state(), CircuitState::Closed { failures: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_half_open_closes_after_success_threshold() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        fail_polls(&mock, 2);
+        poll(&provider).await;
+        poll(&provider).await;
+
+        tokio::time::sleep(RESET_TIMEOUT).await;
+
+        assert!(poll(&provider).await);
+        assert_eq!(
+            provider.state(),
+            CircuitState::HalfOpen {
+                successes: 1,
+                probing: false
+            }
+        );
+        assert!(poll(&provider).await);
+        assert_eq!(provider.state(), CircuitState::Closed { failures: 0 });
+        assert_eq!(calls(&mock), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_reopens() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        fail_polls(&mock, 3);
+        poll(&provider).await;
+        poll(&provider).await;
+
+        tokio::time::sleep(RESET_TIMEOUT).await;
+
+        assert!(!poll(&provider).await);
+        assert!(matches!(provider.state(), CircuitState::Open { .. }));
+        assert!(!poll(&provider).await);
+        assert_eq!(calls(&mock), 3);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_allows_one_probe_at_a_time() {
+        let mock = MockProvider::new();
+        let provider = breaker(&mock);
+        fail_polls(&mock, 2);
+        poll(&provider).await;
+        poll(&provider).await;
+
+        tokio::time::sleep(RESET_TIMEOUT).await;
+
+        let probe = provider.acquire().unwrap();
+        assert!(provider.acquire().is_err());
+
+        // An abandoned probe lets the next one through
+        drop(probe);
+        assert!(provider.acquire().is_ok());
+    }
+}
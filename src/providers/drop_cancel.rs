@@ -0,0 +1,604 @@
+//! Provider wrapper that cancels activations when the caller's future is dropped.
+
+use super::traits::{Provider, SupportsIdempotencyKey};
+use crate::errors::RetryableError;
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "tracing")]
+use tracing::{debug, warn};
+
+/// Result of acquiring a number through the inner provider.
+type Acquired<E> = Result<(TaskId, FullNumber, Option<ActivationMetadata>), E>;
+
+/// Errors returned by [`DropCancelProvider`].
+#[derive(Debug, Error)]
+pub enum DropCancelError<E> {
+    /// The background request was aborted before it completed, e.g. because
+    /// the runtime is shutting down.
+    #[error("Background number request was aborted")]
+    Aborted,
+
+    /// The wrapped provider returned an error.
+    #[error(transparent)]
+    Provider(E),
+}
+
+impl<E> DropCancelError<E> {
+    /// Returns the provider error, if the call reached the provider.
+    pub fn into_provider_error(self) -> Option<E> {
+        match self {
+            DropCancelError::Aborted => None,
+            DropCancelError::Provider(e) => Some(e),
+        }
+    }
+}
+
+impl<E: RetryableError> RetryableError for DropCancelError<E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            DropCancelError::Aborted => false,
+            DropCancelError::Provider(e) => e.is_retryable(),
+        }
+    }
+
+    fn should_retry_operation(&self) -> bool {
+        match self {
+            DropCancelError::Aborted => false,
+            DropCancelError::Provider(e) => e.should_retry_operation(),
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            DropCancelError::Aborted => None,
+            DropCancelError::Provider(e) => e.retry_after(),
+        }
+    }
+
+    fn is_provider_failure(&self) -> bool {
+        match self {
+            DropCancelError::Aborted => false,
+            DropCancelError::Provider(e) => e.is_provider_failure(),
+        }
+    }
+
+    fn metric_label(&self) -> &'static str {
+        match self {
+            DropCancelError::Aborted => "aborted",
+            DropCancelError::Provider(e) => e.metric_label(),
+        }
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            DropCancelError::Aborted => None,
+            DropCancelError::Provider(e) => e.live_task_id(),
+        }
+    }
+}
+
+/// Wrapper that cancels activations abandoned by a dropped future.
+///
+/// With [`CancellationToken`](crate::CancellationToken) the caller has to
+/// signal cancellation explicitly. This wrapper instead relies on Rust's
+/// drop-based cancellation: if the future returned by `get_phone_number` is
+/// dropped before it completes (e.g. it lost a `tokio::select!` race or its
+/// task was aborted), the in-flight request keeps running in the background
+/// and the number it acquires is cancelled right away.
+///
+/// Requires a Tokio runtime. Use [`DropCancelProvider::guard`] to extend the
+/// same protection to the rest of your flow once a number is acquired.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::DropCancelProvider;
+///
+/// let provider = DropCancelProvider::new(base_provider);
+///
+/// tokio::select! {
+///     result = provider.get_phone_number(country, service) => { /* use number */ }
+///     _ = shutdown.recv() => { /* number, if any, is cancelled */ }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DropCancelProvider<P: Provider> {
+    inner: Arc<P>,
+}
+
+impl<P: Provider> Clone for DropCancelProvider<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<P: Provider + 'static> DropCancelProvider<P> {
+    /// Wrap a provider.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// Get reference to the inner provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Create a guard that cancels `task_id` when dropped, unless disarmed.
+    pub fn guard(&self, task_id: TaskId) -> DropGuard<P> {
+        DropGuard {
+            task_id: Some(task_id),
+            provider: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<P: Provider + 'static> Provider for DropCancelProvider<P> {
+    type Error = DropCancelError<P::Error>;
+    type Service = P::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        let (task_id, full_number, _) = self
+            .get_phone_number_with_metadata(country, service)
+            .await?;
+        Ok((task_id, full_number))
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        DropGuardFuture::spawn(Arc::clone(&self.inner), country, service, None).await
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        DropGuardFuture::spawn(
+            Arc::clone(&self.inner),
+            country,
+            service,
+            Some(idempotency_key.to_string()),
+        )
+        .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        self.inner
+            .get_phone_number_for_any_country(service)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        self.inner
+            .get_sms_code(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        self.inner
+            .get_sms_code_with_text(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        self.inner
+            .get_all_sms_codes(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        self.inner
+            .get_all_sms_codes_with_text(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        self.inner
+            .get_activation_status(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner
+            .request_another_code(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.inner
+            .finish_activation(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.inner
+            .cancel_activation(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        self.inner
+            .get_sms_codes_batch(task_ids)
+            .await
+            .into_iter()
+            .map(|result| result.map_err(DropCancelError::Provider))
+            .collect()
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        self.inner
+            .cancel_all_pending(task_ids)
+            .await
+            .into_iter()
+            .map(|result| result.map_err(DropCancelError::Provider))
+            .collect()
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner
+            .is_task_alive(task_id)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.inner.warmup().await.map_err(DropCancelError::Provider)
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.inner
+            .account_reputation()
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        self.inner
+            .country_prices(service)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.inner
+            .available_countries_with_counts(service)
+            .await
+            .map_err(DropCancelError::Provider)
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.inner.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.inner.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.inner.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        self.inner.available_countries(service)
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.inner.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.inner.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+impl<P: SupportsIdempotencyKey + 'static> SupportsIdempotencyKey for DropCancelProvider<P> {}
+
+/// Guard that cancels an activation when dropped.
+///
+/// Call [`DropGuard::disarm`] once the activation has been used or finished.
+#[derive(Debug)]
+pub struct DropGuard<P: Provider + 'static> {
+    task_id: Option<TaskId>,
+    provider: Arc<P>,
+}
+
+impl<P: Provider + 'static> DropGuard<P> {
+    /// The guarded activation, if still armed.
+    pub fn task_id(&self) -> Option<&TaskId> {
+        self.task_id.as_ref()
+    }
+
+    /// Stop guarding the activation and return its ID.
+    pub fn disarm(&mut self) -> Option<TaskId> {
+        self.task_id.take()
+    }
+}
+
+impl<P: Provider + 'static> Drop for DropGuard<P> {
+    fn drop(&mut self) {
+        if let Some(task_id) = self.task_id.take() {
+            let provider = Arc::clone(&self.provider);
+            spawn_cleanup(async move {
+                cancel(&*provider, &task_id).await;
+            });
+        }
+    }
+}
+
+/// Future that acquires a number on a background task.
+///
+/// If dropped before completion, the background request is left to finish
+/// and the acquired number is cancelled. Resolves to
+/// [`DropCancelError::Aborted`] if the background request is aborted, e.g.
+/// by a runtime shutdown.
+pub struct DropGuardFuture<P: Provider + 'static> {
+    handle: Option<JoinHandle<Acquired<P::Error>>>,
+    provider: Arc<P>,
+}
+
+impl<P: Provider + 'static> DropGuardFuture<P> {
+    fn spawn(
+        provider: Arc<P>,
+        country: Country,
+        service: P::Service,
+        idempotency_key: Option<String>,
+    ) -> Self {
+        let inner = Arc::clone(&provider);
+        let handle = tokio::spawn(async move {
+            match idempotency_key {
+                Some(key) => {
+                    inner
+                        .get_phone_number_with_idempotency_key(country, service, &key)
+                        .await
+                }
+                None => inner.get_phone_number_with_metadata(country, service).await,
+            }
+        });
+        Self {
+            handle: Some(handle),
+            provider,
+        }
+    }
+}
+
+impl<P: Provider + 'static> Future for DropGuardFuture<P> {
+    type Output = Acquired<DropCancelError<P::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let handle = self
+            .handle
+            .as_mut()
+            .expect("DropGuardFuture polled after completion");
+        let result = ready!(Pin::new(handle).poll(cx));
+        self.handle = None;
+
+        match result {
+            Ok(acquired) => Poll::Ready(acquired.map_err(DropCancelError::Provider)),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => Poll::Ready(Err(DropCancelError::Aborted)),
+        }
+    }
+}
+
+impl<P: Provider + 'static> Drop for DropGuardFuture<P> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let provider = Arc::clone(&self.provider);
+            spawn_cleanup(async move {
+                if let Ok(Ok((task_id, _, _))) = handle.await {
+                    cancel(&*provider, &task_id).await;
+                }
+            });
+        }
+    }
+}
+
+/// Cancel an abandoned activation, logging failures.
+pub(crate) async fn cancel<P: Provider>(provider: &P, task_id: &TaskId) {
+    #[cfg(feature = "tracing")]
+    debug!(task_id = %task_id, "Cancelling abandoned activation");
+
+    if let Err(_e) = provider.cancel_activation(task_id).await {
+        #[cfg(feature = "tracing")]
+        warn!(error = %_e, task_id = %task_id, "Failed to cancel abandoned activation");
+    }
+}
+
+/// Run cleanup on the current Tokio runtime, if any.
+pub(crate) fn spawn_cleanup(cleanup: impl Future<Output = ()> + Send + 'static) {
+    match Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn(cleanup);
+        }
+        Err(_) => {
+            #[cfg(feature = "tracing")]
+            warn!("No Tokio runtime available, abandoned activation was not cancelled");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Error)]
+    #[error("mock error")]
+    struct MockError;
+
+    impl RetryableError for MockError {
+        fn is_retryable(&self) -> bool {
+            false
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SlowProvider {
+        cancelled: Arc<Mutex<Vec<TaskId>>>,
+    }
+
+    impl Provider for SlowProvider {
+        type Error = MockError;
+        type Service = ();
+
+        async fn get_phone_number(
+            &self,
+            _country: Country,
+            _service: Self::Service,
+        ) -> Result<(TaskId, FullNumber), Self::Error> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok((TaskId::from("task123"), FullNumber::from("380501234567")))
+        }
+
+        async fn get_sms_code(&self, _task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn finish_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+            self.cancelled.lock().unwrap().push(task_id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_abort_during_get_phone_number_cancels_activation() {
+        let inner = SlowProvider::default();
+        let provider = DropCancelProvider::new(inner.clone());
+
+        let task =
+            tokio::spawn(
+                async move { provider.get_phone_number(Alpha2::UA.to_country(), ()).await },
+            );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            *inner.cancelled.lock().unwrap(),
+            vec![TaskId::from("task123")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completed_get_phone_number_is_not_cancelled() {
+        let inner = SlowProvider::default();
+        let provider = DropCancelProvider::new(inner.clone());
+
+        let (task_id, full_number) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "task123");
+        assert_eq!(full_number.as_ref(), "380501234567");
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(inner.cancelled.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aborted_request_is_an_error() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let future = {
+            let _entered = runtime.enter();
+            DropGuardFuture::spawn(
+                Arc::new(SlowProvider::default()),
+                Alpha2::UA.to_country(),
+                (),
+                None,
+            )
+        };
+        runtime.shutdown_background();
+
+        let err = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(future)
+            .unwrap_err();
+        assert!(matches!(err, DropCancelError::Aborted));
+        assert!(!err.should_retry_operation());
+    }
+
+    #[tokio::test]
+    async fn test_drop_guard() {
+        let inner = SlowProvider::default();
+        let provider = DropCancelProvider::new(inner.clone());
+
+        let mut guard = provider.guard(TaskId::from("kept"));
+        assert_eq!(guard.disarm(), Some(TaskId::from("kept")));
+        drop(guard);
+
+        let guard = provider.guard(TaskId::from("dropped"));
+        assert_eq!(guard.task_id(), Some(&TaskId::from("dropped")));
+        drop(guard);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            *inner.cancelled.lock().unwrap(),
+            vec![TaskId::from("dropped")]
+        );
+    }
+}
@@ -0,0 +1,1486 @@
+//! Primary/fallback provider composition for tuples.
+//!
+//! [`FallbackProvider`] is a two-provider form that falls back on any
+//! error and routes tasks with a table instead of tagging their IDs.
+//!
+//! `(P1, P2)` and `(P1, P2, P3)` implement [`Provider`] by trying each
+//! provider in order. An error falls through to the next provider only when
+//! it is not retryable ([`RetryableError::is_retryable`] is false) but a
+//! fresh attempt elsewhere might succeed
+//! ([`RetryableError::should_retry_operation`] is true). Retryable errors
+//! such as `NO_NUMBERS` are returned as is; wrap the providers in
+//! [`SmsRetryableProvider`] to retry them. Errors from the last provider are
+//! returned as is.
+//!
+//! Task-bound methods (`get_sms_code`, `finish_activation`, ...) never fall
+//! back. Task IDs issued by a later provider are tagged with its position,
+//! e.g. `fallback1:123` for `P2`, and every task-bound call goes straight to
+//! the provider that issued the task. Task IDs from `P1` are left unchanged
+//! unless they already look tagged, in which case they are tagged with
+//! `fallback0:`. Untagged IDs, e.g. stored before the tuple was introduced,
+//! go to `P1`.
+//!
+//! These are blanket impls over any providers with matching `Service` and
+//! `Error` types. Rust has no impl priority, so they cannot be specialised
+//! for particular provider types; wrap the tuple in your own type to change
+//! the behaviour of a single method.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use sms_solvers::{Provider, SmsRetryableProvider};
+//!
+//! let provider = (
+//!     SmsRetryableProvider::new(primary),
+//!     SmsRetryableProvider::new(backup),
+//! );
+//! let (task_id, number) = provider.get_phone_number(country, service).await?;
+//! ```
+//!
+//! [`SmsRetryableProvider`]: crate::SmsRetryableProvider
+
+use super::traits::Provider;
+use crate::errors::RetryableError;
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use futures::future::join_all;
+use keshvar::Country;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "tracing")]
+use tracing::debug;
+
+/// Prefix of task IDs issued by a provider other than the first.
+const TAG_PREFIX: &str = "fallback";
+
+/// Evaluate provider calls in order, moving on while the error allows it.
+macro_rules! try_in_order {
+    ($method:literal; $last:expr) => {
+        $last
+    };
+    ($method:literal; $first:expr, $($rest:expr),+) => {
+        match $first {
+            Err(e) if !e.is_retryable() && e.should_retry_operation() => {
+                #[cfg(feature = "tracing")]
+                debug!(error = %e, method = $method, "Provider failed, falling back to next provider");
+                try_in_order!($method; $($rest),+)
+            }
+            result => result,
+        }
+    };
+}
+
+/// Parse a tagged task ID into the provider index and the provider's own ID.
+fn parse_tag(task_id: &TaskId, providers: usize) -> Option<(usize, &str)> {
+    let (index, id) = task_id.as_ref().strip_prefix(TAG_PREFIX)?.split_once(':')?;
+    let index = index
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < providers)?;
+    Some((index, id))
+}
+
+/// Split a task ID into the index of the provider that issued it and the
+/// provider's own task ID.
+fn untag(task_id: &TaskId, providers: usize) -> (usize, TaskId) {
+    match parse_tag(task_id, providers) {
+        Some((index, id)) => (index, TaskId::from(id)),
+        None => (0, task_id.clone()),
+    }
+}
+
+/// Tag a task ID with the index of the provider that issued it.
+fn tag(index: usize, task_id: TaskId, providers: usize) -> TaskId {
+    if index == 0 && parse_tag(&task_id, providers).is_none() {
+        return task_id;
+    }
+    TaskId::from(format!("{TAG_PREFIX}{index}:{task_id}"))
+}
+
+/// Acquisition results whose task ID gets tagged.
+trait Tagged {
+    fn tagged(self, index: usize, providers: usize) -> Self;
+}
+
+impl Tagged for (TaskId, FullNumber) {
+    fn tagged(self, index: usize, providers: usize) -> Self {
+        (tag(index, self.0, providers), self.1)
+    }
+}
+
+impl Tagged for (TaskId, FullNumber, Option<ActivationMetadata>) {
+    fn tagged(self, index: usize, providers: usize) -> Self {
+        (tag(index, self.0, providers), self.1, self.2)
+    }
+}
+
+impl Tagged for Option<(TaskId, FullNumber, Country)> {
+    fn tagged(self, index: usize, providers: usize) -> Self {
+        self.map(|(task_id, number, country)| (tag(index, task_id, providers), number, country))
+    }
+}
+
+/// Run a batch call per issuing provider, keeping the input order.
+///
+/// `route` maps a task ID to the index of its provider and the provider's
+/// own task ID.
+async fn batch_grouped<T, E, R, F, Fut>(
+    task_ids: &[TaskId],
+    providers: usize,
+    route: R,
+    run_batch: F,
+) -> Vec<Result<T, E>>
+where
+    R: Fn(&TaskId) -> (usize, TaskId),
+    F: Fn(usize, Vec<TaskId>) -> Fut,
+    Fut: Future<Output = Vec<Result<T, E>>>,
+{
+    let mut batches: Vec<(Vec<usize>, Vec<TaskId>)> = vec![Default::default(); providers];
+    for (position, task_id) in task_ids.iter().enumerate() {
+        let (index, task_id) = route(task_id);
+        batches[index].0.push(position);
+        batches[index].1.push(task_id);
+    }
+
+    let mut results: Vec<Option<Result<T, E>>> = task_ids.iter().map(|_| None).collect();
+    let batch_results = join_all(
+        batches
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, ids))| !ids.is_empty())
+            .map(|(index, (positions, ids))| {
+                let batch = run_batch(index, ids.clone());
+                async move { (positions, batch.await) }
+            }),
+    )
+    .await;
+    for (positions, batch) in batch_results {
+        for (position, result) in positions.iter().zip(batch) {
+            results[*position] = Some(result);
+        }
+    }
+    results.into_iter().flatten().collect()
+}
+
+/// Collapse results into the first error, if any.
+fn first_error<E>(results: impl IntoIterator<Item = Result<(), E>>) -> Result<(), E> {
+    results.into_iter().collect()
+}
+
+/// Append countries from `extra` that are not in `countries` yet.
+fn merge_countries(mut countries: Vec<Country>, extra: Vec<Country>) -> Vec<Country> {
+    for country in extra {
+        if !countries.iter().any(|c| c.alpha2() == country.alpha2()) {
+            countries.push(country);
+        }
+    }
+    countries
+}
+
+impl<P1, P2> Provider for (P1, P2)
+where
+    P1: Provider,
+    P2: Provider<Service = P1::Service, Error = P1::Error>,
+{
+    type Error = P1::Error;
+    type Service = P1::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        try_in_order!(
+            "get_phone_number";
+            self.0.get_phone_number(country.clone(), service.clone()).await.map(|r| r.tagged(0, 2)),
+            self.1.get_phone_number(country, service).await.map(|r| r.tagged(1, 2))
+        )
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        try_in_order!(
+            "get_phone_number_with_metadata";
+            self.0.get_phone_number_with_metadata(country.clone(), service.clone()).await.map(|r| r.tagged(0, 2)),
+            self.1.get_phone_number_with_metadata(country, service).await.map(|r| r.tagged(1, 2))
+        )
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        try_in_order!(
+            "get_phone_number_with_idempotency_key";
+            self.0.get_phone_number_with_idempotency_key(country.clone(), service.clone(), idempotency_key).await.map(|r| r.tagged(0, 2)),
+            self.1.get_phone_number_with_idempotency_key(country, service, idempotency_key).await.map(|r| r.tagged(1, 2))
+        )
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        try_in_order!(
+            "get_phone_number_for_any_country";
+            self.0.get_phone_number_for_any_country(service.clone()).await.map(|r| r.tagged(0, 2)),
+            self.1.get_phone_number_for_any_country(service).await.map(|r| r.tagged(1, 2))
+        )
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.get_sms_code(&id).await,
+            (_, id) => self.1.get_sms_code(&id).await,
+        }
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.get_sms_code_with_text(&id).await,
+            (_, id) => self.1.get_sms_code_with_text(&id).await,
+        }
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.get_all_sms_codes(&id).await,
+            (_, id) => self.1.get_all_sms_codes(&id).await,
+        }
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.get_all_sms_codes_with_text(&id).await,
+            (_, id) => self.1.get_all_sms_codes_with_text(&id).await,
+        }
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.finish_activation(&id).await,
+            (_, id) => self.1.finish_activation(&id).await,
+        }
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.cancel_activation(&id).await,
+            (_, id) => self.1.cancel_activation(&id).await,
+        }
+    }
+
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        batch_grouped(
+            task_ids,
+            2,
+            |task_id| untag(task_id, 2),
+            |index, ids| async move {
+                match index {
+                    0 => self.0.get_sms_codes_batch(&ids).await,
+                    _ => self.1.get_sms_codes_batch(&ids).await,
+                }
+            },
+        )
+        .await
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        batch_grouped(
+            task_ids,
+            2,
+            |task_id| untag(task_id, 2),
+            |index, ids| async move {
+                match index {
+                    0 => self.0.cancel_all_pending(&ids).await,
+                    _ => self.1.cancel_all_pending(&ids).await,
+                }
+            },
+        )
+        .await
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.is_task_alive(&id).await,
+            (_, id) => self.1.is_task_alive(&id).await,
+        }
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.get_activation_status(&id).await,
+            (_, id) => self.1.get_activation_status(&id).await,
+        }
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        match untag(task_id, 2) {
+            (0, id) => self.0.request_another_code(&id).await,
+            (_, id) => self.1.request_another_code(&id).await,
+        }
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        let (first, second) = futures::join!(self.0.warmup(), self.1.warmup());
+        first_error([first, second])
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        try_in_order!(
+            "account_reputation";
+            self.0.account_reputation().await,
+            self.1.account_reputation().await
+        )
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        try_in_order!(
+            "country_prices";
+            self.0.country_prices(service.clone()).await,
+            self.1.country_prices(service).await
+        )
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        try_in_order!(
+            "available_countries_with_counts";
+            self.0.available_countries_with_counts(service).await,
+            self.1.available_countries_with_counts(service).await
+        )
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        let report = self.0.health_report().await;
+        if report.is_healthy {
+            return report;
+        }
+        self.1.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.0.is_dial_code_supported(dial_code) || self.1.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.0.supports_service(service) || self.1.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        merge_countries(
+            self.0.available_countries(service),
+            self.1.available_countries(service),
+        )
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.0.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.0.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.0.provider_kind()
+    }
+}
+
+impl<P1, P2, P3> Provider for (P1, P2, P3)
+where
+    P1: Provider,
+    P2: Provider<Service = P1::Service, Error = P1::Error>,
+    P3: Provider<Service = P1::Service, Error = P1::Error>,
+{
+    type Error = P1::Error;
+    type Service = P1::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        try_in_order!(
+            "get_phone_number";
+            self.0.get_phone_number(country.clone(), service.clone()).await.map(|r| r.tagged(0, 3)),
+            self.1.get_phone_number(country.clone(), service.clone()).await.map(|r| r.tagged(1, 3)),
+            self.2.get_phone_number(country, service).await.map(|r| r.tagged(2, 3))
+        )
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        try_in_order!(
+            "get_phone_number_with_metadata";
+            self.0.get_phone_number_with_metadata(country.clone(), service.clone()).await.map(|r| r.tagged(0, 3)),
+            self.1.get_phone_number_with_metadata(country.clone(), service.clone()).await.map(|r| r.tagged(1, 3)),
+            self.2.get_phone_number_with_metadata(country, service).await.map(|r| r.tagged(2, 3))
+        )
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        try_in_order!(
+            "get_phone_number_with_idempotency_key";
+            self.0.get_phone_number_with_idempotency_key(country.clone(), service.clone(), idempotency_key).await.map(|r| r.tagged(0, 3)),
+            self.1.get_phone_number_with_idempotency_key(country.clone(), service.clone(), idempotency_key).await.map(|r| r.tagged(1, 3)),
+            self.2.get_phone_number_with_idempotency_key(country, service, idempotency_key).await.map(|r| r.tagged(2, 3))
+        )
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        try_in_order!(
+            "get_phone_number_for_any_country";
+            self.0.get_phone_number_for_any_country(service.clone()).await.map(|r| r.tagged(0, 3)),
+            self.1.get_phone_number_for_any_country(service.clone()).await.map(|r| r.tagged(1, 3)),
+            self.2.get_phone_number_for_any_country(service).await.map(|r| r.tagged(2, 3))
+        )
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.get_sms_code(&id).await,
+            (1, id) => self.1.get_sms_code(&id).await,
+            (_, id) => self.2.get_sms_code(&id).await,
+        }
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.get_sms_code_with_text(&id).await,
+            (1, id) => self.1.get_sms_code_with_text(&id).await,
+            (_, id) => self.2.get_sms_code_with_text(&id).await,
+        }
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.get_all_sms_codes(&id).await,
+            (1, id) => self.1.get_all_sms_codes(&id).await,
+            (_, id) => self.2.get_all_sms_codes(&id).await,
+        }
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.get_all_sms_codes_with_text(&id).await,
+            (1, id) => self.1.get_all_sms_codes_with_text(&id).await,
+            (_, id) => self.2.get_all_sms_codes_with_text(&id).await,
+        }
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.finish_activation(&id).await,
+            (1, id) => self.1.finish_activation(&id).await,
+            (_, id) => self.2.finish_activation(&id).await,
+        }
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.cancel_activation(&id).await,
+            (1, id) => self.1.cancel_activation(&id).await,
+            (_, id) => self.2.cancel_activation(&id).await,
+        }
+    }
+
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        batch_grouped(
+            task_ids,
+            3,
+            |task_id| untag(task_id, 3),
+            |index, ids| async move {
+                match index {
+                    0 => self.0.get_sms_codes_batch(&ids).await,
+                    1 => self.1.get_sms_codes_batch(&ids).await,
+                    _ => self.2.get_sms_codes_batch(&ids).await,
+                }
+            },
+        )
+        .await
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        batch_grouped(
+            task_ids,
+            3,
+            |task_id| untag(task_id, 3),
+            |index, ids| async move {
+                match index {
+                    0 => self.0.cancel_all_pending(&ids).await,
+                    1 => self.1.cancel_all_pending(&ids).await,
+                    _ => self.2.cancel_all_pending(&ids).await,
+                }
+            },
+        )
+        .await
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.is_task_alive(&id).await,
+            (1, id) => self.1.is_task_alive(&id).await,
+            (_, id) => self.2.is_task_alive(&id).await,
+        }
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.get_activation_status(&id).await,
+            (1, id) => self.1.get_activation_status(&id).await,
+            (_, id) => self.2.get_activation_status(&id).await,
+        }
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        match untag(task_id, 3) {
+            (0, id) => self.0.request_another_code(&id).await,
+            (1, id) => self.1.request_another_code(&id).await,
+            (_, id) => self.2.request_another_code(&id).await,
+        }
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        let (first, second, third) =
+            futures::join!(self.0.warmup(), self.1.warmup(), self.2.warmup());
+        first_error([first, second, third])
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        try_in_order!(
+            "account_reputation";
+            self.0.account_reputation().await,
+            self.1.account_reputation().await,
+            self.2.account_reputation().await
+        )
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        try_in_order!(
+            "country_prices";
+            self.0.country_prices(service.clone()).await,
+            self.1.country_prices(service.clone()).await,
+            self.2.country_prices(service).await
+        )
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        try_in_order!(
+            "available_countries_with_counts";
+            self.0.available_countries_with_counts(service).await,
+            self.1.available_countries_with_counts(service).await,
+            self.2.available_countries_with_counts(service).await
+        )
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        let report = self.0.health_report().await;
+        if report.is_healthy {
+            return report;
+        }
+        let report = self.1.health_report().await;
+        if report.is_healthy {
+            return report;
+        }
+        self.2.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.0.is_dial_code_supported(dial_code)
+            || self.1.is_dial_code_supported(dial_code)
+            || self.2.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.0.supports_service(service)
+            || self.1.supports_service(service)
+            || self.2.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        let countries = merge_countries(
+            self.0.available_countries(service),
+            self.1.available_countries(service),
+        );
+        merge_countries(countries, self.2.available_countries(service))
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.0.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.0.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.0.provider_kind()
+    }
+}
+
+/// Which provider of a [`FallbackProvider`] issued a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WhichProvider {
+    Primary,
+    Fallback,
+}
+
+/// Whether an error from the primary provider sends the request on to the
+/// fallback provider.
+///
+/// Unlike the tuples, [`FallbackProvider`] falls back on every error. A
+/// non-retryable error may not apply to the other provider (e.g. a bad API
+/// key), and a retryable error that reaches this layer means the primary
+/// provider's own retries, if any, are used up; this includes
+/// [`SmsRetryableError::RetryBudgetExhausted`](crate::SmsRetryableError::RetryBudgetExhausted).
+fn should_fall_back<E: RetryableError>(_error: &E) -> bool {
+    true
+}
+
+/// Try the primary acquisition, falling back when [`should_fall_back`]
+/// allows it.
+///
+/// Evaluates to the acquired value and the provider that issued it.
+macro_rules! acquire {
+    ($method:literal; $primary:expr, $fallback:expr) => {
+        match $primary {
+            Ok(acquired) => (acquired, WhichProvider::Primary),
+            Err(e) if should_fall_back(&e) => {
+                #[cfg(feature = "tracing")]
+                debug!(error = %e, method = $method, "Primary provider failed, using fallback provider");
+                ($fallback?, WhichProvider::Fallback)
+            }
+            Err(e) => return Err(e),
+        }
+    };
+}
+
+/// Call a task-bound method on the provider that issued the task.
+macro_rules! routed {
+    ($self:ident, $task_id:expr, $method:ident($($arg:expr),*)) => {
+        match $self.issued_by($task_id) {
+            WhichProvider::Primary => $self.primary.$method($($arg),*).await,
+            WhichProvider::Fallback => $self.fallback.$method($($arg),*).await,
+        }
+    };
+}
+
+/// Provider that acquires numbers from `A` and falls back to `B`.
+///
+/// Any error from `A` when acquiring a number, non-retryable or left over
+/// after `A`'s own retries, makes the request go to `B` instead. Errors from
+/// `B` are returned as is. Wrap `A` in [`SmsRetryableProvider`] to retry it
+/// before falling back.
+///
+/// Each acquired task is remembered along with the provider that issued it,
+/// so `get_sms_code`, `finish_activation`, `cancel_activation` and the other
+/// task-bound methods go straight to that provider; task IDs are returned
+/// unchanged. Tasks are forgotten once finished or cancelled. Unknown task
+/// IDs, e.g. from before a restart, are sent to `A`. Clones share the
+/// routing table.
+///
+/// Both providers must issue distinct task IDs; a task ID issued by both is
+/// routed to whichever issued it last.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::{FallbackProvider, Provider, SmsRetryableProvider};
+///
+/// let provider = FallbackProvider::new(SmsRetryableProvider::new(eu), us);
+/// let (task_id, number) = provider.get_phone_number(country, service).await?;
+/// // Polled on whichever provider issued the number
+/// let code = provider.get_sms_code(&task_id).await?;
+/// ```
+///
+/// [`SmsRetryableProvider`]: crate::SmsRetryableProvider
+#[derive(Debug)]
+pub struct FallbackProvider<A, B> {
+    primary: A,
+    fallback: B,
+    tasks: Arc<Mutex<HashMap<TaskId, WhichProvider>>>,
+}
+
+impl<A: Clone, B: Clone> Clone for FallbackProvider<A, B> {
+    fn clone(&self) -> Self {
+        Self {
+            primary: self.primary.clone(),
+            fallback: self.fallback.clone(),
+            tasks: Arc::clone(&self.tasks),
+        }
+    }
+}
+
+impl<A, B> FallbackProvider<A, B> {
+    /// Combine a primary provider with a fallback provider.
+    pub fn new(primary: A, fallback: B) -> Self {
+        Self {
+            primary,
+            fallback,
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get reference to the primary provider.
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Get reference to the fallback provider.
+    pub fn fallback(&self) -> &B {
+        &self.fallback
+    }
+
+    /// Number of tasks currently being routed.
+    pub fn tracked_tasks(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<TaskId, WhichProvider>> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record(&self, task_id: &TaskId, which: WhichProvider) {
+        self.lock().insert(task_id.clone(), which);
+    }
+
+    fn forget(&self, task_id: &TaskId) {
+        self.lock().remove(task_id);
+    }
+
+    fn issued_by(&self, task_id: &TaskId) -> WhichProvider {
+        self.lock()
+            .get(task_id)
+            .copied()
+            .unwrap_or(WhichProvider::Primary)
+    }
+
+    /// Index of the provider that issued each task, for [`batch_grouped`].
+    fn route(&self, task_id: &TaskId) -> (usize, TaskId) {
+        match self.issued_by(task_id) {
+            WhichProvider::Primary => (0, task_id.clone()),
+            WhichProvider::Fallback => (1, task_id.clone()),
+        }
+    }
+}
+
+impl<A, B> Provider for FallbackProvider<A, B>
+where
+    A: Provider,
+    B: Provider<Service = A::Service, Error = A::Error>,
+{
+    type Error = A::Error;
+    type Service = A::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        let ((task_id, full_number), which) = acquire!(
+            "get_phone_number";
+            self.primary.get_phone_number(country.clone(), service.clone()).await,
+            self.fallback.get_phone_number(country, service).await
+        );
+        self.record(&task_id, which);
+        Ok((task_id, full_number))
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        let ((task_id, full_number, metadata), which) = acquire!(
+            "get_phone_number_with_metadata";
+            self.primary.get_phone_number_with_metadata(country.clone(), service.clone()).await,
+            self.fallback.get_phone_number_with_metadata(country, service).await
+        );
+        self.record(&task_id, which);
+        Ok((task_id, full_number, metadata))
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        let ((task_id, full_number, metadata), which) = acquire!(
+            "get_phone_number_with_idempotency_key";
+            self.primary.get_phone_number_with_idempotency_key(country.clone(), service.clone(), idempotency_key).await,
+            self.fallback.get_phone_number_with_idempotency_key(country, service, idempotency_key).await
+        );
+        self.record(&task_id, which);
+        Ok((task_id, full_number, metadata))
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        let (acquired, which) = match self
+            .primary
+            .get_phone_number_for_any_country(service.clone())
+            .await
+        {
+            Ok(Some(acquired)) => (Some(acquired), WhichProvider::Primary),
+            Err(e) if !should_fall_back(&e) => return Err(e),
+            _ => (
+                self.fallback
+                    .get_phone_number_for_any_country(service)
+                    .await?,
+                WhichProvider::Fallback,
+            ),
+        };
+        if let Some((task_id, _, _)) = &acquired {
+            self.record(task_id, which);
+        }
+        Ok(acquired)
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        routed!(self, task_id, get_sms_code(task_id))
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        routed!(self, task_id, get_sms_code_with_text(task_id))
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        routed!(self, task_id, get_all_sms_codes(task_id))
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        routed!(self, task_id, get_all_sms_codes_with_text(task_id))
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        routed!(self, task_id, get_activation_status(task_id))
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        routed!(self, task_id, request_another_code(task_id))
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        routed!(self, task_id, finish_activation(task_id))?;
+        self.forget(task_id);
+        Ok(())
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        routed!(self, task_id, cancel_activation(task_id))?;
+        self.forget(task_id);
+        Ok(())
+    }
+
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        batch_grouped(
+            task_ids,
+            2,
+            |task_id| self.route(task_id),
+            |index, ids| async move {
+                match index {
+                    0 => self.primary.get_sms_codes_batch(&ids).await,
+                    _ => self.fallback.get_sms_codes_batch(&ids).await,
+                }
+            },
+        )
+        .await
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        let results = batch_grouped(
+            task_ids,
+            2,
+            |task_id| self.route(task_id),
+            |index, ids| async move {
+                match index {
+                    0 => self.primary.cancel_all_pending(&ids).await,
+                    _ => self.fallback.cancel_all_pending(&ids).await,
+                }
+            },
+        )
+        .await;
+        for (task_id, result) in task_ids.iter().zip(&results) {
+            if result.is_ok() {
+                self.forget(task_id);
+            }
+        }
+        results
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        routed!(self, task_id, is_task_alive(task_id))
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        let (primary, fallback) = futures::join!(self.primary.warmup(), self.fallback.warmup());
+        first_error([primary, fallback])
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.primary.account_reputation().await
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        match self.primary.country_prices(service.clone()).await {
+            Err(e) if should_fall_back(&e) => self.fallback.country_prices(service).await,
+            result => result,
+        }
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        match self.primary.available_countries_with_counts(service).await {
+            Err(e) if should_fall_back(&e) => {
+                self.fallback.available_countries_with_counts(service).await
+            }
+            result => result,
+        }
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        let report = self.primary.health_report().await;
+        if report.is_healthy {
+            return report;
+        }
+        self.fallback.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.primary.is_dial_code_supported(dial_code)
+            || self.fallback.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.primary.supports_service(service) || self.fallback.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        merge_countries(
+            self.primary.available_countries(service),
+            self.fallback.available_countries(service),
+        )
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.primary.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.primary.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.primary.provider_kind()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use thiserror::Error;
+
+    #[derive(Debug, Clone, Error)]
+    enum MockError {
+        /// Out of stock for good; another provider might have numbers.
+        #[error("NO_NUMBERS")]
+        NoNumbers,
+        /// Worth retrying on the same provider.
+        #[error("ERROR_SQL")]
+        Transient,
+        #[error("BAD_KEY")]
+        BadKey,
+    }
+
+    impl RetryableError for MockError {
+        fn is_retryable(&self) -> bool {
+            matches!(self, MockError::Transient)
+        }
+
+        fn should_retry_operation(&self) -> bool {
+            matches!(self, MockError::NoNumbers | MockError::Transient)
+        }
+    }
+
+    #[derive(Clone)]
+    struct MockProvider {
+        name: &'static str,
+        error: Option<MockError>,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl MockProvider {
+        fn ok(name: &'static str) -> Self {
+            Self {
+                name,
+                error: None,
+                calls: Arc::new(AtomicU32::new(0)),
+            }
+        }
+
+        fn failing(name: &'static str, error: MockError) -> Self {
+            Self {
+                error: Some(error),
+                ..Self::ok(name)
+            }
+        }
+
+        fn result<T>(&self, value: T) -> Result<T, MockError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match &self.error {
+                Some(error) => Err(error.clone()),
+                None => Ok(value),
+            }
+        }
+    }
+
+    impl Provider for MockProvider {
+        type Error = MockError;
+        type Service = ();
+
+        async fn get_phone_number(
+            &self,
+            _country: Country,
+            _service: Self::Service,
+        ) -> Result<(TaskId, FullNumber), Self::Error> {
+            self.result((TaskId::from(self.name), FullNumber::from("380501234567")))
+        }
+
+        async fn get_sms_code(&self, _task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+            self.result(Some(SmsCode::from(self.name)))
+        }
+
+        async fn finish_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+            self.result(())
+        }
+
+        async fn cancel_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+            self.result(())
+        }
+
+        async fn warmup(&self) -> Result<(), Self::Error> {
+            self.result(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pair_falls_back_on_no_numbers() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::ok("backup");
+        let provider = (primary.clone(), backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "fallback1:backup");
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pair_does_not_fall_back_on_retryable_error() {
+        let primary = MockProvider::failing("primary", MockError::Transient);
+        let backup = MockProvider::ok("backup");
+        let provider = (primary, backup.clone());
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+
+        assert!(matches!(result, Err(MockError::Transient)));
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pair_routes_task_calls_to_issuing_provider() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::ok("backup");
+        let provider = (primary.clone(), backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        let code = provider.get_sms_code(&task_id).await.unwrap();
+        provider.finish_activation(&task_id).await.unwrap();
+
+        assert_eq!(code.unwrap().as_str(), "backup");
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pair_task_calls_never_fall_back() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::ok("backup");
+        let provider = (primary, backup.clone());
+
+        let result = provider.cancel_activation(&TaskId::from("1")).await;
+
+        assert!(matches!(result, Err(MockError::NoNumbers)));
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pair_cancel_all_pending_routes_each_task() {
+        let primary = MockProvider::ok("primary");
+        let backup = MockProvider::failing("backup", MockError::BadKey);
+        let provider = (primary.clone(), backup.clone());
+
+        let results = provider
+            .cancel_all_pending(&[
+                TaskId::from("fallback1:2"),
+                TaskId::from("1"),
+                TaskId::from("3"),
+            ])
+            .await;
+
+        assert!(matches!(results[0], Err(MockError::BadKey)));
+        assert!(results[1].is_ok() && results[2].is_ok());
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pair_warms_up_every_provider() {
+        let primary = MockProvider::ok("primary");
+        let backup = MockProvider::failing("backup", MockError::BadKey);
+        let provider = (primary.clone(), backup.clone());
+
+        assert!(matches!(provider.warmup().await, Err(MockError::BadKey)));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_task_id_tagging() {
+        assert_eq!(tag(0, TaskId::from("123"), 2).as_ref(), "123");
+        assert_eq!(tag(1, TaskId::from("123"), 2).as_ref(), "fallback1:123");
+        // Primary IDs that look tagged are escaped, e.g. from a nested pair
+        let nested = tag(0, TaskId::from("fallback1:123"), 2);
+        assert_eq!(nested.as_ref(), "fallback0:fallback1:123");
+
+        assert_eq!(untag(&nested, 2), (0, TaskId::from("fallback1:123")));
+        assert_eq!(
+            untag(&TaskId::from("fallback1:123"), 2),
+            (1, TaskId::from("123"))
+        );
+        assert_eq!(
+            untag(&TaskId::from("fallback2:123"), 2),
+            (0, TaskId::from("fallback2:123"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pair_prefers_primary() {
+        let primary = MockProvider::ok("primary");
+        let backup = MockProvider::ok("backup");
+        let provider = (primary, backup.clone());
+
+        let code = provider.get_sms_code(&TaskId::from("1")).await.unwrap();
+
+        assert_eq!(code.unwrap().as_str(), "primary");
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pair_does_not_fall_back_on_permanent_error() {
+        let primary = MockProvider::failing("primary", MockError::BadKey);
+        let backup = MockProvider::ok("backup");
+        let provider = (primary, backup.clone());
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+
+        assert!(matches!(result, Err(MockError::BadKey)));
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_triple_falls_back_to_last() {
+        let provider = (
+            MockProvider::failing("first", MockError::NoNumbers),
+            MockProvider::failing("second", MockError::NoNumbers),
+            MockProvider::ok("third"),
+        );
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "fallback2:third");
+        assert_eq!(
+            provider
+                .get_sms_code(&task_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "third"
+        );
+
+        let provider = (
+            MockProvider::failing("first", MockError::NoNumbers),
+            MockProvider::failing("second", MockError::NoNumbers),
+            MockProvider::failing("third", MockError::NoNumbers),
+        );
+        assert!(matches!(
+            provider.cancel_activation(&TaskId::from("1")).await,
+            Err(MockError::NoNumbers)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_routes_to_issuing_provider() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary.clone(), backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "backup");
+        assert_eq!(provider.tracked_tasks(), 1);
+
+        let code = provider.get_sms_code(&task_id).await.unwrap();
+        assert_eq!(code.unwrap().as_str(), "backup");
+        provider.finish_activation(&task_id).await.unwrap();
+
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(provider.tracked_tasks(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_prefers_primary() {
+        let primary = MockProvider::ok("primary");
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary.clone(), backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "primary");
+
+        let code = provider.get_sms_code(&task_id).await.unwrap();
+        assert_eq!(code.unwrap().as_str(), "primary");
+        provider.cancel_activation(&task_id).await.unwrap();
+
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+        assert_eq!(provider.tracked_tasks(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_returns_fallback_error() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::failing("backup", MockError::BadKey);
+        let provider = FallbackProvider::new(primary, backup.clone());
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+
+        assert!(matches!(result, Err(MockError::BadKey)));
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(provider.tracked_tasks(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_falls_back_on_retryable_error() {
+        let primary = MockProvider::failing("primary", MockError::Transient);
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary, backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "backup");
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_falls_back_on_permanent_error() {
+        let primary = MockProvider::failing("primary", MockError::BadKey);
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary, backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "backup");
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_sends_unknown_tasks_to_primary() {
+        let primary = MockProvider::ok("primary");
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary.clone(), backup.clone());
+
+        let code = provider.get_sms_code(&TaskId::from("1")).await.unwrap();
+
+        assert_eq!(code.unwrap().as_str(), "primary");
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_cancel_all_pending_routes_each_task() {
+        let primary = MockProvider::failing("primary", MockError::NoNumbers);
+        let backup = MockProvider::ok("backup");
+        let provider = FallbackProvider::new(primary.clone(), backup.clone());
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        let results = provider
+            .cancel_all_pending(&[TaskId::from("1"), task_id])
+            .await;
+
+        assert!(matches!(results[0], Err(MockError::NoNumbers)));
+        assert!(results[1].is_ok());
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(provider.tracked_tasks(), 0);
+    }
+
+    #[cfg(feature = "hero-sms")]
+    #[tokio::test]
+    async fn test_fallback_provider_falls_back_on_hero_no_numbers() {
+        use crate::providers::hero_sms::{HeroSmsError, HeroSmsErrorCode, HeroSmsServiceError};
+
+        /// Provider with the Hero error type, out of stock unless `in_stock`.
+        #[derive(Clone)]
+        struct HeroStock {
+            in_stock: bool,
+        }
+
+        impl Provider for HeroStock {
+            type Error = HeroSmsError;
+            type Service = ();
+
+            async fn get_phone_number(
+                &self,
+                _country: Country,
+                _service: Self::Service,
+            ) -> Result<(TaskId, FullNumber), Self::Error> {
+                if self.in_stock {
+                    return Ok((TaskId::from("backup"), FullNumber::from("380501234567")));
+                }
+                Err(HeroSmsError::Service(HeroSmsServiceError::new(
+                    HeroSmsErrorCode::NoNumbers,
+                    "NO_NUMBERS".to_string(),
+                )))
+            }
+
+            async fn get_sms_code(
+                &self,
+                _task_id: &TaskId,
+            ) -> Result<Option<SmsCode>, Self::Error> {
+                Ok(None)
+            }
+
+            async fn finish_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            async fn cancel_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        let provider =
+            FallbackProvider::new(HeroStock { in_stock: false }, HeroStock { in_stock: true });
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "backup");
+        assert_eq!(provider.tracked_tasks(), 1);
+    }
+}
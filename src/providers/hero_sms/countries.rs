@@ -1,5 +1,6 @@
 //! Country code mapping for Hero SMS API.
 
+use crate::utils::{ProviderKind, is_sanctioned_country};
 use keshvar::{Alpha2, Country, CountryIterator};
 use once_cell::sync::Lazy;
 use serde_json::Value;
@@ -71,6 +72,7 @@ static NAME_OVERRIDES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(||
         // Abbreviations
         ("salvador", "SV"),
         ("papua", "PG"),
+        ("uk", "GB"),
         // Diacritics removed
         ("reunion", "RE"),
         // Region codes
@@ -92,6 +94,22 @@ static ISO_NAME2ALPHA2: Lazy<HashMap<String, Alpha2>> = Lazy::new(|| {
     m
 });
 
+/// Look up a country by name.
+///
+/// The name is normalized with [`norm`], then matched against
+/// [`NAME_OVERRIDES`] first and ISO short names second.
+pub(crate) fn country_from_name(name: &str) -> Option<Country> {
+    let key = norm(name);
+
+    // 1) First check overrides for known name differences
+    if let Some(&alpha2_str) = NAME_OVERRIDES.get(key.as_str()) {
+        return Country::try_from(alpha2_str).ok();
+    }
+
+    // 2) Try to match against ISO standard name()
+    ISO_NAME2ALPHA2.get(&key).map(|alpha2| alpha2.to_country())
+}
+
 /// Mapping from Hero SMS country IDs to Country.
 /// Built from hero_sms_countries.json at startup.
 pub static SMS_ID2COUNTRY: Lazy<HashMap<u16, Country>> = Lazy::new(|| {
@@ -108,22 +126,11 @@ pub static SMS_ID2COUNTRY: Lazy<HashMap<u16, Country>> = Lazy::new(|| {
             continue;
         };
 
-        let key = norm(name);
-
-        // 1) First check overrides for known name differences
-        if let Some(&alpha2_str) = NAME_OVERRIDES.get(key.as_str())
-            && let Ok(country) = Country::try_from(alpha2_str)
-        {
+        if let Some(country) = country_from_name(name) {
             map.insert(id, country);
             continue;
         }
 
-        // 2) Try to match against ISO standard name()
-        if let Some(&alpha2) = ISO_NAME2ALPHA2.get(&key) {
-            map.insert(id, alpha2.to_country());
-            continue;
-        }
-
         // If no match found, skip but could log for debugging
         #[cfg(feature = "tracing")]
         tracing::debug!("No ISO match for SMS country name: '{name}' (id={id})");
@@ -141,6 +148,134 @@ pub static COUNTRY2SMS_ID: Lazy<HashMap<String, u16>> = Lazy::new(|| {
     m
 });
 
+/// Default country map built from the embedded Hero SMS assets.
+static DEFAULT_COUNTRY_MAP: Lazy<CountryMap> = Lazy::new(|| CountryMap {
+    id_to_country: SMS_ID2COUNTRY.clone(),
+    country_to_id: SMS_ID2COUNTRY
+        .iter()
+        .map(|(id, country)| (country.alpha2().to_string(), *id))
+        .collect(),
+});
+
+/// Bidirectional mapping between countries and Hero SMS country IDs.
+///
+/// The default map is built from the embedded `hero_sms_countries.json`
+/// asset. Use [`CountryMap::builder`] to fix individual mappings without
+/// patching the asset.
+#[derive(Debug, Clone)]
+pub struct CountryMap {
+    id_to_country: HashMap<u16, Country>,
+    country_to_id: HashMap<String, u16>,
+}
+
+impl CountryMap {
+    /// Create a builder starting from the default mappings.
+    pub fn builder() -> HeroSmsCountryMapBuilder {
+        HeroSmsCountryMapBuilder::new()
+    }
+
+    /// Get the Hero SMS country ID for a country.
+    pub fn sms_id(&self, country: &Country) -> Result<u16, CountryMapError> {
+        self.country_to_id
+            .get(&country.alpha2().to_string())
+            .copied()
+            .ok_or_else(|| CountryMapError::NoSmsMapping {
+                country: Box::new(country.clone()),
+            })
+    }
+
+    /// Get the country for a Hero SMS country ID.
+    pub fn country(&self, id: u16) -> Result<Country, CountryMapError> {
+        self.id_to_country
+            .get(&id)
+            .cloned()
+            .ok_or(CountryMapError::UnknownSmsId { id })
+    }
+
+    /// All countries that have a Hero SMS mapping.
+    pub fn countries(&self) -> Vec<Country> {
+        self.id_to_country.values().cloned().collect()
+    }
+
+    /// Number of mapped countries.
+    pub fn len(&self) -> usize {
+        self.id_to_country.len()
+    }
+
+    /// Returns true if no countries are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.id_to_country.is_empty()
+    }
+}
+
+impl Default for CountryMap {
+    fn default() -> Self {
+        DEFAULT_COUNTRY_MAP.clone()
+    }
+}
+
+/// Builder for a [`CountryMap`] with user-supplied overrides.
+///
+/// # Example
+///
+/// ```rust
+/// use sms_solvers::Alpha2;
+/// use sms_solvers::hero_sms::CountryMap;
+///
+/// let map = CountryMap::builder()
+///     .override_mapping(Alpha2::UA.to_country(), 999)
+///     .remove_mapping(Alpha2::GB.to_country())
+///     .build();
+///
+/// assert_eq!(map.sms_id(&Alpha2::UA.to_country()).unwrap(), 999);
+/// assert!(map.sms_id(&Alpha2::GB.to_country()).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeroSmsCountryMapBuilder {
+    map: CountryMap,
+}
+
+impl HeroSmsCountryMapBuilder {
+    /// Create a builder starting from the default mappings.
+    pub fn new() -> Self {
+        Self {
+            map: CountryMap::default(),
+        }
+    }
+
+    /// Map `country` to `sms_id`, replacing any existing mapping for either.
+    pub fn override_mapping(self, country: Country, sms_id: u16) -> Self {
+        let mut builder = self.remove_mapping(country.clone());
+        let map = &mut builder.map;
+        if let Some(previous) = map.id_to_country.remove(&sms_id) {
+            map.country_to_id.remove(&previous.alpha2().to_string());
+        }
+        map.country_to_id
+            .insert(country.alpha2().to_string(), sms_id);
+        map.id_to_country.insert(sms_id, country);
+        builder
+    }
+
+    /// Remove the mapping for `country`.
+    pub fn remove_mapping(mut self, country: Country) -> Self {
+        if let Some(id) = self.map.country_to_id.remove(&country.alpha2().to_string()) {
+            self.map.id_to_country.remove(&id);
+        }
+        self
+    }
+
+    /// Build the [`CountryMap`].
+    pub fn build(self) -> CountryMap {
+        self.map
+    }
+}
+
+impl Default for HeroSmsCountryMapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Extension trait for country code mapping.
 pub trait SmsCountryExt {
     /// Get the Hero SMS country ID for this country.
@@ -148,6 +283,19 @@ pub trait SmsCountryExt {
 
     /// Get the Country for a Hero SMS ID.
     fn from_sms_id(id: u16) -> Result<Country, CountryMapError>;
+
+    /// Get the Country for a user-typed name such as `"germany"` or `"uk"`.
+    ///
+    /// Matching ignores case and punctuation; returns `None` if nothing matches.
+    fn try_from_name(name: &str) -> Option<Country>;
+
+    /// Returns true if the country is under sanctions.
+    fn is_sanctioned(&self) -> bool;
+
+    /// Returns true if the given provider cannot serve this country.
+    ///
+    /// Sanctioned countries are restricted for every provider.
+    fn is_restricted_for_provider(&self, provider: ProviderKind) -> bool;
 }
 
 impl SmsCountryExt for Country {
@@ -166,6 +314,28 @@ impl SmsCountryExt for Country {
             .cloned()
             .ok_or(CountryMapError::UnknownSmsId { id })
     }
+
+    fn try_from_name(name: &str) -> Option<Country> {
+        country_from_name(name)
+    }
+
+    fn is_sanctioned(&self) -> bool {
+        is_sanctioned_country(self)
+    }
+
+    fn is_restricted_for_provider(&self, provider: ProviderKind) -> bool {
+        if self.is_sanctioned() {
+            return true;
+        }
+
+        match provider {
+            ProviderKind::HeroSms => self.sms_id().is_err(),
+            ProviderKind::PhoneBook
+            | ProviderKind::FiveSim
+            | ProviderKind::OnlineSim
+            | ProviderKind::Custom => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -331,6 +501,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_default_country_map_matches_statics() {
+        let map = CountryMap::default();
+        assert_eq!(map.len(), SMS_ID2COUNTRY.len());
+        assert_eq!(map.sms_id(&Alpha2::UA.to_country()).unwrap(), 1);
+        assert_eq!(map.country(187).unwrap().alpha2(), Alpha2::US);
+    }
+
+    #[test]
+    fn test_country_map_builder_override() {
+        let map = CountryMap::builder()
+            .override_mapping(Alpha2::UA.to_country(), 16)
+            .build();
+
+        // UA takes over GB's id; GB loses its mapping, UA's old id is freed
+        assert_eq!(map.sms_id(&Alpha2::UA.to_country()).unwrap(), 16);
+        assert_eq!(map.country(16).unwrap().alpha2(), Alpha2::UA);
+        assert!(map.sms_id(&Alpha2::GB.to_country()).is_err());
+        assert!(map.country(1).is_err());
+        assert_eq!(map.len(), SMS_ID2COUNTRY.len() - 1);
+    }
+
+    #[test]
+    fn test_country_map_builder_remove() {
+        let map = CountryMap::builder()
+            .remove_mapping(Alpha2::US.to_country())
+            .remove_mapping(Alpha2::AQ.to_country())
+            .build();
+
+        assert!(map.sms_id(&Alpha2::US.to_country()).is_err());
+        assert!(map.country(187).is_err());
+        assert_eq!(map.len(), SMS_ID2COUNTRY.len() - 1);
+    }
+
     #[test]
     fn test_error_display() {
         let err1 = CountryMapError::UnknownSmsId { id: 12345 };
@@ -358,4 +562,37 @@ mod tests {
             "hero_sms_countries.json should not be empty"
         );
     }
+
+    #[test]
+    fn test_try_from_name() {
+        let cases = [
+            ("Germany", Alpha2::DE),
+            ("germany", Alpha2::DE),
+            ("UK", Alpha2::GB),
+            ("U.S.A.", Alpha2::US),
+            ("south  korea", Alpha2::KR),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(
+                Country::try_from_name(name).map(|c| c.alpha2()),
+                Some(expected),
+                "{name}"
+            );
+        }
+
+        assert!(Country::try_from_name("germny").is_none());
+        assert!(Country::try_from_name("").is_none());
+    }
+
+    #[test]
+    fn test_country_restrictions() {
+        let cuba = Alpha2::CU.to_country();
+        assert!(cuba.is_sanctioned());
+        assert!(cuba.is_restricted_for_provider(ProviderKind::PhoneBook));
+
+        let ukraine = Alpha2::UA.to_country();
+        assert!(!ukraine.is_sanctioned());
+        assert!(!ukraine.is_restricted_for_provider(ProviderKind::HeroSms));
+        assert!(!ukraine.is_restricted_for_provider(ProviderKind::PhoneBook));
+    }
 }
@@ -73,6 +73,29 @@ impl HeroSmsErrorCode {
         }
     }
 
+    /// Returns a stable snake_case label for metric attributes.
+    ///
+    /// All unknown codes share the `"unknown"` label.
+    pub fn metric_label(&self) -> &'static str {
+        match self {
+            Self::NoNumbers => "no_numbers",
+            Self::ErrorSql => "error_sql",
+            Self::ChannelsLimit => "channels_limit",
+            Self::NoActivation => "no_activation",
+            Self::BadKey => "bad_key",
+            Self::BadAction => "bad_action",
+            Self::OrderAlreadyExists => "order_already_exists",
+            Self::BadService => "bad_service",
+            Self::WrongExceptionPhone => "wrong_exception_phone",
+            Self::Banned { .. } => "banned",
+            Self::WrongMaxPrice { .. } => "wrong_max_price",
+            Self::EarlyCancelDenied => "early_cancel_denied",
+            Self::BadStatus => "bad_status",
+            Self::WrongActivationId => "wrong_activation_id",
+            Self::Unknown { .. } => "unknown",
+        }
+    }
+
     /// Returns human-readable description.
     pub fn description(&self) -> String {
         match self {
@@ -183,6 +206,11 @@ impl HeroSmsErrorCode {
         matches!(self, Self::NoNumbers | Self::ErrorSql | Self::ChannelsLimit)
     }
 
+    /// Returns true if the activation does not exist or its ID is invalid.
+    pub fn is_activation_specific(&self) -> bool {
+        matches!(self, Self::NoActivation | Self::WrongActivationId)
+    }
+
     /// Returns true if a fresh operation might succeed.
     pub fn should_retry_operation(&self) -> bool {
         match self {
@@ -204,6 +232,43 @@ impl HeroSmsErrorCode {
             Self::Unknown { .. } => false,
         }
     }
+
+    /// Returns the broad category of this error code.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::BadKey | Self::Banned { .. } => ErrorCategory::AccountIssue,
+            Self::NoNumbers | Self::ErrorSql | Self::ChannelsLimit => {
+                ErrorCategory::TemporarilyUnavailable
+            }
+            Self::BadAction
+            | Self::OrderAlreadyExists
+            | Self::BadService
+            | Self::WrongExceptionPhone
+            | Self::WrongMaxPrice { .. }
+            | Self::EarlyCancelDenied
+            | Self::BadStatus => ErrorCategory::BadRequest,
+            Self::NoActivation | Self::WrongActivationId => ErrorCategory::ActivationExpired,
+            Self::Unknown { .. } => ErrorCategory::Unknown,
+        }
+    }
+}
+
+/// Broad category of a [`HeroSmsError`], for routing errors without matching
+/// every variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The account is misconfigured or blocked, e.g. a bad API key or a ban.
+    AccountIssue,
+    /// No capacity right now; the same request may succeed later.
+    TemporarilyUnavailable,
+    /// The request itself was rejected and will not succeed as is.
+    BadRequest,
+    /// The request did not complete or the response could not be read.
+    NetworkFailure,
+    /// The activation no longer exists or its ID is invalid.
+    ActivationExpired,
+    /// Anything else.
+    Unknown,
 }
 
 impl Display for HeroSmsErrorCode {
@@ -241,16 +306,26 @@ pub struct HeroSmsServiceError {
     pub description: String,
     /// Original raw response text.
     pub raw: String,
+    /// Delay to wait before retrying.
+    ///
+    /// Set for `CHANNELS_LIMIT`, which gives no hint of its own; the client
+    /// fills in the `channels_limit_delay` it was built with.
+    pub retry_after: Option<Duration>,
 }
 
 impl HeroSmsServiceError {
     /// Create new service error from code and raw response.
+    ///
+    /// `CHANNELS_LIMIT` errors get a retry delay of 60 seconds.
     pub fn new(code: HeroSmsErrorCode, raw: String) -> Self {
         let description = code.description();
+        let retry_after =
+            (code == HeroSmsErrorCode::ChannelsLimit).then_some(DEFAULT_CHANNELS_LIMIT_DELAY);
         Self {
             code,
             description,
             raw,
+            retry_after,
         }
     }
 }
@@ -278,6 +353,19 @@ pub enum HeroSmsError {
     #[error("Failed to build HTTP client: {0}")]
     BuildHttpClient(#[source] reqwest::Error),
 
+    /// Invalid TLS certificate supplied to the client builder.
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    /// The API endpoint URL is malformed or cannot be used as a base URL.
+    #[error("Invalid endpoint URL '{url}': {source}")]
+    InvalidEndpointUrl {
+        /// The offending URL.
+        url: String,
+        #[source]
+        source: url::ParseError,
+    },
+
     /// Error building Hero SMS request URL.
     #[error("Error building Hero SMS request URL: {0}")]
     BuildRequestUrl(#[source] serde_urlencoded::ser::Error),
@@ -286,6 +374,13 @@ pub enum HeroSmsError {
     #[error("Failed to send HTTP request: {0}")]
     HttpRequest(#[from] reqwest_middleware::Error),
 
+    /// The API answered with HTTP 429 Too Many Requests.
+    #[error("Rate limited by Hero SMS API (HTTP 429)")]
+    TooManyRequests {
+        /// Delay from the `Retry-After` header, if present and valid.
+        retry_after: Option<Duration>,
+    },
+
     /// Failed to parse response.
     #[error("Failed to parse response: {0}")]
     ParseResponse(#[source] reqwest::Error),
@@ -294,6 +389,17 @@ pub enum HeroSmsError {
     #[error("Hero SMS service error: {0}")]
     Service(#[source] HeroSmsServiceError),
 
+    /// The `maxPrice` limit is below the current price of the number.
+    ///
+    /// Built from a `WRONG_MAX_PRICE` response that reports the minimum.
+    #[error("Maximum price is below the allowed minimum of {minimum}")]
+    MaxPriceTooLow {
+        /// The limit sent with the request, if known.
+        requested: Option<f64>,
+        /// The lowest limit the service accepts.
+        minimum: f64,
+    },
+
     /// Timeout waiting for SMS.
     #[error(
         "Timeout waiting for SMS after {:.1}s; Task id: {task_id}",
@@ -311,11 +417,270 @@ pub enum HeroSmsError {
 
     /// Failed to deserialize JSON response.
     #[error("Failed to deserialize JSON response: {0}")]
-    DeserializeJson(#[source] serde_json::Error),
+    DeserializeJson(#[from] serde_json::Error),
+
+    /// I/O error, e.g. from file-based workflows built around the client.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to parse getBalance response.
+    #[error("Failed to parse getBalance response: {raw}")]
+    FailedToParseBalanceResponse { raw: String },
+
+    /// Failed to parse v1 getNumber response.
+    #[error("Failed to parse getNumber response: {raw}")]
+    FailedToParseNumberResponse { raw: String },
+
+    /// The country code of a getNumberV2 response matches no single country.
+    #[error("Failed to resolve country code of getNumber response: {raw}")]
+    FailedToParseCountryCode { raw: String },
+
+    /// Failed to parse v1 getStatus response.
+    #[error("Failed to parse getStatus response: {raw}")]
+    FailedToParseStatusResponse { raw: String },
+
+    /// Required environment variable is not set.
+    #[error("Environment variable {name} is not set")]
+    MissingEnvVar { name: String },
+
+    /// Environment variable has an invalid value.
+    #[error("Environment variable {name} has invalid value '{value}'")]
+    InvalidEnvVar { name: String, value: String },
+
+    /// Every number received had a blacklisted prefix.
+    #[error("All {attempts} numbers had a blacklisted prefix")]
+    AllNumbersBlacklisted {
+        /// Number of requests made.
+        attempts: u32,
+        /// Activations that were cancelled successfully.
+        task_ids_cancelled: Vec<TaskId>,
+        /// Activations whose cancellation failed; they are still held.
+        task_ids_not_cancelled: Vec<TaskId>,
+    },
+
+    /// The service code is not in the list of supported services.
+    #[error("Service code '{code}' is not supported by Hero SMS")]
+    UnsupportedService { code: String },
+
+    /// The account balance is below the provider's configured minimum.
+    #[error("Balance {balance} is below the required minimum {required}")]
+    InsufficientBalance { balance: f64, required: f64 },
+
+    /// The call forwarding number is not a valid E.164 number.
+    #[error("Forwarding number '{number}' is not a valid E.164 number")]
+    InvalidForwardNumber { number: String },
+
+    /// The API rejected call forwarding for the requested service.
+    #[error("Call forwarding is not supported for service '{service}': {raw}")]
+    ForwardingNotSupported { service: String, raw: String },
+
+    /// A follow-up action failed after the activation was acquired.
+    ///
+    /// The activation is still alive; use `task_id` to cancel it.
+    #[error("{action} failed for live activation {task_id}: {source}")]
+    PartialSuccess {
+        action: &'static str,
+        task_id: TaskId,
+        #[source]
+        source: Box<HeroSmsServiceError>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, HeroSmsError>;
 
+/// Delay assumed for `CHANNELS_LIMIT` unless configured otherwise.
+pub(crate) const DEFAULT_CHANNELS_LIMIT_DELAY: Duration = Duration::from_secs(60);
+
+impl HeroSmsError {
+    /// Get the inner service error, if this is a [`HeroSmsError::Service`].
+    ///
+    /// Also returns the underlying error of a [`HeroSmsError::PartialSuccess`].
+    pub fn as_service_error(&self) -> Option<&HeroSmsServiceError> {
+        match self {
+            HeroSmsError::Service(error) => Some(error),
+            HeroSmsError::PartialSuccess { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Get the service error code, if this is a [`HeroSmsError::Service`].
+    pub fn as_service_code(&self) -> Option<&HeroSmsErrorCode> {
+        self.as_service_error().map(|error| &error.code)
+    }
+
+    /// Get the inner HTTP error, if this is a [`HeroSmsError::HttpRequest`].
+    pub fn as_http_error(&self) -> Option<&reqwest_middleware::Error> {
+        match self {
+            HeroSmsError::HttpRequest(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Get the underlying [`reqwest::Error`], if any.
+    ///
+    /// Covers [`HeroSmsError::HttpRequest`] failures raised by reqwest itself
+    /// and [`HeroSmsError::ParseResponse`]. Errors raised by middleware
+    /// return `None`.
+    pub fn as_reqwest_error(&self) -> Option<&reqwest::Error> {
+        match self {
+            HeroSmsError::HttpRequest(reqwest_middleware::Error::Reqwest(error))
+            | HeroSmsError::ParseResponse(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the request timed out.
+    pub fn is_timeout_error(&self) -> bool {
+        self.as_reqwest_error()
+            .is_some_and(reqwest::Error::is_timeout)
+    }
+
+    /// Returns true if connecting to the API failed.
+    pub fn is_connection_error(&self) -> bool {
+        self.as_reqwest_error()
+            .is_some_and(reqwest::Error::is_connect)
+    }
+
+    /// Get the delay to wait before retrying a rate-limited request.
+    ///
+    /// Returns the `Retry-After` value of an HTTP 429 response, or the
+    /// [`HeroSmsServiceError::retry_after`] delay of a `CHANNELS_LIMIT`
+    /// service error (60 seconds unless configured otherwise).
+    pub fn rate_limit_delay(&self) -> Option<Duration> {
+        if let Some(error) = self.as_service_error() {
+            return error.retry_after;
+        }
+
+        match self {
+            HeroSmsError::TooManyRequests { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Returns a stable snake_case label for metric attributes.
+    ///
+    /// Service errors use the label of their error code, e.g. `"no_numbers"`.
+    pub fn to_metric_label(&self) -> &'static str {
+        match self {
+            HeroSmsError::Service(error) => error.code.metric_label(),
+            HeroSmsError::PartialSuccess { source, .. } => source.code.metric_label(),
+            HeroSmsError::MaxPriceTooLow { .. } => "wrong_max_price",
+            HeroSmsError::AllNumbersBlacklisted { .. } => "all_numbers_blacklisted",
+            HeroSmsError::UnsupportedService { .. } => "unsupported_service",
+            HeroSmsError::InvalidForwardNumber { .. } => "invalid_forward_number",
+            HeroSmsError::InsufficientBalance { .. } => "insufficient_balance",
+            HeroSmsError::ForwardingNotSupported { .. } => "forwarding_not_supported",
+            HeroSmsError::BuildHttpClient(_) => "build_http_client",
+            HeroSmsError::InvalidCertificate(_) => "invalid_certificate",
+            HeroSmsError::InvalidEndpointUrl { .. } => "invalid_endpoint_url",
+            HeroSmsError::BuildRequestUrl(_) => "build_request_url",
+            HeroSmsError::HttpRequest(_) => "http_request",
+            HeroSmsError::TooManyRequests { .. } => "too_many_requests",
+            HeroSmsError::ParseResponse(_) => "parse_response",
+            HeroSmsError::SolutionTimeout { .. } => "solution_timeout",
+            HeroSmsError::CountryMapping { .. } => "country_mapping",
+            HeroSmsError::FailedToParseSetStatusResponse { .. } => "parse_set_status_response",
+            HeroSmsError::DeserializeJson(_) => "deserialize_json",
+            HeroSmsError::Io(_) => "io",
+            HeroSmsError::FailedToParseBalanceResponse { .. } => "parse_balance_response",
+            HeroSmsError::FailedToParseNumberResponse { .. } => "parse_number_response",
+            HeroSmsError::FailedToParseCountryCode { .. } => "parse_country_code",
+            HeroSmsError::FailedToParseStatusResponse { .. } => "parse_status_response",
+            HeroSmsError::MissingEnvVar { .. } => "missing_env_var",
+            HeroSmsError::InvalidEnvVar { .. } => "invalid_env_var",
+        }
+    }
+
+    /// Returns the broad category of this error.
+    pub fn classify(&self) -> ErrorCategory {
+        match self {
+            HeroSmsError::Service(error) => error.code.category(),
+            HeroSmsError::PartialSuccess { source, .. } => source.code.category(),
+            HeroSmsError::HttpRequest(_) | HeroSmsError::ParseResponse(_) | HeroSmsError::Io(_) => {
+                ErrorCategory::NetworkFailure
+            }
+            HeroSmsError::TooManyRequests { .. }
+            | HeroSmsError::SolutionTimeout { .. }
+            | HeroSmsError::AllNumbersBlacklisted { .. } => ErrorCategory::TemporarilyUnavailable,
+            HeroSmsError::MaxPriceTooLow { .. }
+            | HeroSmsError::CountryMapping { .. }
+            | HeroSmsError::UnsupportedService { .. }
+            | HeroSmsError::InvalidForwardNumber { .. }
+            | HeroSmsError::ForwardingNotSupported { .. }
+            | HeroSmsError::InvalidEndpointUrl { .. }
+            | HeroSmsError::BuildRequestUrl(_) => ErrorCategory::BadRequest,
+            HeroSmsError::MissingEnvVar { .. }
+            | HeroSmsError::InvalidEnvVar { .. }
+            | HeroSmsError::InsufficientBalance { .. } => ErrorCategory::AccountIssue,
+            HeroSmsError::BuildHttpClient(_)
+            | HeroSmsError::InvalidCertificate(_)
+            | HeroSmsError::FailedToParseSetStatusResponse { .. }
+            | HeroSmsError::DeserializeJson(_)
+            | HeroSmsError::FailedToParseBalanceResponse { .. }
+            | HeroSmsError::FailedToParseNumberResponse { .. }
+            | HeroSmsError::FailedToParseCountryCode { .. }
+            | HeroSmsError::FailedToParseStatusResponse { .. } => ErrorCategory::Unknown,
+        }
+    }
+
+    /// Returns true if the service reported no numbers available.
+    pub fn is_no_numbers(&self) -> bool {
+        matches!(self.as_service_code(), Some(HeroSmsErrorCode::NoNumbers))
+    }
+
+    /// Returns true if the account hit its channel quota (`CHANNELS_LIMIT`).
+    ///
+    /// The Hero SMS API returns this from `getNumber` when the account has
+    /// too many activations open. It clears only as activations finish or
+    /// are cancelled, so it calls for a longer back-off than
+    /// [`is_inventory_issue`](Self::is_inventory_issue).
+    pub fn is_quota_related(&self) -> bool {
+        matches!(
+            self.as_service_code(),
+            Some(HeroSmsErrorCode::ChannelsLimit)
+        )
+    }
+
+    /// Returns true if no numbers are in stock (`NO_NUMBERS`).
+    ///
+    /// The Hero SMS API returns this from `getNumber` when the country and
+    /// service have no free numbers. Stock is replenished continuously, so
+    /// retrying quickly is worthwhile.
+    pub fn is_inventory_issue(&self) -> bool {
+        self.is_no_numbers()
+    }
+
+    /// Returns true if the service rejected the API key.
+    pub fn is_bad_key(&self) -> bool {
+        matches!(self.as_service_code(), Some(HeroSmsErrorCode::BadKey))
+    }
+
+    /// Returns true if the service reported that the activation does not
+    /// exist (`NO_ACTIVATION`) or that its ID is invalid (`WRONG_ACTIVATION_ID`).
+    ///
+    /// Unlike network errors, retrying the same task will not help.
+    pub fn is_activation_specific(&self) -> bool {
+        self.as_service_code()
+            .is_some_and(HeroSmsErrorCode::is_activation_specific)
+    }
+
+    /// Wrap this error with the operation that produced it.
+    pub fn with_context(self, context: &'static str) -> ContextualHeroSmsError {
+        ContextualHeroSmsError {
+            context,
+            inner: self,
+        }
+    }
+
+    /// Returns true if the account is banned.
+    pub fn is_banned(&self) -> bool {
+        matches!(
+            self.as_service_code(),
+            Some(HeroSmsErrorCode::Banned { .. })
+        )
+    }
+}
+
 impl RetryableError for HeroSmsError {
     fn is_retryable(&self) -> bool {
         match self {
@@ -323,13 +688,32 @@ impl RetryableError for HeroSmsError {
             HeroSmsError::Service(error) => error.code.is_retryable(),
             // Retryable HTTP/network errors
             HeroSmsError::HttpRequest(_) => true,
+            // Rate limited - retry after the requested delay
+            HeroSmsError::TooManyRequests { .. } => true,
+            // I/O errors are often transient
+            HeroSmsError::Io(_) => true,
             // Non-retryable errors - permanent configuration or logic errors
-            HeroSmsError::BuildHttpClient(_)
+            HeroSmsError::PartialSuccess { .. }
+            | HeroSmsError::MaxPriceTooLow { .. }
+            | HeroSmsError::AllNumbersBlacklisted { .. }
+            | HeroSmsError::UnsupportedService { .. }
+            | HeroSmsError::InvalidForwardNumber { .. }
+            | HeroSmsError::ForwardingNotSupported { .. }
+            | HeroSmsError::InsufficientBalance { .. }
+            | HeroSmsError::BuildHttpClient(_)
+            | HeroSmsError::InvalidCertificate(_)
+            | HeroSmsError::InvalidEndpointUrl { .. }
             | HeroSmsError::BuildRequestUrl(_)
             | HeroSmsError::ParseResponse(_)
             | HeroSmsError::SolutionTimeout { .. }
             | HeroSmsError::CountryMapping { .. }
             | HeroSmsError::FailedToParseSetStatusResponse { .. }
+            | HeroSmsError::FailedToParseBalanceResponse { .. }
+            | HeroSmsError::FailedToParseNumberResponse { .. }
+            | HeroSmsError::FailedToParseCountryCode { .. }
+            | HeroSmsError::FailedToParseStatusResponse { .. }
+            | HeroSmsError::MissingEnvVar { .. }
+            | HeroSmsError::InvalidEnvVar { .. }
             | HeroSmsError::DeserializeJson(_) => false,
         }
     }
@@ -338,19 +722,164 @@ impl RetryableError for HeroSmsError {
         match self {
             // Service errors have their own logic
             HeroSmsError::Service(error) => error.code.should_retry_operation(),
-            // HTTP errors - retry the operation
-            HeroSmsError::HttpRequest(_) => true,
+            // HTTP and I/O errors - retry the operation
+            HeroSmsError::HttpRequest(_) | HeroSmsError::Io(_) => true,
+            // Rate limited - the operation can be repeated later
+            HeroSmsError::TooManyRequests { .. } => true,
             // Timeouts - fresh attempt might work
             HeroSmsError::SolutionTimeout { .. } => true,
+            // Follow-up failures - depends on the underlying service error
+            HeroSmsError::PartialSuccess { source, .. } => source.code.should_retry_operation(),
             // Configuration errors - won't work until fixed
-            HeroSmsError::BuildHttpClient(_)
+            HeroSmsError::MaxPriceTooLow { .. }
+            | HeroSmsError::AllNumbersBlacklisted { .. }
+            | HeroSmsError::UnsupportedService { .. }
+            | HeroSmsError::InvalidForwardNumber { .. }
+            | HeroSmsError::ForwardingNotSupported { .. }
+            | HeroSmsError::InsufficientBalance { .. }
+            | HeroSmsError::BuildHttpClient(_)
+            | HeroSmsError::InvalidCertificate(_)
+            | HeroSmsError::InvalidEndpointUrl { .. }
             | HeroSmsError::BuildRequestUrl(_)
             | HeroSmsError::ParseResponse(_)
             | HeroSmsError::CountryMapping { .. }
             | HeroSmsError::FailedToParseSetStatusResponse { .. }
+            | HeroSmsError::FailedToParseBalanceResponse { .. }
+            | HeroSmsError::FailedToParseNumberResponse { .. }
+            | HeroSmsError::FailedToParseCountryCode { .. }
+            | HeroSmsError::FailedToParseStatusResponse { .. }
+            | HeroSmsError::MissingEnvVar { .. }
+            | HeroSmsError::InvalidEnvVar { .. }
             | HeroSmsError::DeserializeJson(_) => false,
         }
     }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.rate_limit_delay()
+    }
+
+    fn is_provider_failure(&self) -> bool {
+        match self.as_service_code() {
+            Some(code) => *code == HeroSmsErrorCode::ErrorSql,
+            None => self.classify() == ErrorCategory::NetworkFailure,
+        }
+    }
+
+    fn metric_label(&self) -> &'static str {
+        self.to_metric_label()
+    }
+
+    /// The task ID of a [`HeroSmsError::PartialSuccess`].
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            HeroSmsError::PartialSuccess { task_id, .. } => Some(task_id),
+            _ => None,
+        }
+    }
+}
+
+/// A [`HeroSmsError`] annotated with the operation that produced it.
+///
+/// Created via [`HeroSmsError::with_context`] or [`ResultExt::context`].
+/// Displays as `"{context}: {inner}"` and delegates everything else
+/// (source chain, retry classification) to the inner error.
+#[derive(Debug)]
+pub struct ContextualHeroSmsError {
+    context: &'static str,
+    inner: HeroSmsError,
+}
+
+impl ContextualHeroSmsError {
+    /// The operation context attached to this error.
+    pub fn context(&self) -> &'static str {
+        self.context
+    }
+
+    /// Get a reference to the wrapped error.
+    pub fn inner(&self) -> &HeroSmsError {
+        &self.inner
+    }
+
+    /// Unwrap into the underlying error, discarding the context.
+    pub fn into_inner(self) -> HeroSmsError {
+        self.inner
+    }
+}
+
+impl Display for ContextualHeroSmsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.inner)
+    }
+}
+
+impl std::error::Error for ContextualHeroSmsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl RetryableError for ContextualHeroSmsError {
+    fn is_retryable(&self) -> bool {
+        self.inner.is_retryable()
+    }
+
+    fn should_retry_operation(&self) -> bool {
+        self.inner.should_retry_operation()
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.inner.retry_after()
+    }
+
+    fn is_provider_failure(&self) -> bool {
+        self.inner.is_provider_failure()
+    }
+
+    fn metric_label(&self) -> &'static str {
+        self.inner.metric_label()
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        self.inner.live_task_id()
+    }
+}
+
+impl From<HeroSmsServiceError> for HeroSmsError {
+    fn from(error: HeroSmsServiceError) -> Self {
+        match error.code {
+            HeroSmsErrorCode::WrongMaxPrice { min: Some(minimum) } => {
+                HeroSmsError::MaxPriceTooLow {
+                    requested: None,
+                    minimum,
+                }
+            }
+            _ => HeroSmsError::Service(error),
+        }
+    }
+}
+
+impl From<reqwest::Error> for HeroSmsError {
+    fn from(error: reqwest::Error) -> Self {
+        HeroSmsError::HttpRequest(reqwest_middleware::Error::Reqwest(error))
+    }
+}
+
+impl From<ContextualHeroSmsError> for HeroSmsError {
+    fn from(error: ContextualHeroSmsError) -> Self {
+        error.inner
+    }
+}
+
+/// Extension trait for attaching operation context to [`HeroSmsError`] results.
+pub trait ResultExt<T> {
+    /// Wrap the error, if any, with the given operation context.
+    fn context(self, ctx: &'static str) -> std::result::Result<T, ContextualHeroSmsError>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn context(self, ctx: &'static str) -> std::result::Result<T, ContextualHeroSmsError> {
+        self.map_err(|error| error.with_context(ctx))
+    }
 }
 
 #[cfg(test)]
@@ -423,4 +952,352 @@ mod tests {
         assert!(!HeroSmsErrorCode::BadKey.is_retryable());
         assert!(!HeroSmsErrorCode::NoActivation.is_retryable());
     }
+
+    #[tokio::test]
+    async fn test_reqwest_error_accessors() {
+        // Bind and drop a listener to get a local port that refuses connections
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let error = HeroSmsError::from(
+            reqwest::get(format!("http://{addr}"))
+                .await
+                .expect_err("the listener is closed"),
+        );
+        assert!(error.as_reqwest_error().is_some());
+        assert!(error.is_connection_error());
+        assert!(!error.is_timeout_error());
+
+        let error = HeroSmsError::HttpRequest(reqwest_middleware::Error::middleware(
+            std::io::Error::other("middleware failure"),
+        ));
+        assert!(error.as_http_error().is_some());
+        assert!(error.as_reqwest_error().is_none());
+        assert!(!error.is_connection_error());
+    }
+
+    #[test]
+    fn test_error_accessors() {
+        let error = HeroSmsError::Service(parse_hero_sms_error("NO_NUMBERS").unwrap());
+        assert_eq!(error.as_service_code(), Some(&HeroSmsErrorCode::NoNumbers));
+        assert_eq!(error.as_service_error().unwrap().raw, "NO_NUMBERS");
+        assert!(error.as_http_error().is_none());
+        assert!(error.is_no_numbers());
+        assert!(error.is_inventory_issue());
+        assert!(!error.is_quota_related());
+        assert!(!error.is_bad_key());
+        assert!(!error.is_banned());
+
+        let error = HeroSmsError::Service(parse_hero_sms_error("CHANNELS_LIMIT").unwrap());
+        assert!(error.is_quota_related());
+        assert!(!error.is_inventory_issue());
+
+        let error = HeroSmsError::Service(parse_hero_sms_error("BAD_KEY").unwrap());
+        assert!(error.is_bad_key());
+
+        let error =
+            HeroSmsError::Service(parse_hero_sms_error("BANNED:'2025-12-31 23:59:59'").unwrap());
+        assert!(error.is_banned());
+
+        let error = HeroSmsError::FailedToParseSetStatusResponse {
+            raw: "???".to_string(),
+        };
+        assert!(error.as_service_error().is_none());
+        assert!(error.as_service_code().is_none());
+        assert!(!error.is_no_numbers());
+    }
+
+    #[test]
+    fn test_error_context() {
+        let error = HeroSmsError::Service(parse_hero_sms_error("NO_NUMBERS").unwrap())
+            .with_context("get_phone_number");
+        assert_eq!(error.context(), "get_phone_number");
+        assert!(error.to_string().starts_with("get_phone_number: Hero SMS"));
+        assert!(error.is_retryable());
+        assert!(error.inner().is_no_numbers());
+
+        let result: Result<()> = Err(HeroSmsError::FailedToParseBalanceResponse {
+            raw: "???".to_string(),
+        });
+        let error = result.context("get_balance").unwrap_err();
+        assert_eq!(error.context(), "get_balance");
+        assert!(!error.is_retryable());
+        assert!(!error.should_retry_operation());
+        assert!(matches!(
+            HeroSmsError::from(error),
+            HeroSmsError::FailedToParseBalanceResponse { .. }
+        ));
+
+        let ok: Result<u8> = Ok(1);
+        assert_eq!(ok.context("unused").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_from_io_and_json_errors() {
+        fn read_transcript() -> Result<()> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "disk hiccup",
+            ))?;
+            Ok(())
+        }
+
+        let error = read_transcript().unwrap_err();
+        assert!(matches!(error, HeroSmsError::Io(_)));
+        assert_eq!(error.to_string(), "I/O error: disk hiccup");
+        assert!(error.is_retryable());
+        assert!(error.should_retry_operation());
+
+        let json_error = serde_json::from_str::<u8>("not json").unwrap_err();
+        let error = HeroSmsError::from(json_error);
+        assert!(matches!(error, HeroSmsError::DeserializeJson(_)));
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_to_metric_label() {
+        let error = HeroSmsError::Service(HeroSmsServiceError::new(
+            HeroSmsErrorCode::NoNumbers,
+            "NO_NUMBERS".to_string(),
+        ));
+        assert_eq!(error.to_metric_label(), "no_numbers");
+        assert_eq!(
+            error.with_context("get number").metric_label(),
+            "no_numbers"
+        );
+
+        let error = HeroSmsError::MissingEnvVar {
+            name: "HERO_SMS_API_KEY".to_string(),
+        };
+        assert_eq!(error.to_metric_label(), "missing_env_var");
+
+        let error = HeroSmsErrorCode::Unknown {
+            raw: "SOMETHING NEW".to_string(),
+        };
+        assert_eq!(error.metric_label(), "unknown");
+    }
+
+    #[test]
+    fn test_rate_limit_delay() {
+        let error = HeroSmsError::Service(HeroSmsServiceError::new(
+            HeroSmsErrorCode::ChannelsLimit,
+            "CHANNELS_LIMIT".to_string(),
+        ));
+        assert_eq!(error.rate_limit_delay(), Some(Duration::from_secs(60)));
+        assert_eq!(
+            error.with_context("get number").retry_after(),
+            Some(Duration::from_secs(60))
+        );
+
+        let error = HeroSmsError::TooManyRequests {
+            retry_after: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(error.rate_limit_delay(), Some(Duration::from_secs(7)));
+        assert!(error.is_retryable());
+        assert!(error.should_retry_operation());
+        assert_eq!(error.metric_label(), "too_many_requests");
+
+        let error = HeroSmsError::TooManyRequests { retry_after: None };
+        assert_eq!(error.rate_limit_delay(), None);
+        assert!(error.is_retryable());
+
+        let error = HeroSmsError::Service(HeroSmsServiceError::new(
+            HeroSmsErrorCode::NoNumbers,
+            "NO_NUMBERS".to_string(),
+        ));
+        assert_eq!(error.rate_limit_delay(), None);
+    }
+
+    fn reqwest_error() -> reqwest::Error {
+        reqwest::Client::new()
+            .get("http://[invalid")
+            .build()
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_error_code_category() {
+        let cases = vec![
+            (
+                HeroSmsErrorCode::NoNumbers,
+                ErrorCategory::TemporarilyUnavailable,
+            ),
+            (
+                HeroSmsErrorCode::ErrorSql,
+                ErrorCategory::TemporarilyUnavailable,
+            ),
+            (
+                HeroSmsErrorCode::ChannelsLimit,
+                ErrorCategory::TemporarilyUnavailable,
+            ),
+            (
+                HeroSmsErrorCode::NoActivation,
+                ErrorCategory::ActivationExpired,
+            ),
+            (
+                HeroSmsErrorCode::WrongActivationId,
+                ErrorCategory::ActivationExpired,
+            ),
+            (HeroSmsErrorCode::BadKey, ErrorCategory::AccountIssue),
+            (
+                HeroSmsErrorCode::Banned {
+                    until: "2025-01-01 00:00:00".to_string(),
+                },
+                ErrorCategory::AccountIssue,
+            ),
+            (HeroSmsErrorCode::BadAction, ErrorCategory::BadRequest),
+            (
+                HeroSmsErrorCode::OrderAlreadyExists,
+                ErrorCategory::BadRequest,
+            ),
+            (HeroSmsErrorCode::BadService, ErrorCategory::BadRequest),
+            (
+                HeroSmsErrorCode::WrongExceptionPhone,
+                ErrorCategory::BadRequest,
+            ),
+            (
+                HeroSmsErrorCode::WrongMaxPrice { min: Some(1.5) },
+                ErrorCategory::BadRequest,
+            ),
+            (
+                HeroSmsErrorCode::EarlyCancelDenied,
+                ErrorCategory::BadRequest,
+            ),
+            (HeroSmsErrorCode::BadStatus, ErrorCategory::BadRequest),
+            (
+                HeroSmsErrorCode::Unknown {
+                    raw: "SOMETHING".to_string(),
+                },
+                ErrorCategory::Unknown,
+            ),
+        ];
+
+        for (code, expected) in cases {
+            assert_eq!(code.category(), expected, "{code}");
+            let error = HeroSmsError::Service(HeroSmsServiceError::new(code, String::new()));
+            assert_eq!(error.classify(), expected, "{error}");
+        }
+    }
+
+    #[test]
+    fn test_is_provider_failure() {
+        let service_error =
+            |code| HeroSmsError::Service(HeroSmsServiceError::new(code, String::new()));
+
+        assert!(service_error(HeroSmsErrorCode::ErrorSql).is_provider_failure());
+        assert!(!service_error(HeroSmsErrorCode::NoNumbers).is_provider_failure());
+        assert!(!service_error(HeroSmsErrorCode::BadKey).is_provider_failure());
+        assert!(
+            HeroSmsError::HttpRequest(reqwest_middleware::Error::Reqwest(reqwest_error()))
+                .is_provider_failure()
+        );
+        assert!(!HeroSmsError::TooManyRequests { retry_after: None }.is_provider_failure());
+    }
+
+    #[test]
+    fn test_classify() {
+        let service_error = HeroSmsServiceError::new(HeroSmsErrorCode::BadStatus, String::new());
+        let cases = vec![
+            HeroSmsError::BuildHttpClient(reqwest_error()),
+            HeroSmsError::InvalidCertificate("bad pem".to_string()),
+            HeroSmsError::InvalidEndpointUrl {
+                url: "not a url".to_string(),
+                source: url::ParseError::RelativeUrlWithoutBase,
+            },
+            HeroSmsError::BuildRequestUrl(serde_urlencoded::ser::Error::Custom("bad".into())),
+            HeroSmsError::HttpRequest(reqwest_middleware::Error::Reqwest(reqwest_error())),
+            HeroSmsError::TooManyRequests {
+                retry_after: Some(Duration::from_secs(5)),
+            },
+            HeroSmsError::ParseResponse(reqwest_error()),
+            HeroSmsError::MaxPriceTooLow {
+                requested: Some(1.0),
+                minimum: 2.0,
+            },
+            HeroSmsError::SolutionTimeout {
+                timeout: Duration::from_secs(60),
+                task_id: TaskId::from("1"),
+            },
+            HeroSmsError::CountryMapping {
+                country: Box::new(keshvar::Alpha2::US.to_country()),
+            },
+            HeroSmsError::FailedToParseSetStatusResponse { raw: String::new() },
+            HeroSmsError::DeserializeJson(serde_json::from_str::<u8>("x").unwrap_err()),
+            HeroSmsError::Io(std::io::Error::other("broken pipe")),
+            HeroSmsError::FailedToParseBalanceResponse { raw: String::new() },
+            HeroSmsError::FailedToParseNumberResponse { raw: String::new() },
+            HeroSmsError::FailedToParseCountryCode { raw: String::new() },
+            HeroSmsError::FailedToParseStatusResponse { raw: String::new() },
+            HeroSmsError::MissingEnvVar {
+                name: "HERO_SMS_API_KEY".to_string(),
+            },
+            HeroSmsError::InvalidEnvVar {
+                name: "HERO_SMS_API_KEY".to_string(),
+                value: String::new(),
+            },
+            HeroSmsError::AllNumbersBlacklisted {
+                attempts: 4,
+                task_ids_cancelled: Vec::new(),
+                task_ids_not_cancelled: Vec::new(),
+            },
+            HeroSmsError::UnsupportedService {
+                code: "zz".to_string(),
+            },
+            HeroSmsError::InsufficientBalance {
+                balance: 1.0,
+                required: 5.0,
+            },
+            HeroSmsError::InvalidForwardNumber {
+                number: "123".to_string(),
+            },
+            HeroSmsError::ForwardingNotSupported {
+                service: "wa".to_string(),
+                raw: "FORWARD_NOT_SUPPORTED".to_string(),
+            },
+            HeroSmsError::PartialSuccess {
+                action: "get_sms_code",
+                task_id: TaskId::from("1"),
+                source: Box::new(service_error.clone()),
+            },
+            HeroSmsError::Service(service_error),
+        ];
+
+        for error in cases {
+            // No wildcard arm, so a new variant must be classified here
+            let expected = match &error {
+                HeroSmsError::HttpRequest(_)
+                | HeroSmsError::ParseResponse(_)
+                | HeroSmsError::Io(_) => ErrorCategory::NetworkFailure,
+                HeroSmsError::TooManyRequests { .. }
+                | HeroSmsError::SolutionTimeout { .. }
+                | HeroSmsError::AllNumbersBlacklisted { .. } => {
+                    ErrorCategory::TemporarilyUnavailable
+                }
+                // Both wrap `BAD_STATUS`
+                HeroSmsError::Service(_) | HeroSmsError::PartialSuccess { .. } => {
+                    ErrorCategory::BadRequest
+                }
+                HeroSmsError::MaxPriceTooLow { .. }
+                | HeroSmsError::CountryMapping { .. }
+                | HeroSmsError::UnsupportedService { .. }
+                | HeroSmsError::InvalidForwardNumber { .. }
+                | HeroSmsError::ForwardingNotSupported { .. }
+                | HeroSmsError::InvalidEndpointUrl { .. }
+                | HeroSmsError::BuildRequestUrl(_) => ErrorCategory::BadRequest,
+                HeroSmsError::MissingEnvVar { .. }
+                | HeroSmsError::InvalidEnvVar { .. }
+                | HeroSmsError::InsufficientBalance { .. } => ErrorCategory::AccountIssue,
+                HeroSmsError::BuildHttpClient(_)
+                | HeroSmsError::InvalidCertificate(_)
+                | HeroSmsError::FailedToParseSetStatusResponse { .. }
+                | HeroSmsError::DeserializeJson(_)
+                | HeroSmsError::FailedToParseBalanceResponse { .. }
+                | HeroSmsError::FailedToParseNumberResponse { .. }
+                | HeroSmsError::FailedToParseCountryCode { .. }
+                | HeroSmsError::FailedToParseStatusResponse { .. } => ErrorCategory::Unknown,
+            };
+            assert_eq!(error.classify(), expected, "{error}");
+        }
+    }
 }
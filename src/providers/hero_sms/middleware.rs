@@ -0,0 +1,100 @@
+//! HTTP middleware for debugging Hero SMS API traffic.
+
+use http::Extensions;
+use reqwest::{Request, Response, ResponseBuilderExt};
+use reqwest_middleware::{Middleware, Next, Result};
+use tracing::trace;
+use url::Url;
+
+/// Query parameter carrying the API key in legacy requests.
+const API_KEY_PARAM: &str = "api_key";
+
+/// Middleware that logs raw response bodies at `TRACE` level.
+///
+/// The API key query parameter is redacted from the logged URL. The response
+/// body is buffered so it can be logged and then handed on unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for LoggingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let method = req.method().clone();
+        let url = redact_url(req.url());
+
+        let response = next.run(req, extensions).await?;
+
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let response_url = response.url().clone();
+        let body = response.bytes().await?;
+
+        trace!(
+            method = %method,
+            url = %url,
+            status = %status,
+            body = %String::from_utf8_lossy(&body),
+            "Hero SMS API response"
+        );
+
+        let mut builder = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(response_url);
+        if let Some(builder_headers) = builder.headers_mut() {
+            *builder_headers = headers;
+        }
+        let response = builder
+            .body(body)
+            .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+
+        Ok(Response::from(response))
+    }
+}
+
+/// Replace the API key query parameter value with `[REDACTED]`.
+pub(crate) fn redact_url(url: &Url) -> String {
+    if !url.query_pairs().any(|(key, _)| key == API_KEY_PARAM) {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if key == API_KEY_PARAM {
+                "[REDACTED]".to_string()
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+
+    let mut redacted = url.clone();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    // Keep the placeholder readable instead of percent-encoded.
+    redacted.to_string().replace("%5BREDACTED%5D", "[REDACTED]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_url() {
+        let url = Url::parse("https://example.com/api?api_key=secret&action=getBalance").unwrap();
+        let redacted = redact_url(&url);
+        assert!(!redacted.contains("secret"));
+        assert!(redacted.contains("api_key=[REDACTED]"));
+        assert!(redacted.contains("action=getBalance"));
+
+        let url = Url::parse("https://example.com/api/v2/getBalance").unwrap();
+        assert_eq!(redact_url(&url), "https://example.com/api/v2/getBalance");
+    }
+}
@@ -33,18 +33,37 @@
 //! let code = service.wait_for_sms_code(&result.task_id).await?;
 //! println!("Got code: {}", code);
 //! ```
+//!
+//! # Operators
+//!
+//! Numbers can be restricted to one mobile operator with
+//! [`HeroSmsProvider::with_operator`], or for every request of a client with
+//! [`HeroSmsClientBuilder::operator`](client::HeroSmsClientBuilder::operator).
+//! Operator names are lowercase and specific to a country, e.g. `"mts"`,
+//! `"beeline"`, `"megafon"` or `"tele2"` in Russia and `"kyivstar"` or
+//! `"lifecell"` in Ukraine. Use [`HeroSmsProvider::available_operators`] to
+//! list the names Hero SMS accepts for a country and service.
 
 pub mod client;
 pub mod countries;
 pub mod errors;
+#[cfg(feature = "tracing")]
+pub mod middleware;
 pub mod provider;
 mod response;
 pub mod services;
 pub mod types;
+#[cfg(feature = "v1-compat")]
+pub mod v1;
 
 // Re-export commonly used types
-pub use client::HeroSms;
-pub use countries::SmsCountryExt;
-pub use errors::HeroSmsError;
-pub use provider::HeroSmsProvider;
+pub use client::{ApiVersion, HeroSms};
+pub use countries::{CountryMap, HeroSmsCountryMapBuilder, SmsCountryExt};
+pub use errors::{
+    ContextualHeroSmsError, ErrorCategory, HeroSmsError, HeroSmsErrorCode, HeroSmsServiceError,
+    ResultExt,
+};
+pub use provider::{CountryPreferenceStrategy, HeroSmsProvider};
 pub use services::Service;
+#[cfg(feature = "v1-compat")]
+pub use v1::{HeroSmsV1, HeroSmsV1Provider};
@@ -1,17 +1,56 @@
 //! Hero SMS provider implementation.
 
 use super::client::HeroSms;
-use super::countries::SMS_ID2COUNTRY;
-use super::errors::{HeroSmsError, Result};
+use super::countries::CountryMap;
+use super::errors::{HeroSmsError, HeroSmsErrorCode, HeroSmsServiceError, Result};
 use super::services::Service;
-use super::types::ActivationStatus;
+use super::types::{
+    ActivationStatus, CountryInfo, CountryOperatorEntry, GetNumberOptions, GetPhoneNumberResponse,
+    MaxPrice, ServicePrice, SetStatusResponse,
+};
 use crate::providers::traits::Provider;
-use crate::types::{DialCode, FullNumber, SmsCode, TaskId};
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    Number, PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
 use keshvar::Country;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
 #[cfg(feature = "tracing")]
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Environment variable holding the Hero SMS API key.
+pub const API_KEY_ENV: &str = "HERO_SMS_API_KEY";
+
+/// Environment variable holding the Hero SMS referral code.
+pub const REFERRAL_CODE_ENV: &str = "HERO_SMS_REF";
+
+/// Environment variable holding the Hero SMS affiliate ID.
+pub const AFFILIATE_ID_ENV: &str = "HERO_SMS_AFFILIATE_ID";
+
+/// Default time a `getPrices` response is reused for.
+const DEFAULT_PRICE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Default number of extra requests made when a number is blacklisted.
+const DEFAULT_MAX_BLACKLIST_RETRIES: u8 = 3;
+
+/// Service prices keyed by service code, with the time they were fetched.
+type PriceCache = HashMap<String, (Instant, Vec<(Country, ServicePrice)>)>;
+
+/// How [`HeroSmsProvider::get_number_with_strategy`] picks a country.
+#[derive(Debug, Clone)]
+pub enum CountryPreferenceStrategy {
+    /// Request a number from this country only.
+    Explicit(Box<Country>),
+    /// Try the given countries with numbers in stock, cheapest first.
+    CheapestFrom(Vec<Country>),
+    /// Try every country with numbers in stock, cheapest first.
+    AnyAvailable,
+}
 
 /// Hero SMS provider implementation.
 ///
@@ -45,6 +84,15 @@ use tracing::debug;
 pub struct HeroSmsProvider {
     client: HeroSms,
     blacklisted_dial_codes: HashSet<DialCode>,
+    number_options: GetNumberOptions,
+    cached_balance: Arc<RwLock<Option<f64>>>,
+    cached_countries: Arc<RwLock<Option<Vec<CountryInfo>>>>,
+    price_cache: Arc<RwLock<PriceCache>>,
+    price_cache_ttl: Duration,
+    max_blacklist_retries: u8,
+    validate_services: bool,
+    supported_service_codes: Arc<RwLock<Option<HashSet<String>>>>,
+    min_balance: Option<f64>,
 }
 
 impl HeroSmsProvider {
@@ -53,10 +101,7 @@ impl HeroSmsProvider {
     /// # Arguments
     /// * `client` - The Hero SMS client to use
     pub fn new(client: HeroSms) -> Self {
-        Self {
-            client,
-            blacklisted_dial_codes: HashSet::new(),
-        }
+        Self::with_blacklist(client, HashSet::new())
     }
 
     /// Create a new Hero SMS provider with a blacklist of dial codes.
@@ -66,7 +111,128 @@ impl HeroSmsProvider {
         Self {
             client,
             blacklisted_dial_codes: blacklist,
+            number_options: GetNumberOptions::default(),
+            cached_balance: Arc::new(RwLock::new(None)),
+            cached_countries: Arc::new(RwLock::new(None)),
+            price_cache: Arc::new(RwLock::new(HashMap::new())),
+            price_cache_ttl: DEFAULT_PRICE_CACHE_TTL,
+            max_blacklist_retries: DEFAULT_MAX_BLACKLIST_RETRIES,
+            validate_services: false,
+            supported_service_codes: Arc::new(RwLock::new(None)),
+            min_balance: None,
+        }
+    }
+
+    /// Check service codes against the supported services list before
+    /// requesting a number.
+    ///
+    /// Unknown codes fail with [`HeroSmsError::UnsupportedService`] instead of
+    /// a `BAD_SERVICE` response. The list is fetched on first use and cached.
+    pub fn with_service_validation(mut self, enabled: bool) -> Self {
+        self.validate_services = enabled;
+        self
+    }
+
+    /// Check the balance before every number request.
+    ///
+    /// Requests fail with [`HeroSmsError::InsufficientBalance`] while the
+    /// balance is below `min_balance`, instead of with the API's error.
+    pub fn with_min_balance(mut self, min_balance: f64) -> Self {
+        self.min_balance = Some(min_balance);
+        self
+    }
+
+    /// Get the current account balance.
+    pub async fn balance(&self) -> Result<f64> {
+        self.client.get_balance().await
+    }
+
+    /// Set how many times a number with a blacklisted prefix is cancelled
+    /// and requested again (default: 3).
+    pub fn with_max_blacklist_retries(mut self, retries: u8) -> Self {
+        self.max_blacklist_retries = retries;
+        self
+    }
+
+    /// Set how long service prices are cached by
+    /// [`Self::get_number_with_strategy`] (default: 5 minutes).
+    pub fn with_price_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.price_cache_ttl = ttl;
+        self
+    }
+
+    /// Create a provider configured from environment variables.
+    ///
+    /// Reads the API key from `HERO_SMS_API_KEY` and, if set, the referral
+    /// code from `HERO_SMS_REF` and the affiliate ID from `HERO_SMS_AFFILIATE_ID`.
+    pub fn from_env() -> Result<Self> {
+        let api_key = env::var(API_KEY_ENV).map_err(|_| HeroSmsError::MissingEnvVar {
+            name: API_KEY_ENV.to_string(),
+        })?;
+        let mut provider = Self::new(HeroSms::with_api_key(api_key)?);
+
+        if let Ok(referral_code) = env::var(REFERRAL_CODE_ENV) {
+            provider = provider.with_referral_code(referral_code);
+        }
+
+        if let Ok(value) = env::var(AFFILIATE_ID_ENV) {
+            let affiliate_id =
+                value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| HeroSmsError::InvalidEnvVar {
+                        name: AFFILIATE_ID_ENV.to_string(),
+                        value: value.clone(),
+                    })?;
+            provider = provider.with_affiliate_id(affiliate_id);
         }
+
+        Ok(provider)
+    }
+
+    /// Set the referral code sent with every number request.
+    pub fn with_referral_code(mut self, code: impl Into<String>) -> Self {
+        self.number_options.referral_code = Some(code.into());
+        self
+    }
+
+    /// Set the affiliate ID sent with every number request.
+    pub fn with_affiliate_id(mut self, id: u64) -> Self {
+        self.number_options.affiliate_id = Some(id);
+        self
+    }
+
+    /// Take numbers from the given mobile operator only.
+    ///
+    /// Names are lowercase and country-specific, e.g. `"mts"` or
+    /// `"kyivstar"`; see [`available_operators`](Self::available_operators).
+    /// Overrides the client's default operator.
+    pub fn with_operator(mut self, operator: impl Into<String>) -> Self {
+        self.number_options.operator = Some(operator.into());
+        self
+    }
+
+    /// Use a custom country map, e.g. to fix mappings from the embedded assets.
+    ///
+    /// See [`CountryMap::builder`].
+    pub fn with_country_map(mut self, country_map: CountryMap) -> Self {
+        self.client = self.client.with_country_map(country_map);
+        self
+    }
+
+    /// Get the referral code, if set.
+    pub fn referral_code(&self) -> Option<&str> {
+        self.number_options.referral_code.as_deref()
+    }
+
+    /// Get the affiliate ID, if set.
+    pub fn affiliate_id(&self) -> Option<u64> {
+        self.number_options.affiliate_id
+    }
+
+    /// Get the preferred mobile operator, if set.
+    pub fn operator(&self) -> Option<&str> {
+        self.number_options.operator.as_deref()
     }
 
     /// Add a dial code to the blacklist.
@@ -88,6 +254,429 @@ impl HeroSmsProvider {
     pub fn blacklisted_dial_codes(&self) -> &HashSet<DialCode> {
         &self.blacklisted_dial_codes
     }
+
+    /// Get the account balance cached by [`Provider::warmup`].
+    pub fn cached_balance(&self) -> Option<f64> {
+        *self
+            .cached_balance
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the country list cached by [`Provider::warmup`].
+    pub fn cached_countries(&self) -> Option<Vec<CountryInfo>> {
+        self.cached_countries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Check the account balance and active activations concurrently.
+    ///
+    /// The report is healthy if both requests succeed and the balance is
+    /// positive. `api_latency` is the duration of the balance request.
+    pub async fn health(&self) -> HealthReport {
+        let checked_at = SystemTime::now();
+        let timed_balance = async {
+            let started = Instant::now();
+            let balance = self.client.get_balance().await;
+            (balance, started.elapsed())
+        };
+        let ((balance, api_latency), active_activations) =
+            futures::join!(timed_balance, self.client.get_active_activation_count());
+
+        let is_healthy = matches!(balance, Ok(b) if b > 0.0) && active_activations.is_ok();
+
+        HealthReport {
+            balance: balance.ok(),
+            active_activations: active_activations.ok(),
+            api_latency,
+            is_healthy,
+            checked_at,
+        }
+    }
+
+    /// Returns true if the country has a Hero SMS country ID.
+    ///
+    /// Requests for other countries fail with [`HeroSmsError::CountryMapping`]
+    /// without contacting the API.
+    pub fn is_country_supported(&self, country: &Country) -> bool {
+        self.client.country_map().sms_id(country).is_ok()
+    }
+
+    /// Check whether the service code is in the supported services list.
+    ///
+    /// The list is fetched on the first call and cached.
+    pub async fn validate_service(&self, service: &Service) -> Result<bool> {
+        if let Some(codes) = self
+            .supported_service_codes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return Ok(codes.contains(service.code()));
+        }
+
+        let codes: HashSet<String> = self
+            .client
+            .get_supported_services()
+            .await?
+            .into_iter()
+            .map(|service| service.code)
+            .collect();
+        let supported = codes.contains(service.code());
+        *self
+            .supported_service_codes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(codes);
+
+        Ok(supported)
+    }
+
+    /// Get the mobile operators available for a country and service.
+    pub async fn available_operators(
+        &self,
+        country: Country,
+        service: Service,
+    ) -> Result<Vec<String>> {
+        self.client.get_operator_list(country, service).await
+    }
+
+    /// Get the operators of a country that offer the service and currently
+    /// have free numbers.
+    ///
+    /// Country operators are cached by the client, see
+    /// [`HeroSms::get_country_operators`].
+    pub async fn available_operators_for_country(
+        &self,
+        country: Country,
+        service: Service,
+    ) -> Result<Vec<String>> {
+        let offered = self
+            .client
+            .get_operator_list(country.clone(), service)
+            .await?;
+
+        Ok(self
+            .client
+            .get_country_operators(country)
+            .await?
+            .into_iter()
+            .filter(|operator| operator.has_numbers && offered.contains(&operator.name))
+            .map(|operator| operator.name)
+            .collect())
+    }
+
+    /// Get a phone number, overriding the server-side wait for a free number.
+    ///
+    /// `max_wait_seconds` is sent as `maxTime` and takes precedence over
+    /// [`HeroSmsClientBuilder::default_max_wait`](super::client::HeroSmsClientBuilder::default_max_wait).
+    pub async fn get_number_with_timeout_override(
+        &self,
+        country: Country,
+        service: Service,
+        max_wait_seconds: u32,
+    ) -> Result<(TaskId, FullNumber)> {
+        let options = GetNumberOptions {
+            max_wait_seconds: Some(max_wait_seconds),
+            ..self.number_options.clone()
+        };
+        let (task_id, full_number, _) = self.acquire_number(country, service, &options).await?;
+        Ok((task_id, full_number))
+    }
+
+    /// Get a phone number, replacing numbers with a blacklisted prefix.
+    ///
+    /// A blacklisted number is cancelled and a new one requested, up to
+    /// `max_retries` times. Fails with [`HeroSmsError::AllNumbersBlacklisted`]
+    /// if every number was blacklisted.
+    pub async fn get_number_with_soft_retry(
+        &self,
+        country: Country,
+        service: Service,
+        max_retries: u8,
+    ) -> Result<(TaskId, FullNumber)> {
+        let (task_id, full_number, _) = self
+            .acquire_number_with_retries(country, service, &self.number_options, max_retries)
+            .await?;
+        Ok((task_id, full_number))
+    }
+
+    /// Get the price of a service in every country, cheapest first.
+    ///
+    /// Responses are cached per service for the configured price cache TTL.
+    pub async fn service_prices(&self, service: Service) -> Result<Vec<(Country, ServicePrice)>> {
+        if let Some((fetched_at, prices)) = self
+            .price_cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(service.code())
+            && fetched_at.elapsed() < self.price_cache_ttl
+        {
+            return Ok(prices.clone());
+        }
+
+        let prices = self.client.get_service_prices(service.clone()).await?;
+        self.price_cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(service.code().to_string(), (Instant::now(), prices.clone()));
+
+        Ok(prices)
+    }
+
+    /// Get a phone number from a country chosen by `strategy`.
+    ///
+    /// For price-based strategies, countries are tried cheapest first, moving
+    /// on while the service reports no numbers. Countries with blacklisted
+    /// dial codes or nothing in stock are skipped. Returns the country the
+    /// number came from.
+    pub async fn get_number_with_strategy(
+        &self,
+        strategy: CountryPreferenceStrategy,
+        service: Service,
+    ) -> Result<(TaskId, FullNumber, Country)> {
+        let candidates: Vec<Country> = match strategy {
+            CountryPreferenceStrategy::Explicit(country) => vec![*country],
+            CountryPreferenceStrategy::CheapestFrom(countries) => self
+                .in_stock_by_price(service.clone())
+                .await?
+                .into_iter()
+                .filter(|country| countries.iter().any(|c| c.alpha2() == country.alpha2()))
+                .collect(),
+            CountryPreferenceStrategy::AnyAvailable => {
+                self.in_stock_by_price(service.clone()).await?
+            }
+        };
+
+        let mut last_error = None;
+        for country in candidates {
+            #[cfg(feature = "tracing")]
+            debug!(country = %country.iso_short_name(), "Trying country");
+
+            match self
+                .acquire_number(country.clone(), service.clone(), &self.number_options)
+                .await
+            {
+                Ok((task_id, full_number, _)) => return Ok((task_id, full_number, country)),
+                Err(e) if e.is_no_numbers() => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            HeroSmsError::Service(HeroSmsServiceError::new(
+                HeroSmsErrorCode::NoNumbers,
+                "NO_NUMBERS".to_string(),
+            ))
+        }))
+    }
+
+    /// Get a phone number from the first country in `priority` that has one
+    /// within its price cap.
+    ///
+    /// Each entry is a country and the highest price accepted there, sent as
+    /// `maxPrice`. Moves on while the service reports no numbers or a price
+    /// above the cap; other errors are returned immediately. Countries with
+    /// blacklisted dial codes are skipped.
+    pub async fn get_number_with_country_fallback(
+        &self,
+        priority: &[(Country, f64)],
+        service: Service,
+    ) -> Result<GetPhoneNumberResponse> {
+        self.ensure_service_supported(&service).await?;
+        self.ensure_min_balance().await?;
+
+        let mut last_error = None;
+        for (country, max_price) in priority {
+            if !self.is_dial_code_supported(&DialCode::from(country)) {
+                continue;
+            }
+
+            #[cfg(feature = "tracing")]
+            debug!(country = %country.iso_short_name(), max_price = %max_price, "Trying country");
+
+            let options = GetNumberOptions {
+                max_price: Some(MaxPrice::new(*max_price)),
+                ..self.number_options.clone()
+            };
+            match self
+                .client
+                .get_phone_number_with_options(country.clone(), service.clone(), &options)
+                .await
+            {
+                // The service should enforce maxPrice; cancel anything above it regardless
+                Ok(response) if response.activation_cost > *max_price => {
+                    if let Err(_e) = self.cancel_activation(&response.task_id).await {
+                        #[cfg(feature = "tracing")]
+                        warn!(error = %_e, task_id = %response.task_id, "Failed to cancel overpriced number");
+                    }
+                    last_error = Some(HeroSmsError::MaxPriceTooLow {
+                        requested: Some(*max_price),
+                        minimum: response.activation_cost,
+                    });
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_no_numbers() || matches!(e, HeroSmsError::MaxPriceTooLow { .. }) => {
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            HeroSmsError::Service(HeroSmsServiceError::new(
+                HeroSmsErrorCode::NoNumbers,
+                "NO_NUMBERS".to_string(),
+            ))
+        }))
+    }
+
+    /// Countries with the service in stock and an allowed dial code, cheapest first.
+    async fn in_stock_by_price(&self, service: Service) -> Result<Vec<Country>> {
+        Ok(self
+            .service_prices(service)
+            .await?
+            .into_iter()
+            .filter(|(country, price)| {
+                price.count > 0 && self.is_dial_code_supported(&DialCode::from(country))
+            })
+            .map(|(country, _)| country)
+            .collect())
+    }
+
+    /// Request a number, retrying blacklisted numbers up to the configured limit.
+    async fn acquire_number(
+        &self,
+        country: Country,
+        service: Service,
+        options: &GetNumberOptions,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>)> {
+        self.acquire_number_with_retries(country, service, options, self.max_blacklist_retries)
+            .await
+    }
+
+    /// Request a number, cancelling and replacing blacklisted ones.
+    async fn acquire_number_with_retries(
+        &self,
+        country: Country,
+        service: Service,
+        options: &GetNumberOptions,
+        max_retries: u8,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>)> {
+        if !self.is_country_supported(&country) {
+            return Err(HeroSmsError::CountryMapping {
+                country: Box::new(country),
+            });
+        }
+        self.ensure_service_supported(&service).await?;
+        self.ensure_min_balance().await?;
+
+        let mut attempts = 0;
+        let mut task_ids_cancelled = Vec::new();
+        let mut task_ids_not_cancelled = Vec::new();
+
+        for _ in 0..=max_retries {
+            attempts += 1;
+            let (task_id, full_number, metadata) = self
+                .request_number(country.clone(), service.clone(), options)
+                .await?;
+
+            if !self.is_blacklisted(&full_number) {
+                return Ok((task_id, full_number, metadata));
+            }
+
+            #[cfg(feature = "tracing")]
+            debug!(task_id = %task_id, "Number has a blacklisted prefix, requesting another");
+
+            match self.cancel_activation(&task_id).await {
+                Ok(()) => task_ids_cancelled.push(task_id),
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    warn!(error = %_e, task_id = %task_id, "Failed to cancel blacklisted number");
+
+                    task_ids_not_cancelled.push(task_id);
+                }
+            }
+        }
+
+        Err(HeroSmsError::AllNumbersBlacklisted {
+            attempts,
+            task_ids_cancelled,
+            task_ids_not_cancelled,
+        })
+    }
+
+    /// Reject unsupported service codes if service validation is enabled.
+    async fn ensure_service_supported(&self, service: &Service) -> Result<()> {
+        if self.validate_services && !self.validate_service(service).await? {
+            return Err(HeroSmsError::UnsupportedService {
+                code: service.code().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reject requests while the balance is below the configured minimum.
+    async fn ensure_min_balance(&self) -> Result<()> {
+        let Some(required) = self.min_balance else {
+            return Ok(());
+        };
+        let balance = self.balance().await?;
+        if balance < required {
+            return Err(HeroSmsError::InsufficientBalance { balance, required });
+        }
+        Ok(())
+    }
+
+    /// Resolve the `countryCode` of an any-country getNumberV2 response.
+    ///
+    /// Only countries Hero SMS serves are considered. Returns `None` if the
+    /// dial code is unknown or shared by several of them, such as `1` for the
+    /// US, Canada and most of the Caribbean.
+    fn country_for_dial_code(&self, raw: &str) -> Option<Country> {
+        let dial_code = DialCode::new(raw).ok()?;
+        let mut candidates = self
+            .client
+            .country_map()
+            .countries()
+            .into_iter()
+            .filter(|country| DialCode::from(country) == dial_code);
+        let country = candidates.next()?;
+        candidates.next().is_none().then_some(country)
+    }
+
+    /// Returns true if the number starts with a blacklisted dial code.
+    fn is_blacklisted(&self, full_number: &FullNumber) -> bool {
+        self.blacklisted_dial_codes
+            .iter()
+            .any(|dial_code| full_number.starts_with_dial_code(dial_code))
+    }
+
+    /// Request a single number with the given options and collect its metadata.
+    async fn request_number(
+        &self,
+        country: Country,
+        service: Service,
+        options: &GetNumberOptions,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>)> {
+        let response = self
+            .client
+            .get_phone_number_with_options(country, service, options)
+            .await?;
+
+        let metadata = ActivationMetadata {
+            expires_at: Some(response.activation_end_time),
+            cost: Some(response.activation_cost),
+            operator: Some(response.activation_operator),
+        };
+
+        Ok((
+            response.task_id,
+            FullNumber::from(response.phone_number),
+            Some(metadata),
+        ))
+    }
 }
 
 impl Provider for HeroSmsProvider {
@@ -107,9 +696,82 @@ impl Provider for HeroSmsProvider {
         country: Country,
         service: Self::Service,
     ) -> Result<(TaskId, FullNumber)> {
-        let response = self.client.get_phone_number(country, service).await?;
+        let (task_id, full_number, _) = self
+            .get_phone_number_with_metadata(country, service)
+            .await?;
+        Ok((task_id, full_number))
+    }
 
-        Ok((response.task_id, FullNumber::from(response.phone_number)))
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>)> {
+        self.acquire_number(country, service, &self.number_options)
+            .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>> {
+        self.ensure_service_supported(&service).await?;
+        self.ensure_min_balance().await?;
+
+        let mut attempts = 0;
+        let mut task_ids_cancelled = Vec::new();
+        let mut task_ids_not_cancelled = Vec::new();
+
+        for _ in 0..=self.max_blacklist_retries {
+            attempts += 1;
+            let response = self
+                .client
+                .get_phone_number_any_country(service.clone(), &self.number_options)
+                .await?;
+            let task_id = response.task_id;
+            let full_number = FullNumber::from(response.phone_number);
+
+            let Some(country) = self.country_for_dial_code(&response.country_code) else {
+                // The number is useless without knowing its country
+                if let Err(_e) = self.cancel_activation(&task_id).await {
+                    #[cfg(feature = "tracing")]
+                    warn!(error = %_e, task_id = %task_id, "Failed to cancel number of unknown country");
+                }
+                return Err(HeroSmsError::FailedToParseCountryCode {
+                    raw: response.country_code,
+                });
+            };
+
+            let dial_code = DialCode::from(&country);
+            let valid = Number::from_full_number(&full_number, &dial_code)
+                .is_ok_and(|number| number.validate_for_country(&dial_code).is_ok());
+            if valid && !self.is_blacklisted(&full_number) {
+                return Ok(Some((task_id, full_number, country)));
+            }
+
+            #[cfg(feature = "tracing")]
+            debug!(
+                task_id = %task_id,
+                valid,
+                "Number is blacklisted or invalid for its country, requesting another"
+            );
+
+            match self.cancel_activation(&task_id).await {
+                Ok(()) => task_ids_cancelled.push(task_id),
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    warn!(error = %_e, task_id = %task_id, "Failed to cancel rejected number");
+
+                    task_ids_not_cancelled.push(task_id);
+                }
+            }
+        }
+
+        Err(HeroSmsError::AllNumbersBlacklisted {
+            attempts,
+            task_ids_cancelled,
+            task_ids_not_cancelled,
+        })
     }
 
     #[cfg_attr(
@@ -121,17 +783,58 @@ impl Provider for HeroSmsProvider {
         )
     )]
     async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>> {
-        let response = self.client.get_sms_code(task_id).await?;
+        Ok(self
+            .get_sms_code_with_text(task_id)
+            .await?
+            .map(|(code, _)| code))
+    }
+
+    /// Polls every activation through [`HeroSms::get_sms_codes_batch`].
+    async fn get_sms_codes_batch(&self, task_ids: &[TaskId]) -> Vec<Result<Option<SmsCode>>> {
+        let ids: Vec<&TaskId> = task_ids.iter().collect();
+        let mut codes = self.client.get_sms_codes_batch(&ids).await;
+
+        let mut results = Vec::with_capacity(task_ids.len());
+        for task_id in task_ids {
+            let result = match codes.remove(task_id) {
+                Some(result) => result.map_err(|e| polling_error(task_id, e)),
+                // A repeated ID; its first occurrence took the batch result
+                None => self.get_sms_code(task_id).await,
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    async fn get_sms_code_with_text(&self, task_id: &TaskId) -> Result<Option<(SmsCode, String)>> {
+        let response = self
+            .client
+            .get_sms_code(task_id)
+            .await
+            .map_err(|e| polling_error(task_id, e))?;
 
-        if let Some(sms) = &response.sms
+        if let Some(sms) = response.sms
             && !sms.code.is_empty()
         {
-            return Ok(Some(SmsCode::new(&sms.code)));
+            return Ok(Some((SmsCode::new(sms.code), sms.text)));
         }
 
         Ok(None)
     }
 
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>> {
+        let messages = self.client.get_sms_full_list(task_id).await?;
+
+        Ok(messages
+            .into_iter()
+            .filter(|sms| !sms.code.is_empty())
+            .map(|sms| (SmsCode::new(sms.code), sms.text))
+            .collect())
+    }
+
     async fn finish_activation(&self, task_id: &TaskId) -> Result<()> {
         self.client
             .set_activation_status(task_id, ActivationStatus::FinishActivation)
@@ -154,6 +857,91 @@ impl Provider for HeroSmsProvider {
         Ok(())
     }
 
+    async fn get_activation_status(&self, task_id: &TaskId) -> Result<Option<ActivationState>> {
+        let status = self.client.get_phone_status(task_id).await?;
+        Ok(Some(status.into()))
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool> {
+        let response = self
+            .client
+            .set_activation_status(task_id, ActivationStatus::RequestAnotherCode)
+            .await?;
+
+        if response != SetStatusResponse::RetryGet {
+            return Err(HeroSmsError::FailedToParseSetStatusResponse {
+                raw: response.to_string(),
+            });
+        }
+
+        #[cfg(feature = "tracing")]
+        debug!(task_id = %task_id, "Requested another SMS code");
+
+        Ok(true)
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool> {
+        match self.client.get_sms_code(task_id).await {
+            Ok(_) => Ok(true),
+            Err(e)
+                if matches!(
+                    e.as_service_code(),
+                    Some(HeroSmsErrorCode::NoActivation | HeroSmsErrorCode::WrongActivationId)
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "HeroSmsProvider::warmup", skip_all)
+    )]
+    async fn warmup(&self) -> Result<()> {
+        let balance = self.client.get_balance().await?;
+        let countries = self.client.get_countries().await?;
+
+        #[cfg(feature = "tracing")]
+        debug!(balance = %balance, countries = %countries.len(), "Provider warmed up");
+
+        *self
+            .cached_balance
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(balance);
+        *self
+            .cached_countries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(countries);
+
+        Ok(())
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>> {
+        Ok(Some(self.client.get_reputation().await?))
+    }
+
+    async fn country_prices(&self, service: Self::Service) -> Result<Vec<(Country, PriceEntry)>> {
+        self.service_prices(service).await
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>> {
+        let entries = self
+            .client
+            .get_countries_and_operators(Some(service.clone()))
+            .await?;
+
+        Ok(availability_by_country(entries))
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.health().await
+    }
+
     fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
         !self.blacklisted_dial_codes.contains(dial_code)
     }
@@ -165,17 +953,70 @@ impl Provider for HeroSmsProvider {
 
     fn available_countries(&self, _service: &Self::Service) -> Vec<Country> {
         // Return all countries that have Hero SMS mapping
-        SMS_ID2COUNTRY.values().cloned().collect()
+        self.client.country_map().countries()
     }
 
     fn supported_services(&self) -> Vec<Self::Service> {
         Service::all()
     }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        service.code().to_string()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::HeroSms
+    }
+}
+
+/// Wrap a permanent polling failure in [`HeroSmsError::PartialSuccess`].
+///
+/// The activation outlives such a failure, so the error keeps its ID for the
+/// caller to cancel it.
+fn polling_error(task_id: &TaskId, error: HeroSmsError) -> HeroSmsError {
+    match error {
+        HeroSmsError::Service(error)
+            if !error.code.is_retryable() && !error.code.is_activation_specific() =>
+        {
+            HeroSmsError::PartialSuccess {
+                action: "get_sms_code",
+                task_id: task_id.clone(),
+                source: Box::new(error),
+            }
+        }
+        e => e,
+    }
+}
+
+/// Merge operator entries into one entry per country.
+///
+/// Operators with nothing in stock are ignored. Counts are summed and the
+/// lowest price kept; countries keep the order of their first entry.
+fn availability_by_country(entries: Vec<CountryOperatorEntry>) -> Vec<CountryAvailability> {
+    let mut merged: Vec<CountryAvailability> = Vec::new();
+    for entry in entries.into_iter().filter(|e| e.count > 0) {
+        let alpha2 = entry.country.alpha2();
+        match merged.iter_mut().find(|m| m.country.alpha2() == alpha2) {
+            Some(existing) => {
+                existing.count += entry.count;
+                existing.price = Some(existing.price.map_or(entry.price, |p| p.min(entry.price)));
+            }
+            None => merged.push(CountryAvailability {
+                country: entry.country,
+                count: entry.count,
+                price: Some(entry.price),
+            }),
+        }
+    }
+    merged
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::RetryableError;
+    use crate::providers::hero_sms::client::tests::number_response;
+    use crate::providers::hero_sms::errors::HeroSmsErrorCode;
     use keshvar::Alpha2;
     use wiremock::matchers::{method, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -192,17 +1033,9 @@ mod tests {
         Mock::given(method("GET"))
             .and(query_param("action", "getNumberV2"))
             .and(query_param("service", "ig"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "activationId": "123456",
-                "phoneNumber": "380501234567",
-                "activationCost": 10.5,
-                "currency": 643,
-                "countryCode": "380",
-                "canGetAnotherSms": true,
-                "activationTime": "2025-01-01 12:00:00",
-                "activationEndTime": "2025-01-01 12:20:00",
-                "activationOperator": "kyivstar"
-            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
             .mount(&mock_server)
             .await;
 
@@ -218,28 +1051,713 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_sms_code_received() {
+    async fn test_get_phone_number_with_operator() {
         let mock_server = MockServer::start().await;
 
         Mock::given(method("GET"))
-            .and(query_param("action", "getStatusV2"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "sms": {
-                    "dateTime": "2025-01-01 12:05:00",
-                    "code": "123456",
-                    "text": "Your code is: 123456"
-                }
-            })))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("operator", "kyivstar"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .expect(1)
             .mount(&mock_server)
             .await;
 
-        let provider = create_test_provider(&mock_server);
-        let result = provider.get_sms_code(&TaskId::from("123")).await;
+        let provider = create_test_provider(&mock_server).with_operator("kyivstar");
+        assert_eq!(provider.operator(), Some("kyivstar"));
 
-        assert!(result.is_ok());
-        let code = result.unwrap();
-        assert!(code.is_some());
-        assert_eq!(code.unwrap().as_str(), "123456");
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "123456");
+    }
+
+    #[tokio::test]
+    async fn test_shared_provider_in_service() {
+        use crate::service::{SmsSolverService, SmsSolverServiceConfig, SmsSolverServiceTrait};
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = Arc::new(create_test_provider(&mock_server));
+        let service =
+            SmsSolverService::new(Arc::clone(&provider), SmsSolverServiceConfig::default());
+
+        let result = tokio::spawn(async move {
+            service
+                .get_number(Alpha2::UA.to_country(), Service::Whatsapp)
+                .await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(result.task_id.as_ref(), "123456");
+        assert_eq!(provider.provider_kind(), ProviderKind::HeroSms);
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_with_country_map() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", "999"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let country_map = CountryMap::builder()
+            .override_mapping(Alpha2::UA.to_country(), 999)
+            .remove_mapping(Alpha2::GB.to_country())
+            .build();
+        let provider = create_test_provider(&mock_server).with_country_map(country_map);
+
+        assert!(
+            provider
+                .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            provider
+                .get_phone_number(Alpha2::GB.to_country(), Service::Whatsapp)
+                .await,
+            Err(HeroSmsError::CountryMapping { .. })
+        ));
+        assert!(
+            !provider
+                .available_countries(&Service::Whatsapp)
+                .iter()
+                .any(|c| c.alpha2() == Alpha2::GB)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_with_metadata() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let (task_id, _, metadata) = provider
+            .get_phone_number_with_metadata(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "123456");
+        let metadata = metadata.unwrap();
+        assert_eq!(metadata.expires_at.as_deref(), Some("2025-01-01 12:20:00"));
+        assert_eq!(metadata.cost, Some(10.5));
+        assert_eq!(metadata.operator.as_deref(), Some("kyivstar"));
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_with_referral_params() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("ref", "partner42"))
+            .and(query_param("affiliate_id", "1001"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server)
+            .with_referral_code("partner42")
+            .with_affiliate_id(1001);
+        assert_eq!(provider.referral_code(), Some("partner42"));
+        assert_eq!(provider.affiliate_id(), Some(1001));
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "123456");
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_timeout_override() {
+        let mock_server = MockServer::start().await;
+        let response =
+            ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567"));
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("maxTime", "15"))
+            .respond_with(response.clone())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("maxTime", "60"))
+            .respond_with(response)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = HeroSms::builder("test_key")
+            .endpoint(mock_server.uri().parse().unwrap())
+            .default_max_wait(60)
+            .build()
+            .unwrap();
+        assert_eq!(client.default_max_wait(), Some(60));
+        let provider = HeroSmsProvider::new(client);
+
+        provider
+            .get_number_with_timeout_override(Alpha2::UA.to_country(), Service::Whatsapp, 15)
+            .await
+            .unwrap();
+        provider
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_code_received() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sms": {
+                    "dateTime": "2025-01-01 12:05:00",
+                    "code": "123456",
+                    "text": "Your code is: 123456"
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let result = provider.get_sms_code(&TaskId::from("123")).await;
+
+        assert!(result.is_ok());
+        let code = result.unwrap();
+        assert!(code.is_some());
+        assert_eq!(code.unwrap().as_str(), "123456");
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_code_with_text() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sms": {
+                    "dateTime": "2025-01-01 12:05:00",
+                    "code": "123456",
+                    "text": "Your code is: 123456"
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let (code, text) = provider
+            .get_sms_code_with_text(&TaskId::from("123"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(code.as_str(), "123456");
+        assert_eq!(text, "Your code is: 123456");
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_code_permanent_error_keeps_task_id() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("BAD_STATUS"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let err = provider
+            .get_sms_code(&TaskId::from("123"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            HeroSmsError::PartialSuccess {
+                action: "get_sms_code",
+                ..
+            }
+        ));
+        assert_eq!(err.live_task_id(), Some(&TaskId::from("123")));
+        assert_eq!(err.as_service_code(), Some(&HeroSmsErrorCode::BadStatus));
+        assert!(!err.is_retryable());
+        assert!(!err.is_activation_specific());
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_codes_batch() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .and(query_param("id", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sms": {
+                    "dateTime": "2025-01-01 12:05:00",
+                    "code": "111111",
+                    "text": "Your code is: 111111"
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .and(query_param("id", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .and(query_param("id", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("BAD_STATUS"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let task_ids = ["1", "2", "3", "1"].map(TaskId::from);
+        let results = provider.get_sms_codes_batch(&task_ids).await;
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap().as_ref().unwrap().as_str(),
+            "111111"
+        );
+        assert!(results[1].as_ref().unwrap().is_none());
+        let err = results[2].as_ref().unwrap_err();
+        assert!(matches!(err, HeroSmsError::PartialSuccess { .. }));
+        assert_eq!(err.live_task_id(), Some(&TaskId::from("3")));
+        assert_eq!(
+            results[3].as_ref().unwrap().as_ref().unwrap().as_str(),
+            "111111"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_code_wrong_activation_id_is_not_wrapped() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("WRONG_ACTIVATION_ID"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let err = provider
+            .get_sms_code(&TaskId::from("123"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, HeroSmsError::Service(_)));
+        assert_eq!(err.live_task_id(), None);
+        assert_eq!(
+            err.as_service_code(),
+            Some(&HeroSmsErrorCode::WrongActivationId)
+        );
+        assert!(err.is_activation_specific());
+        assert!(!err.is_retryable());
+        assert!(err.should_retry_operation());
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_for_any_country() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", "0"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "905321234567")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let (task_id, full_number, country) = provider
+            .get_phone_number_for_any_country(Service::Whatsapp)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "123456");
+        assert_eq!(full_number.as_ref(), "905321234567");
+        assert_eq!(country.alpha2(), Alpha2::TR);
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_for_any_country_cancels_ambiguous_country() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "12025550123")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("id", "123456"))
+            .and(query_param("status", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_CANCEL"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let err = provider
+            .get_phone_number_for_any_country(Service::Whatsapp)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            HeroSmsError::FailedToParseCountryCode { ref raw } if raw == "1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_for_any_country_skips_blacklisted() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("111", "905321234567")),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("222", "491701234567")),
+            )
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("id", "111"))
+            .and(query_param("status", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_CANCEL"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut provider = create_test_provider(&mock_server);
+        provider.blacklist_dial_code(DialCode::new("90").unwrap());
+
+        let (task_id, _, country) = provider
+            .get_phone_number_for_any_country(Service::Whatsapp)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(task_id.as_ref(), "222");
+        assert_eq!(country.alpha2(), Alpha2::DE);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_country_fallback() {
+        let mock_server = MockServer::start().await;
+        let provider = create_test_provider(&mock_server);
+        let country_id = |alpha2: Alpha2| {
+            let country_map = provider.client().country_map();
+            country_map
+                .sms_id(&alpha2.to_country())
+                .unwrap()
+                .to_string()
+        };
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", country_id(Alpha2::UA)))
+            .respond_with(ResponseTemplate::new(200).set_body_string("NO_NUMBERS"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", country_id(Alpha2::TR)))
+            .and(query_param("maxPrice", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("WRONG_MAX_PRICE:8.5"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", country_id(Alpha2::DE)))
+            .and(query_param("maxPrice", "20"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("777", "4915112345678")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let priority = [
+            (Alpha2::UA.to_country(), 3.0),
+            (Alpha2::TR.to_country(), 5.0),
+            (Alpha2::DE.to_country(), 20.0),
+        ];
+        let response = provider
+            .get_number_with_country_fallback(&priority, Service::Whatsapp)
+            .await
+            .unwrap();
+
+        assert_eq!(response.task_id.as_ref(), "777");
+        assert_eq!(response.country_code, "49");
+        assert_eq!(response.activation_cost, 10.5);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_country_fallback_exhausted() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("WRONG_MAX_PRICE:8.5"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let priority = [
+            (Alpha2::UA.to_country(), 3.0),
+            (Alpha2::TR.to_country(), 5.0),
+        ];
+        let err = provider
+            .get_number_with_country_fallback(&priority, Service::Whatsapp)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            HeroSmsError::MaxPriceTooLow {
+                requested: Some(5.0),
+                minimum: 8.5
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_service_validation() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getServicesList"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "services": [
+                    { "code": "wa", "name": "WhatsApp" },
+                    { "code": "ig", "name": "Instagram + Threads" }
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("BAD_SERVICE"))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server).with_service_validation(true);
+        let unknown = Service::Other {
+            code: "zz".to_string(),
+        };
+
+        assert!(provider.validate_service(&Service::Whatsapp).await.unwrap());
+        assert!(!provider.validate_service(&unknown).await.unwrap());
+
+        let err = provider
+            .get_phone_number(Alpha2::UA.to_country(), unknown)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            HeroSmsError::UnsupportedService { ref code } if code == "zz"
+        ));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_available_countries_with_counts_merges_operators() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getCountriesAndOperators"))
+            .and(query_param("service", "wa"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"country": 1, "operator": "kyivstar", "count": 12, "price": 0.3},
+                {"country": 16, "operator": "vodafone", "count": 0, "price": 0.05},
+                {"country": 1, "operator": "lifecell", "count": 3, "price": 0.2}
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let countries = provider
+            .available_countries_with_counts(&Service::Whatsapp)
+            .await
+            .unwrap();
+
+        assert_eq!(countries.len(), 1);
+        assert_eq!(countries[0].country.alpha2(), Alpha2::UA);
+        assert_eq!(countries[0].count, 15);
+        assert_eq!(countries[0].price, Some(0.2));
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getBalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_BALANCE:12.50"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getActiveActivations"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "activeActivations": [{ "activationId": "1" }, { "activationId": "2" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let report = provider.health().await;
+
+        assert_eq!(report.balance, Some(12.5));
+        assert_eq!(report.active_activations, Some(2));
+        assert!(report.is_healthy);
+    }
+
+    #[tokio::test]
+    async fn test_health_unhealthy_with_zero_balance() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getBalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_BALANCE:0.00"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let report = provider.health().await;
+
+        assert_eq!(report.balance, Some(0.0));
+        assert_eq!(report.active_activations, None);
+        assert!(!report.is_healthy);
+    }
+
+    #[tokio::test]
+    async fn test_available_operators_for_country() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getOperators"))
+            .and(query_param("service", "wa"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "countryOperators": { "1": ["kyivstar", "lifecell"] }
+            })))
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getOperators"))
+            .and(query_param("country", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "countryOperators": { "1": ["kyivstar", "lifecell", "vodafone"] }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        for (operator, count) in [("kyivstar", "12"), ("lifecell", "0"), ("vodafone", "3")] {
+            Mock::given(method("GET"))
+                .and(query_param("action", "getNumbersStatus"))
+                .and(query_param("country", "1"))
+                .and(query_param("operator", operator))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({ "wa_0": count })),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let provider = create_test_provider(&mock_server);
+        for _ in 0..2 {
+            let operators = provider
+                .available_operators_for_country(Alpha2::UA.to_country(), Service::Whatsapp)
+                .await
+                .unwrap();
+            assert_eq!(operators, vec!["kyivstar"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_is_task_alive() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatusV2"))
+            .and(query_param("id", "123"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("NO_ACTIVATION"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        assert!(!provider.is_task_alive(&TaskId::from("123")).await.unwrap());
     }
 
     #[tokio::test]
@@ -276,6 +1794,217 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_request_another_code() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("id", "123"))
+            .and(query_param("status", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_RETRY_GET"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let result = provider.request_another_code(&TaskId::from("123")).await;
+
+        assert!(result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_request_another_code_unexpected_response() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("status", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_CANCEL"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let result = provider.request_another_code(&TaskId::from("123")).await;
+
+        assert!(matches!(
+            result,
+            Err(HeroSmsError::FailedToParseSetStatusResponse { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_min_balance_rejects_before_requesting_number() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getBalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_BALANCE:2.50"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("NO_NUMBERS"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        assert_eq!(provider.balance().await.unwrap(), 2.5);
+
+        let err = provider
+            .clone()
+            .with_min_balance(5.0)
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            HeroSmsError::InsufficientBalance { balance, required }
+                if balance == 2.5 && required == 5.0
+        ));
+
+        // Above the minimum, the request goes through
+        let err = provider
+            .with_min_balance(1.0)
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap_err();
+        assert!(err.is_no_numbers());
+    }
+
+    #[tokio::test]
+    async fn test_warmup_caches_balance_and_countries() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getBalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_BALANCE:50.00"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getCountries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "1": {"id": 1, "eng": "Ukraine", "visible": 1}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        assert!(provider.cached_balance().is_none());
+        assert!(provider.cached_countries().is_none());
+
+        provider.warmup().await.unwrap();
+
+        assert_eq!(provider.cached_balance(), Some(50.0));
+        let countries = provider.cached_countries().unwrap();
+        assert_eq!(countries.len(), 1);
+        assert_eq!(countries[0].eng, "Ukraine");
+    }
+
+    #[tokio::test]
+    async fn test_warmup_fails_on_bad_key() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getBalance"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("BAD_KEY"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let err = provider.warmup().await.unwrap_err();
+        assert!(err.is_bad_key());
+        assert!(provider.cached_balance().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_soft_retry_all_blacklisted() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "79001234567")),
+            )
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("status", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ACCESS_CANCEL"))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let mut provider = create_test_provider(&mock_server);
+        provider.blacklist_dial_code(DialCode::new("7").unwrap());
+
+        let err = provider
+            .get_number_with_soft_retry(Alpha2::UA.to_country(), Service::Whatsapp, 2)
+            .await
+            .unwrap_err();
+
+        match err {
+            HeroSmsError::AllNumbersBlacklisted {
+                attempts,
+                task_ids_cancelled,
+                task_ids_not_cancelled,
+            } => {
+                assert_eq!(attempts, 3);
+                assert_eq!(task_ids_cancelled.len(), 3);
+                assert!(task_ids_not_cancelled.is_empty());
+            }
+            _ => panic!("Expected AllNumbersBlacklisted, got {err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_soft_retry_keeps_uncancelled_task_ids() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "79001234567")),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "setStatus"))
+            .and(query_param("status", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("BAD_STATUS"))
+            .mount(&mock_server)
+            .await;
+
+        let mut provider = create_test_provider(&mock_server);
+        provider.blacklist_dial_code(DialCode::new("7").unwrap());
+
+        let err = provider
+            .get_number_with_soft_retry(Alpha2::UA.to_country(), Service::Whatsapp, 1)
+            .await
+            .unwrap_err();
+
+        match err {
+            HeroSmsError::AllNumbersBlacklisted {
+                attempts,
+                task_ids_cancelled,
+                task_ids_not_cancelled,
+            } => {
+                assert_eq!(attempts, 2);
+                assert!(task_ids_cancelled.is_empty());
+                assert_eq!(task_ids_not_cancelled, vec![TaskId::from("123456"); 2]);
+            }
+            _ => panic!("Expected AllNumbersBlacklisted, got {err:?}"),
+        }
+    }
+
     #[test]
     fn test_dial_code_blacklist() {
         let client = HeroSms::with_api_key("test_key").unwrap();
@@ -291,6 +2020,26 @@ mod tests {
         assert!(provider.is_dial_code_supported(&dial_code));
     }
 
+    #[tokio::test]
+    async fn test_get_phone_number_unmapped_country_fails_before_request() {
+        // Validation would need the network, so any HTTP call would fail differently
+        let client = HeroSms::new("http://127.0.0.1:9", "test_key").unwrap();
+        let provider = HeroSmsProvider::new(client).with_service_validation(true);
+        let antarctica = Alpha2::AQ.to_country();
+
+        assert!(!provider.is_country_supported(&antarctica));
+        assert!(provider.is_country_supported(&Alpha2::UA.to_country()));
+
+        let err = provider
+            .get_phone_number(antarctica, Service::Whatsapp)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            HeroSmsError::CountryMapping { ref country } if country.alpha2() == Alpha2::AQ
+        ));
+    }
+
     #[test]
     fn test_supports_service() {
         let client = HeroSms::with_api_key("test_key").unwrap();
@@ -325,4 +2074,61 @@ mod tests {
         assert!(services.contains(&Service::InstagramThreads));
         assert!(services.contains(&Service::Facebook));
     }
+
+    #[tokio::test]
+    async fn test_get_number_with_strategy_cheapest_first() {
+        use crate::providers::hero_sms::SmsCountryExt;
+
+        let mock_server = MockServer::start().await;
+        let ua_id = Alpha2::UA.to_country().sms_id().unwrap().to_string();
+        let kz_id = Alpha2::KZ.to_country().sms_id().unwrap().to_string();
+        let gb_id = Alpha2::GB.to_country().sms_id().unwrap().to_string();
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getPrices"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                ua_id.clone(): {"wa": {"cost": 20.0, "count": 5}},
+                kz_id.clone(): {"wa": {"cost": 5.0, "count": 3}},
+                gb_id.clone(): {"wa": {"cost": 1.0, "count": 0}},
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", kz_id.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_string("NO_NUMBERS"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumberV2"))
+            .and(query_param("country", ua_id.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(number_response("123456", "380501234567")),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+
+        let (task_id, _, country) = provider
+            .get_number_with_strategy(CountryPreferenceStrategy::AnyAvailable, Service::Whatsapp)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "123456");
+        assert_eq!(country.alpha2(), Alpha2::UA);
+
+        // Prices are served from the cache; GB is out of stock
+        let err = provider
+            .get_number_with_strategy(
+                CountryPreferenceStrategy::CheapestFrom(vec![
+                    Alpha2::GB.to_country(),
+                    Alpha2::KZ.to_country(),
+                ]),
+                Service::Whatsapp,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is_no_numbers());
+    }
 }
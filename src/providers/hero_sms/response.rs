@@ -1,6 +1,7 @@
 //! Response parsing for Hero SMS API.
 
-use super::errors::{HeroSmsServiceError, parse_hero_sms_error};
+use super::errors::{HeroSmsErrorCode, HeroSmsServiceError, parse_hero_sms_error};
+use serde::Deserialize;
 use serde::de::DeserializeOwned;
 
 /// Unified response type for Hero SMS API calls.
@@ -79,6 +80,102 @@ impl HeroSmsTextResponse {
     }
 }
 
+/// Parses raw response bodies into [`HeroSmsResponse`] / [`HeroSmsTextResponse`].
+///
+/// Each API version has its own error envelope, so the client picks the
+/// parser matching its configured [`ApiVersion`](super::client::ApiVersion).
+pub(crate) trait ResponseParser {
+    /// Parse a response whose success payload is JSON.
+    fn parse_json<T: DeserializeOwned>(text: &str)
+    -> Result<HeroSmsResponse<T>, serde_json::Error>;
+
+    /// Parse a response whose success payload is a status string.
+    fn parse_text(text: &str) -> HeroSmsTextResponse;
+}
+
+/// Parser for the legacy `handler_api.php` endpoint.
+///
+/// Errors are plain text codes, success payloads are JSON or plain text.
+pub(crate) struct LegacyResponseParser;
+
+impl ResponseParser for LegacyResponseParser {
+    fn parse_json<T: DeserializeOwned>(
+        text: &str,
+    ) -> Result<HeroSmsResponse<T>, serde_json::Error> {
+        HeroSmsResponse::from_text(text)
+    }
+
+    fn parse_text(text: &str) -> HeroSmsTextResponse {
+        HeroSmsTextResponse::from_text(text)
+    }
+}
+
+/// Structured error body returned by the v2 REST API.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2ErrorBody {
+    error_code: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl V2ErrorBody {
+    fn into_service_error(self, raw: &str) -> HeroSmsServiceError {
+        let code =
+            HeroSmsErrorCode::from_raw(&self.error_code).unwrap_or(HeroSmsErrorCode::Unknown {
+                raw: self.error_code,
+            });
+        let mut error = HeroSmsServiceError::new(code, raw.to_string());
+        if let Some(message) = self.message.filter(|m| !m.is_empty()) {
+            error.description = message;
+        }
+        error
+    }
+}
+
+/// Status body returned by v2 endpoints that have no payload (e.g. `setStatus`).
+#[derive(Debug, Deserialize)]
+struct V2StatusBody {
+    status: String,
+}
+
+/// Parser for the `/api/v2/` REST-JSON endpoint.
+///
+/// Errors are JSON objects of the form `{"errorCode": "...", "message": "..."}`.
+pub(crate) struct V2ResponseParser;
+
+impl V2ResponseParser {
+    fn parse_error(text: &str) -> Option<HeroSmsServiceError> {
+        serde_json::from_str::<V2ErrorBody>(text)
+            .ok()
+            .map(|body| body.into_service_error(text))
+    }
+}
+
+impl ResponseParser for V2ResponseParser {
+    fn parse_json<T: DeserializeOwned>(
+        text: &str,
+    ) -> Result<HeroSmsResponse<T>, serde_json::Error> {
+        if let Some(error) = Self::parse_error(text) {
+            return Ok(HeroSmsResponse::Error(error));
+        }
+
+        let data = serde_json::from_str::<T>(text)?;
+        Ok(HeroSmsResponse::Success(data))
+    }
+
+    fn parse_text(text: &str) -> HeroSmsTextResponse {
+        if let Some(error) = Self::parse_error(text) {
+            return HeroSmsTextResponse::Error(error);
+        }
+
+        match serde_json::from_str::<V2StatusBody>(text) {
+            Ok(body) => HeroSmsTextResponse::Success(body.status),
+            Err(_) => HeroSmsTextResponse::Success(text.trim().to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +239,66 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_v2_json_response_success() {
+        let json = r#"{
+            "activationId": "123456",
+            "phoneNumber": "79001234567",
+            "activationCost": 10.5,
+            "currency": 643,
+            "countryCode": "7",
+            "canGetAnotherSms": true,
+            "activationTime": "2025-01-01 12:00:00",
+            "activationEndTime": "2025-01-01 12:20:00",
+            "activationOperator": "mts"
+        }"#;
+
+        let response = V2ResponseParser::parse_json::<GetPhoneNumberResponse>(json).unwrap();
+        let data = response.into_result().unwrap();
+        assert_eq!(data.phone_number, "79001234567");
+    }
+
+    #[test]
+    fn test_v2_json_response_error() {
+        let text = r#"{"errorCode": "NO_NUMBERS", "message": "No free numbers"}"#;
+        let response = V2ResponseParser::parse_json::<GetPhoneNumberResponse>(text).unwrap();
+
+        match response.into_result() {
+            Err(error) => {
+                assert_eq!(error.code, HeroSmsErrorCode::NoNumbers);
+                assert_eq!(error.description, "No free numbers");
+                assert_eq!(error.raw, text);
+            }
+            Ok(_) => panic!("Expected error"),
+        }
+    }
+
+    #[test]
+    fn test_v2_unknown_error_code() {
+        let text = r#"{"errorCode": "SOMETHING_NEW"}"#;
+        let response = V2ResponseParser::parse_text(text);
+
+        match response {
+            HeroSmsTextResponse::Error(e) => {
+                assert_eq!(
+                    e.code,
+                    HeroSmsErrorCode::Unknown {
+                        raw: "SOMETHING_NEW".to_string()
+                    }
+                );
+            }
+            HeroSmsTextResponse::Success(_) => panic!("Expected error"),
+        }
+    }
+
+    #[test]
+    fn test_v2_text_response_success() {
+        let response = V2ResponseParser::parse_text(r#"{"status": "ACCESS_CANCEL"}"#);
+
+        match response {
+            HeroSmsTextResponse::Success(s) => assert_eq!(s, "ACCESS_CANCEL"),
+            HeroSmsTextResponse::Error(_) => panic!("Expected success"),
+        }
+    }
 }
@@ -1,7 +1,9 @@
 //! Types for SMS Activate API responses.
 
-use crate::types::TaskId;
+use crate::types::{ActivationState, FullNumber, PriceEntry, TaskId};
+use keshvar::Country;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 /// Response from SMS Activate getNumberV2 API call.
@@ -29,6 +31,100 @@ pub struct GetPhoneNumberResponse {
     pub activation_operator: String,
 }
 
+/// Price limit sent as the `maxPrice` parameter of getNumberV2.
+///
+/// Compares by bit pattern, so that [`GetNumberOptions`] can implement `Eq`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxPrice(f64);
+
+impl MaxPrice {
+    /// Create a price limit.
+    pub fn new(price: f64) -> Self {
+        Self(price)
+    }
+
+    /// Get the price limit.
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl From<f64> for MaxPrice {
+    fn from(price: f64) -> Self {
+        Self(price)
+    }
+}
+
+impl PartialEq for MaxPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for MaxPrice {}
+
+impl Display for MaxPrice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Optional parameters for getNumberV2 API call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GetNumberOptions {
+    /// Referral code sent as the `ref` parameter.
+    pub referral_code: Option<String>,
+    /// Affiliate ID sent as the `affiliate_id` parameter.
+    pub affiliate_id: Option<u64>,
+    /// Maximum time in seconds the server waits for a free number before
+    /// answering `NO_NUMBERS`, sent as the `maxTime` parameter.
+    ///
+    /// This limits server-side allocation, unlike the client-side polling
+    /// timeout of the SMS service.
+    pub max_wait_seconds: Option<u32>,
+    /// Highest price to pay for the number, sent as the `maxPrice` parameter.
+    ///
+    /// A limit below the current minimum fails with
+    /// [`HeroSmsError::MaxPriceTooLow`](super::errors::HeroSmsError::MaxPriceTooLow).
+    pub max_price: Option<MaxPrice>,
+    /// Number to forward incoming calls to, sent as the `forward` parameter.
+    ///
+    /// Must be a valid E.164 number, see [`FullNumber::is_valid_e164`].
+    pub forward_to: Option<String>,
+    /// Mobile operator to take the number from, sent as the `operator`
+    /// parameter.
+    ///
+    /// Names are lowercase and country-specific, e.g. `"mts"` or `"kyivstar"`.
+    pub operator: Option<String>,
+}
+
+impl GetNumberOptions {
+    /// Convert the set options into request parameters.
+    pub(crate) fn to_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(referral_code) = &self.referral_code {
+            params.push(("ref", referral_code.clone()));
+        }
+        if let Some(affiliate_id) = self.affiliate_id {
+            params.push(("affiliate_id", affiliate_id.to_string()));
+        }
+        if let Some(max_wait_seconds) = self.max_wait_seconds {
+            params.push(("maxTime", max_wait_seconds.to_string()));
+        }
+        if let Some(max_price) = self.max_price {
+            params.push(("maxPrice", max_price.to_string()));
+        }
+        if let Some(forward_to) = &self.forward_to {
+            params.push(("forward", FullNumber::new(forward_to).with_plus_prefix()));
+        }
+        if let Some(operator) = &self.operator {
+            params.push(("operator", operator.clone()));
+        }
+        params
+    }
+}
+
 /// Response from SMS Activate getStatusV2 API call.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -69,6 +165,168 @@ pub struct CallData {
     pub parsing_count: u32,
 }
 
+/// Country entry from getCountries API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryInfo {
+    /// Hero SMS country ID.
+    pub id: u16,
+    /// Russian country name.
+    #[serde(default)]
+    pub rus: String,
+    /// English country name.
+    #[serde(default)]
+    pub eng: String,
+    /// Chinese country name.
+    #[serde(default)]
+    pub chn: String,
+    /// Whether the country is shown on the site (1 = visible).
+    #[serde(default)]
+    pub visible: u8,
+    /// Whether repeated SMS are available (1 = available).
+    #[serde(default)]
+    pub retry: u8,
+    /// Whether number rent is available (1 = available).
+    #[serde(default)]
+    pub rent: u8,
+    /// Whether multi-service activations are available (1 = available).
+    #[serde(default)]
+    pub multi_service: u8,
+}
+
+/// Price and stock of a service in one country, from getPrices API call.
+pub type ServicePrice = PriceEntry;
+
+/// Response from getOperators API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOperatorsResponse {
+    /// Response status (`success` on success).
+    #[serde(default)]
+    pub status: String,
+    /// Operators keyed by Hero SMS country ID.
+    #[serde(default)]
+    pub country_operators: HashMap<String, Vec<String>>,
+}
+
+/// Response from getReputationLevel API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetReputationResponse {
+    /// Response status (`success` on success).
+    #[serde(default)]
+    pub status: String,
+    /// Account reputation score.
+    pub score: u32,
+    /// Reputation tier name.
+    pub level: String,
+    /// Hourly activation limit, if any.
+    #[serde(default)]
+    pub max_activations_per_hour: Option<u32>,
+}
+
+/// A service listed by the getServicesList API call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupportedService {
+    /// Service code used in requests, e.g. `"wa"`.
+    pub code: String,
+    /// Display name of the service.
+    pub name: String,
+}
+
+/// Response from getServicesList API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetServicesListResponse {
+    /// Response status (`success` on success).
+    #[serde(default)]
+    pub status: String,
+    /// Services available on the account.
+    #[serde(default)]
+    pub services: Vec<SupportedService>,
+}
+
+/// Response from getActiveActivations API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetActiveActivationsResponse {
+    /// Response status (`success` on success).
+    #[serde(default)]
+    pub status: String,
+    /// Activations currently in progress.
+    #[serde(default)]
+    pub active_activations: Vec<serde_json::Value>,
+}
+
+/// Connection pool statistics of a [`HeroSms`](super::HeroSms) client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Connections currently serving requests.
+    pub active: usize,
+    /// Open connections waiting in the pool.
+    pub idle: usize,
+}
+
+/// A mobile operator of a country, from getOperators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorInfo {
+    /// Operator name, as accepted by the `operator` request option.
+    pub name: String,
+    /// Whether the operator currently has numbers for any service.
+    pub has_numbers: bool,
+}
+
+/// A country and operator pair with numbers for sale, from
+/// getCountriesAndOperators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountryOperatorEntry {
+    /// Country the numbers belong to.
+    pub country: Country,
+    /// Operator name, as accepted by the `operator` request option.
+    pub operator: String,
+    /// Number of numbers in stock.
+    pub count: u32,
+    /// Activation cost in the account currency.
+    pub price: f64,
+}
+
+/// Raw entry of a getCountriesAndOperators response, keyed by Hero SMS
+/// country ID.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct RawCountryOperatorEntry {
+    /// Hero SMS country ID.
+    pub country: u16,
+    /// Operator name.
+    pub operator: String,
+    /// Number of numbers in stock.
+    #[serde(default)]
+    pub count: u32,
+    /// Activation cost.
+    #[serde(default)]
+    pub price: f64,
+}
+
+/// Whether a getNumbersStatus response reports any free number.
+///
+/// Counts are keyed by service and may be sent as strings or numbers.
+pub(crate) fn has_free_numbers(status: &HashMap<String, serde_json::Value>) -> bool {
+    status.values().any(|count| {
+        count
+            .as_u64()
+            .or_else(|| count.as_str()?.parse().ok())
+            .is_some_and(|count| count > 0)
+    })
+}
+
+/// Parse account balance from getBalance raw response (`ACCESS_BALANCE:<amount>`).
+pub(crate) fn parse_balance(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    raw.strip_prefix("ACCESS_BALANCE:")
+        .unwrap_or(raw)
+        .trim()
+        .parse::<f64>()
+        .ok()
+}
+
 /// Activation status codes for setStatus API call.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActivationStatus {
@@ -138,10 +396,70 @@ impl Display for SetStatusResponse {
     }
 }
 
+/// Response from getPhoneStatus API call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneStatus {
+    /// Waiting for an SMS.
+    WaitingSms,
+    /// An SMS has been received.
+    SmsSent,
+    /// Activation finished.
+    Finished,
+    /// Activation cancelled.
+    Cancelled,
+    /// Activation expired.
+    Expired,
+}
+
+impl PhoneStatus {
+    /// Parse response from raw API response text.
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        match raw.trim() {
+            "STATUS_WAIT_CODE" => Some(Self::WaitingSms),
+            "STATUS_FINISH" => Some(Self::Finished),
+            "STATUS_CANCEL" => Some(Self::Cancelled),
+            "STATUS_EXPIRED" => Some(Self::Expired),
+            raw if raw.starts_with("STATUS_OK") => Some(Self::SmsSent),
+            _ => None,
+        }
+    }
+}
+
+impl From<PhoneStatus> for ActivationState {
+    fn from(status: PhoneStatus) -> Self {
+        match status {
+            PhoneStatus::WaitingSms => Self::WaitingSms,
+            PhoneStatus::SmsSent => Self::SmsReceived,
+            PhoneStatus::Finished => Self::Finished,
+            PhoneStatus::Cancelled => Self::Cancelled,
+            PhoneStatus::Expired => Self::Expired,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_phone_status_from_raw() {
+        assert_eq!(
+            PhoneStatus::from_raw("STATUS_WAIT_CODE"),
+            Some(PhoneStatus::WaitingSms)
+        );
+        assert_eq!(
+            PhoneStatus::from_raw("STATUS_OK:123456"),
+            Some(PhoneStatus::SmsSent)
+        );
+        assert_eq!(
+            PhoneStatus::from_raw("STATUS_EXPIRED"),
+            Some(PhoneStatus::Expired)
+        );
+        assert_eq!(PhoneStatus::from_raw("STATUS_UNKNOWN"), None);
+        assert!(ActivationState::from(PhoneStatus::Cancelled).is_terminal());
+        assert!(!ActivationState::from(PhoneStatus::SmsSent).is_terminal());
+    }
+
     #[test]
     fn test_activation_status_code() {
         assert_eq!(ActivationStatus::RequestAnotherCode.code(), 3);
@@ -197,6 +515,70 @@ mod tests {
         assert_eq!(response.sms.unwrap().code, "123456");
     }
 
+    #[test]
+    fn test_max_price() {
+        assert_eq!(MaxPrice::new(12.5), MaxPrice::from(12.5));
+        assert_ne!(MaxPrice::new(12.5), MaxPrice::new(12.0));
+        assert_eq!(MaxPrice::new(12.5).get(), 12.5);
+        assert_eq!(MaxPrice::new(12.5).to_string(), "12.5");
+    }
+
+    #[test]
+    fn test_get_number_options_to_params() {
+        assert!(GetNumberOptions::default().to_params().is_empty());
+
+        let options = GetNumberOptions {
+            referral_code: Some("partner".to_string()),
+            affiliate_id: Some(42),
+            max_wait_seconds: Some(30),
+            max_price: Some(MaxPrice::new(12.5)),
+            forward_to: Some("12025550123".to_string()),
+            operator: Some("mts".to_string()),
+        };
+        assert_eq!(
+            options.to_params(),
+            vec![
+                ("ref", "partner".to_string()),
+                ("affiliate_id", "42".to_string()),
+                ("maxTime", "30".to_string()),
+                ("maxPrice", "12.5".to_string()),
+                ("forward", "+12025550123".to_string()),
+                ("operator", "mts".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_operators_response_deserialization() {
+        let json =
+            r#"{"status":"success","countryOperators":{"1":["kyivstar","lifecell","vodafone"]}}"#;
+        let response: GetOperatorsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.status, "success");
+        assert_eq!(
+            response.country_operators["1"],
+            vec!["kyivstar", "lifecell", "vodafone"]
+        );
+    }
+
+    #[test]
+    fn test_parse_balance() {
+        assert_eq!(parse_balance("ACCESS_BALANCE:100.50"), Some(100.5));
+        assert_eq!(parse_balance("ACCESS_BALANCE:0"), Some(0.0));
+        assert_eq!(parse_balance("42.1"), Some(42.1));
+        assert_eq!(parse_balance("ACCESS_BALANCE:abc"), None);
+    }
+
+    #[test]
+    fn test_country_info_deserialization() {
+        let json = r#"{"id": 1, "rus": "Украина", "eng": "Ukraine", "chn": "乌克兰", "visible": 1, "retry": 1, "rent": 0, "multiService": 1}"#;
+
+        let info: CountryInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.id, 1);
+        assert_eq!(info.eng, "Ukraine");
+        assert_eq!(info.visible, 1);
+        assert_eq!(info.multi_service, 1);
+    }
+
     #[test]
     fn test_get_sms_response_empty() {
         let json = r#"{}"#;
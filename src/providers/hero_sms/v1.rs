@@ -0,0 +1,420 @@
+//! Client and provider for the text-format v1 Hero SMS API.
+//!
+//! Accounts on the old plan request numbers with `getNumber`, which answers
+//! `ACCESS_NUMBER:<id>:<phone>`, and poll with `getStatus`, which answers
+//! `STATUS_*` codes instead of JSON.
+
+use super::client::HeroSms;
+use super::errors::{HeroSmsError, HeroSmsErrorCode, HeroSmsServiceError, Result};
+use super::services::Service;
+use super::types::{ActivationStatus, GetNumberOptions, SetStatusResponse};
+use crate::providers::traits::Provider;
+use crate::types::{FullNumber, SmsCode, TaskId};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+
+#[cfg(feature = "tracing")]
+use tracing::debug;
+
+/// Number acquired through the v1 `getNumber` action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessNumber {
+    /// Activation ID.
+    pub task_id: TaskId,
+    /// Phone number including the dial code.
+    pub phone_number: String,
+}
+
+impl AccessNumber {
+    /// Parse an `ACCESS_NUMBER:<id>:<phone>` response.
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        let mut parts = raw.trim().strip_prefix("ACCESS_NUMBER:")?.splitn(2, ':');
+        let task_id = parts.next().filter(|id| !id.is_empty())?;
+        let phone_number = parts.next().filter(|phone| !phone.is_empty())?;
+
+        Some(Self {
+            task_id: TaskId::from(task_id),
+            phone_number: phone_number.to_string(),
+        })
+    }
+}
+
+/// Activation status returned by the v1 `getStatus` action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusResponse {
+    /// Waiting for the SMS.
+    WaitCode,
+    /// Waiting for another SMS; carries the previous code.
+    WaitRetry { code: String },
+    /// Waiting for the SMS to be resent.
+    WaitResend,
+    /// Activation was cancelled.
+    Cancel,
+    /// SMS received.
+    Ok { code: String },
+}
+
+impl StatusResponse {
+    /// Parse response from raw API response text.
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if let Some(code) = raw.strip_prefix("STATUS_OK:") {
+            return Some(Self::Ok {
+                code: code.to_string(),
+            });
+        }
+        if let Some(code) = raw.strip_prefix("STATUS_WAIT_RETRY:") {
+            return Some(Self::WaitRetry {
+                code: code.to_string(),
+            });
+        }
+        match raw {
+            "STATUS_WAIT_CODE" => Some(Self::WaitCode),
+            "STATUS_WAIT_RESEND" => Some(Self::WaitResend),
+            "STATUS_CANCEL" => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+}
+
+/// Hero SMS client for the text-format v1 API.
+///
+/// Shares transport, country mapping and error handling with [`HeroSms`],
+/// which must use [`ApiVersion::Legacy`](super::ApiVersion::Legacy).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::hero_sms::{HeroSmsV1, Service};
+/// use sms_solvers::Alpha2;
+///
+/// let client = HeroSmsV1::with_api_key("your_api_key")?;
+/// let number = client.get_phone_number(Alpha2::US.to_country(), Service::Whatsapp).await?;
+/// println!("Got number: {}", number.phone_number);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeroSmsV1 {
+    client: HeroSms,
+}
+
+impl HeroSmsV1 {
+    /// Wrap a legacy-endpoint Hero SMS client.
+    pub fn new(client: HeroSms) -> Self {
+        Self { client }
+    }
+
+    /// Create a new client with the default API URL.
+    pub fn with_api_key(api_key: impl Into<String>) -> Result<Self> {
+        Ok(Self::new(HeroSms::with_api_key(api_key)?))
+    }
+
+    /// Get reference to the inner client.
+    pub fn client(&self) -> &HeroSms {
+        &self.client
+    }
+
+    /// Get a phone number for verification.
+    pub async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Service,
+    ) -> Result<AccessNumber> {
+        self.get_phone_number_with_options(country, service, &GetNumberOptions::default())
+            .await
+    }
+
+    /// Get a phone number for verification with additional request options.
+    pub async fn get_phone_number_with_options(
+        &self,
+        country: Country,
+        service: Service,
+        options: &GetNumberOptions,
+    ) -> Result<AccessNumber> {
+        let country_id = self.client.country_map().sms_id(&country).map_err(|_| {
+            HeroSmsError::CountryMapping {
+                country: Box::new(country),
+            }
+        })?;
+
+        let mut params = vec![
+            ("service", service.code().to_string()),
+            ("country", country_id.to_string()),
+        ];
+        params.extend(options.to_params());
+
+        let text = self.client.call("getNumber", params).await?;
+        let raw = self.client.parse_text(&text)?;
+
+        AccessNumber::from_raw(&raw).ok_or(HeroSmsError::FailedToParseNumberResponse { raw })
+    }
+
+    /// Get the status of an activation.
+    pub async fn get_status(&self, task_id: &TaskId) -> Result<StatusResponse> {
+        let text = self
+            .client
+            .call("getStatus", vec![("id", task_id.to_string())])
+            .await?;
+        let raw = self.client.parse_text(&text)?;
+
+        StatusResponse::from_raw(&raw).ok_or(HeroSmsError::FailedToParseStatusResponse { raw })
+    }
+
+    /// Set activation status.
+    pub async fn set_activation_status(
+        &self,
+        task_id: &TaskId,
+        status: ActivationStatus,
+    ) -> Result<SetStatusResponse> {
+        self.client.set_activation_status(task_id, status).await
+    }
+
+    /// Get the current account balance.
+    pub async fn get_balance(&self) -> Result<f64> {
+        self.client.get_balance().await
+    }
+}
+
+/// Provider implementation for the text-format v1 Hero SMS API.
+///
+/// Uses the same [`Service`] and [`HeroSmsError`] types as
+/// [`HeroSmsProvider`](super::HeroSmsProvider).
+#[derive(Debug, Clone)]
+pub struct HeroSmsV1Provider {
+    client: HeroSmsV1,
+}
+
+impl HeroSmsV1Provider {
+    /// Create a new v1 Hero SMS provider.
+    pub fn new(client: HeroSmsV1) -> Self {
+        Self { client }
+    }
+
+    /// Get reference to the inner client.
+    pub fn client(&self) -> &HeroSmsV1 {
+        &self.client
+    }
+}
+
+impl Provider for HeroSmsV1Provider {
+    type Error = HeroSmsError;
+    type Service = Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber)> {
+        let number = self.client.get_phone_number(country, service).await?;
+        Ok((number.task_id, FullNumber::from(number.phone_number)))
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>> {
+        match self.client.get_status(task_id).await? {
+            StatusResponse::Ok { code } if !code.is_empty() => Ok(Some(SmsCode::new(code))),
+            StatusResponse::Cancel => Err(HeroSmsError::Service(HeroSmsServiceError::new(
+                HeroSmsErrorCode::NoActivation,
+                "STATUS_CANCEL".to_string(),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<()> {
+        self.client
+            .set_activation_status(task_id, ActivationStatus::FinishActivation)
+            .await?;
+
+        #[cfg(feature = "tracing")]
+        debug!(task_id = %task_id, "Activation finished successfully");
+
+        Ok(())
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool> {
+        let response = self
+            .client
+            .set_activation_status(task_id, ActivationStatus::RequestAnotherCode)
+            .await?;
+
+        if response != SetStatusResponse::RetryGet {
+            return Err(HeroSmsError::FailedToParseSetStatusResponse {
+                raw: response.to_string(),
+            });
+        }
+
+        #[cfg(feature = "tracing")]
+        debug!(task_id = %task_id, "Requested another SMS code");
+
+        Ok(true)
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<()> {
+        self.client
+            .set_activation_status(task_id, ActivationStatus::CancelUsedNumber)
+            .await?;
+
+        #[cfg(feature = "tracing")]
+        debug!(task_id = %task_id, "Activation cancelled");
+
+        Ok(())
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.client.get_balance().await.map(|_| ())
+    }
+
+    fn supports_service(&self, _service: &Self::Service) -> bool {
+        true
+    }
+
+    fn available_countries(&self, _service: &Self::Service) -> Vec<Country> {
+        self.client.client().country_map().countries()
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        Service::all()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::HeroSms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+    use wiremock::matchers::{method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn create_test_provider(mock_server: &MockServer) -> HeroSmsV1Provider {
+        let client = HeroSms::new(mock_server.uri(), "test_key").unwrap();
+        HeroSmsV1Provider::new(HeroSmsV1::new(client))
+    }
+
+    #[test]
+    fn test_access_number_from_raw() {
+        assert_eq!(
+            AccessNumber::from_raw("ACCESS_NUMBER:123456:380501234567"),
+            Some(AccessNumber {
+                task_id: TaskId::from("123456"),
+                phone_number: "380501234567".to_string(),
+            })
+        );
+        assert_eq!(AccessNumber::from_raw("ACCESS_NUMBER:123456"), None);
+        assert_eq!(AccessNumber::from_raw("ACCESS_NUMBER::380501234567"), None);
+        assert_eq!(AccessNumber::from_raw("ACCESS_READY"), None);
+    }
+
+    #[test]
+    fn test_status_response_from_raw() {
+        assert_eq!(
+            StatusResponse::from_raw("STATUS_OK:54321"),
+            Some(StatusResponse::Ok {
+                code: "54321".to_string()
+            })
+        );
+        assert_eq!(
+            StatusResponse::from_raw("STATUS_WAIT_RETRY:54321"),
+            Some(StatusResponse::WaitRetry {
+                code: "54321".to_string()
+            })
+        );
+        assert_eq!(
+            StatusResponse::from_raw("STATUS_WAIT_CODE"),
+            Some(StatusResponse::WaitCode)
+        );
+        assert_eq!(
+            StatusResponse::from_raw("STATUS_CANCEL"),
+            Some(StatusResponse::Cancel)
+        );
+        assert_eq!(StatusResponse::from_raw("UNKNOWN"), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumber"))
+            .and(query_param("service", "wa"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("ACCESS_NUMBER:123456:380501234567"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let (task_id, full_number) = provider
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "123456");
+        assert_eq!(full_number.as_ref(), "380501234567");
+    }
+
+    #[tokio::test]
+    async fn test_get_phone_number_no_numbers() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getNumber"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("NO_NUMBERS"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        let err = provider
+            .get_phone_number(Alpha2::UA.to_country(), Service::Whatsapp)
+            .await
+            .unwrap_err();
+        assert!(err.is_no_numbers());
+    }
+
+    #[tokio::test]
+    async fn test_get_sms_code() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatus"))
+            .and(query_param("id", "waiting"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("STATUS_WAIT_CODE"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatus"))
+            .and(query_param("id", "received"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("STATUS_OK:54321"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(query_param("action", "getStatus"))
+            .and(query_param("id", "cancelled"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("STATUS_CANCEL"))
+            .mount(&mock_server)
+            .await;
+
+        let provider = create_test_provider(&mock_server);
+        assert_eq!(
+            provider
+                .get_sms_code(&TaskId::from("waiting"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            provider
+                .get_sms_code(&TaskId::from("received"))
+                .await
+                .unwrap(),
+            Some(SmsCode::new("54321"))
+        );
+        let err = provider
+            .get_sms_code(&TaskId::from("cancelled"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.as_service_code(), Some(&HeroSmsErrorCode::NoActivation));
+    }
+}
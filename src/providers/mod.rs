@@ -1,10 +1,24 @@
 //! SMS provider implementations.
 
+pub(crate) mod circuit_breaker;
+pub(crate) mod drop_cancel;
+pub(crate) mod fallback;
+pub(crate) mod phonebook;
+pub(crate) mod pool;
+pub(crate) mod rate_limited;
 pub(crate) mod retryable;
+pub(crate) mod statistics;
 pub(crate) mod traits;
 
 #[cfg(feature = "hero-sms")]
 pub mod hero_sms;
 
-pub use retryable::SmsRetryableProvider;
-pub use traits::Provider;
+pub use circuit_breaker::{CircuitBreakerError, CircuitBreakerProvider, CircuitState};
+pub use drop_cancel::{DropCancelError, DropCancelProvider, DropGuard, DropGuardFuture};
+pub use fallback::FallbackProvider;
+pub use phonebook::{PhoneBookEntry, PhoneBookError, PhoneBookProvider};
+pub use pool::{NumberPool, NumberPoolError};
+pub use rate_limited::RateLimitedProvider;
+pub use retryable::{RetryPredicate, SmsRetryableError, SmsRetryableProvider};
+pub use statistics::{OperationSnapshot, ProviderStatistics, StatisticsSnapshot};
+pub use traits::{Provider, SupportsIdempotencyKey};
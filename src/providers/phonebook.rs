@@ -0,0 +1,327 @@
+//! Provider that hands out numbers from a user-supplied pool.
+
+use super::traits::Provider;
+use crate::errors::RetryableError;
+use crate::types::{FullNumber, SmsCode, TaskId};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError};
+use thiserror::Error;
+
+/// A number in a [`PhoneBookProvider`] pool.
+#[derive(Debug, Clone)]
+pub struct PhoneBookEntry {
+    /// Task ID reported when this number is allocated.
+    pub task_id: TaskId,
+    /// The full phone number with country code.
+    pub full_number: FullNumber,
+    /// Country the number belongs to.
+    pub country: Country,
+    /// Codes returned by successive `get_sms_code` calls.
+    pub queued_codes: VecDeque<SmsCode>,
+}
+
+impl PhoneBookEntry {
+    /// Create an entry with the codes it will receive, in order.
+    pub fn new(
+        task_id: impl Into<TaskId>,
+        full_number: impl Into<FullNumber>,
+        country: Country,
+        codes: impl IntoIterator<Item = SmsCode>,
+    ) -> Self {
+        Self {
+            task_id: task_id.into(),
+            full_number: full_number.into(),
+            country,
+            queued_codes: codes.into_iter().collect(),
+        }
+    }
+}
+
+/// Errors returned by [`PhoneBookProvider`].
+#[derive(Debug, Clone, Error)]
+pub enum PhoneBookError {
+    /// No free number left for the requested country.
+    #[error("No phone book numbers left for country {}", country.iso_short_name())]
+    NoNumbers { country: Box<Country> },
+
+    /// The task ID does not belong to an allocated number.
+    #[error("Unknown phone book task {task_id}")]
+    UnknownTask { task_id: TaskId },
+}
+
+impl RetryableError for PhoneBookError {
+    fn is_retryable(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Default)]
+struct PhoneBook {
+    free: Vec<PhoneBookEntry>,
+    allocated: HashMap<TaskId, PhoneBookEntry>,
+}
+
+/// Provider that allocates numbers from a fixed pool instead of an external API.
+///
+/// Intended for integration tests. Numbers are handed out in pool order per
+/// country, each `get_sms_code` call pops the next queued code of the
+/// number, and `cancel_activation` returns the number to the pool. Clones
+/// share the same pool.
+///
+/// The service type is ignored; `S` only has to match the service type of
+/// the code under test.
+///
+/// # Example
+///
+/// ```rust
+/// use sms_solvers::{Alpha2, PhoneBookEntry, PhoneBookProvider, SmsCode};
+///
+/// let provider: PhoneBookProvider = PhoneBookProvider::new(vec![PhoneBookEntry::new(
+///     "task1",
+///     "380501234567",
+///     Alpha2::UA.to_country(),
+///     [SmsCode::new("123456")],
+/// )]);
+/// assert_eq!(provider.available(), 1);
+/// ```
+#[derive(Debug)]
+pub struct PhoneBookProvider<S = ()> {
+    book: Arc<Mutex<PhoneBook>>,
+    _service: PhantomData<fn() -> S>,
+}
+
+impl<S> Clone for PhoneBookProvider<S> {
+    fn clone(&self) -> Self {
+        Self {
+            book: Arc::clone(&self.book),
+            _service: PhantomData,
+        }
+    }
+}
+
+impl<S> PhoneBookProvider<S> {
+    /// Create a provider serving the given entries.
+    pub fn new(entries: Vec<PhoneBookEntry>) -> Self {
+        Self {
+            book: Arc::new(Mutex::new(PhoneBook {
+                free: entries,
+                allocated: HashMap::new(),
+            })),
+            _service: PhantomData,
+        }
+    }
+
+    /// Number of free entries in the pool.
+    pub fn available(&self) -> usize {
+        self.lock().free.len()
+    }
+
+    /// Number of allocated entries.
+    pub fn allocated(&self) -> usize {
+        self.lock().allocated.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PhoneBook> {
+        self.book.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S: Clone + Send + Sync> Provider for PhoneBookProvider<S> {
+    type Error = PhoneBookError;
+    type Service = S;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        _service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        let mut book = self.lock();
+        let index = book
+            .free
+            .iter()
+            .position(|entry| entry.country.alpha2() == country.alpha2())
+            .ok_or_else(|| PhoneBookError::NoNumbers {
+                country: Box::new(country),
+            })?;
+
+        let entry = book.free.remove(index);
+        let result = (entry.task_id.clone(), entry.full_number.clone());
+        book.allocated.insert(entry.task_id.clone(), entry);
+
+        Ok(result)
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        let mut book = self.lock();
+        let entry = book
+            .allocated
+            .get_mut(task_id)
+            .ok_or_else(|| PhoneBookError::UnknownTask {
+                task_id: task_id.clone(),
+            })?;
+
+        Ok(entry.queued_codes.pop_front())
+    }
+
+    /// Polls every task under a single lock.
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        let mut book = self.lock();
+        task_ids
+            .iter()
+            .map(|task_id| {
+                book.allocated
+                    .get_mut(task_id)
+                    .map(|entry| entry.queued_codes.pop_front())
+                    .ok_or_else(|| PhoneBookError::UnknownTask {
+                        task_id: task_id.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.lock()
+            .allocated
+            .remove(task_id)
+            .map(|_| ())
+            .ok_or_else(|| PhoneBookError::UnknownTask {
+                task_id: task_id.clone(),
+            })
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        let mut book = self.lock();
+        let entry = book
+            .allocated
+            .remove(task_id)
+            .ok_or_else(|| PhoneBookError::UnknownTask {
+                task_id: task_id.clone(),
+            })?;
+        book.free.push(entry);
+
+        Ok(())
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        Ok(self.lock().allocated.contains_key(task_id))
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::PhoneBook
+    }
+
+    fn available_countries(&self, _service: &Self::Service) -> Vec<Country> {
+        let book = self.lock();
+        let mut countries: Vec<Country> = Vec::new();
+        for entry in &book.free {
+            if !countries
+                .iter()
+                .any(|c| c.alpha2() == entry.country.alpha2())
+            {
+                countries.push(entry.country.clone());
+            }
+        }
+        countries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+
+    fn provider() -> PhoneBookProvider {
+        PhoneBookProvider::new(vec![
+            PhoneBookEntry::new(
+                "ua1",
+                "380501234567",
+                Alpha2::UA.to_country(),
+                [SmsCode::new("111111"), SmsCode::new("222222")],
+            ),
+            PhoneBookEntry::new("us1", "12025550123", Alpha2::US.to_country(), []),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_allocates_by_country() {
+        let provider = provider();
+
+        let (task_id, full_number) = provider
+            .get_phone_number(Alpha2::US.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "us1");
+        assert_eq!(full_number.as_ref(), "12025550123");
+        assert_eq!(provider.available(), 1);
+        assert_eq!(provider.allocated(), 1);
+
+        let err = provider
+            .get_phone_number(Alpha2::US.to_country(), ())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PhoneBookError::NoNumbers { .. }));
+        assert!(!err.should_retry_operation());
+    }
+
+    #[tokio::test]
+    async fn test_pops_queued_codes() {
+        let provider = provider();
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+
+        let codes = [
+            provider.get_sms_code(&task_id).await.unwrap(),
+            provider.get_sms_code(&task_id).await.unwrap(),
+            provider.get_sms_code(&task_id).await.unwrap(),
+        ];
+        assert_eq!(
+            codes,
+            [
+                Some(SmsCode::new("111111")),
+                Some(SmsCode::new("222222")),
+                None
+            ]
+        );
+
+        provider.finish_activation(&task_id).await.unwrap();
+        assert_eq!(provider.allocated(), 0);
+        assert!(matches!(
+            provider.get_sms_code(&task_id).await,
+            Err(PhoneBookError::UnknownTask { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_returns_entry_to_pool() {
+        let provider = provider();
+        let clone = provider.clone();
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert!(
+            !clone
+                .available_countries(&())
+                .iter()
+                .any(|c| c.alpha2() == Alpha2::UA)
+        );
+
+        clone.cancel_activation(&task_id).await.unwrap();
+        assert_eq!(provider.available(), 2);
+
+        let (again, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), ())
+            .await
+            .unwrap();
+        assert_eq!(again, task_id);
+    }
+}
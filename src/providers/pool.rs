@@ -0,0 +1,558 @@
+//! Pool of pre-fetched phone numbers.
+
+use super::traits::Provider;
+use crate::errors::RetryableError;
+use crate::types::{DialCode, Number, SmsTaskResult, TaskId};
+use keshvar::Country;
+use std::collections::VecDeque;
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "tracing")]
+use tracing::{debug, warn};
+
+/// Delay before a failed pre-fetch is first retried.
+const REFILL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound of the delay between failed pre-fetches.
+const REFILL_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Errors returned by [`NumberPool`].
+#[derive(Debug, Clone, Error)]
+pub enum NumberPoolError {
+    /// The pool was drained and no longer hands out numbers.
+    #[error("Number pool is closed")]
+    Closed,
+
+    /// Pre-fetching failed with an error that retrying cannot fix, such as
+    /// a rejected API key, so the pool stopped.
+    #[error("Number pool stopped after a provider error: {0}")]
+    Provider(Arc<dyn StdError + Send + Sync>),
+}
+
+impl RetryableError for NumberPoolError {
+    fn is_retryable(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    /// Pre-fetched activations, oldest first.
+    queue: Mutex<VecDeque<SmsTaskResult>>,
+    /// Free places in the pool; a place is taken before a pre-fetch starts.
+    slots: Semaphore,
+    /// Pre-fetch requests allowed to run at once.
+    prefetch: Semaphore,
+    /// Activations waiting in `queue`.
+    ready: Semaphore,
+    /// Delay before the next pre-fetch; grows while pre-fetches fail.
+    backoff: Mutex<Duration>,
+    /// The error that stopped the pool, if any.
+    failure: Mutex<Option<NumberPoolError>>,
+    /// Cancelled once the pool is closed.
+    closed: CancellationToken,
+}
+
+impl PoolState {
+    /// Add a pre-fetched activation, or return it if the pool was closed.
+    fn push(&self, task: SmsTaskResult) -> Option<SmsTaskResult> {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.slots.is_closed() {
+            return Some(task);
+        }
+        queue.push_back(task);
+        self.ready.add_permits(1);
+        None
+    }
+
+    /// Stop refilling and wake every waiter.
+    ///
+    /// Callers that need the queue emptied atomically with closing hold its
+    /// lock while calling this.
+    fn close(&self) {
+        self.slots.close();
+        self.prefetch.close();
+        self.ready.close();
+        self.closed.cancel();
+    }
+
+    /// Close the pool because of a permanent provider error.
+    fn fail(&self, error: NumberPoolError) {
+        self.failure
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(error);
+        self.close();
+    }
+
+    /// The error to return once the pool is closed.
+    fn closed_error(&self) -> NumberPoolError {
+        self.failure
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .unwrap_or(NumberPoolError::Closed)
+    }
+
+    fn lock_backoff(&self) -> MutexGuard<'_, Duration> {
+        self.backoff.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Double the delay before the next pre-fetch, honouring `retry_after`.
+    fn record_failure(&self, retry_after: Option<Duration>) {
+        let mut backoff = self.lock_backoff();
+        *backoff = (*backoff * 2)
+            .max(REFILL_RETRY_DELAY)
+            .max(retry_after.unwrap_or_default())
+            .min(REFILL_MAX_RETRY_DELAY);
+    }
+}
+
+/// Pool that keeps phone numbers for one country and service ready ahead of
+/// time.
+///
+/// Acquiring a number from a provider usually takes hundreds of
+/// milliseconds. The pool runs a background task that keeps up to
+/// `target_size` activations pre-fetched, with at most `max_prefetch`
+/// requests in flight, so [`acquire`](Self::acquire) returns immediately
+/// while the pool is populated.
+///
+/// Failed pre-fetches are retried with an exponential backoff of up to a
+/// minute. An error that retrying cannot fix, i.e. one that is neither
+/// retryable nor worth a new operation, stops the pool and is returned by
+/// [`acquire`](Self::acquire).
+///
+/// Pre-fetched activations are paid for. Call
+/// [`drain_and_cancel`](Self::drain_and_cancel) before dropping the pool to
+/// see the cancel results; otherwise dropping it, or a permanent error,
+/// cancels queued activations in the background. Pre-fetch requests already
+/// in flight finish and their activations are cancelled as they arrive.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::{Alpha2, NumberPool};
+///
+/// let pool = NumberPool::new(provider, Alpha2::US.to_country(), Service::Whatsapp, 5, 2);
+/// let task = pool.acquire().await?;
+/// // ...
+/// pool.drain_and_cancel().await;
+/// ```
+#[derive(Debug)]
+pub struct NumberPool<P: Provider> {
+    provider: P,
+    state: Arc<PoolState>,
+}
+
+impl<P> NumberPool<P>
+where
+    P: Provider + 'static,
+    P::Service: 'static,
+{
+    /// Create a pool and start filling it in the background.
+    ///
+    /// Must be called from within a Tokio runtime. `target_size` and
+    /// `max_prefetch` are raised to at least 1.
+    pub fn new(
+        provider: P,
+        country: Country,
+        service: P::Service,
+        target_size: usize,
+        max_prefetch: usize,
+    ) -> Self {
+        let state = Arc::new(PoolState {
+            queue: Mutex::new(VecDeque::new()),
+            slots: Semaphore::new(target_size.max(1)),
+            prefetch: Semaphore::new(max_prefetch.max(1)),
+            ready: Semaphore::new(0),
+            backoff: Mutex::new(Duration::ZERO),
+            failure: Mutex::new(None),
+            closed: CancellationToken::new(),
+        });
+        tokio::spawn(refill(
+            provider.clone(),
+            country,
+            service,
+            Arc::clone(&state),
+        ));
+
+        Self { provider, state }
+    }
+
+    /// Take a pre-fetched number, waiting for one if the pool is empty.
+    ///
+    /// Numbers that expired while queued are cancelled and skipped. Returns
+    /// [`NumberPoolError::Closed`] once the pool has been drained, or
+    /// [`NumberPoolError::Provider`] if it stopped after a permanent error.
+    pub async fn acquire(&self) -> Result<SmsTaskResult, NumberPoolError> {
+        loop {
+            self.state
+                .ready
+                .acquire()
+                .await
+                .map_err(|_| self.state.closed_error())?
+                .forget();
+
+            let task = self
+                .state
+                .queue
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop_front()
+                .ok_or_else(|| self.state.closed_error())?;
+            self.state.slots.add_permits(1);
+
+            if !task.is_expired() {
+                return Ok(task);
+            }
+
+            #[cfg(feature = "tracing")]
+            debug!(task_id = %task.task_id, "Pre-fetched number expired, cancelling");
+
+            cancel(&self.provider, &task.task_id).await;
+        }
+    }
+
+    /// Number of pre-fetched numbers ready to be acquired.
+    pub fn len(&self) -> usize {
+        self.state
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns true if no pre-fetched number is ready.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop refilling and cancel every pre-fetched activation.
+    ///
+    /// Returns the task ID and cancel result of each drained activation.
+    /// Activations whose pre-fetch is still in flight are cancelled as soon
+    /// as they arrive. Pending and later [`acquire`](Self::acquire) calls
+    /// fail with [`NumberPoolError::Closed`].
+    pub async fn drain_and_cancel(&self) -> Vec<(TaskId, Result<(), P::Error>)> {
+        let task_ids: Vec<TaskId> = {
+            let mut queue = self
+                .state
+                .queue
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            self.state.close();
+            queue.drain(..).map(|task| task.task_id).collect()
+        };
+
+        #[cfg(feature = "tracing")]
+        debug!(count = task_ids.len(), "Cancelling pre-fetched activations");
+
+        let results = self.provider.cancel_all_pending(&task_ids).await;
+        task_ids.into_iter().zip(results).collect()
+    }
+}
+
+impl<P: Provider> Drop for NumberPool<P> {
+    fn drop(&mut self) {
+        // The refill task cancels whatever is still queued as it exits
+        self.state.close();
+    }
+}
+
+/// Keep the pool filled until it is closed, then cancel what is left in it.
+async fn refill<P>(provider: P, country: Country, service: P::Service, state: Arc<PoolState>)
+where
+    P: Provider + 'static,
+    P::Service: 'static,
+{
+    loop {
+        let Ok(slot) = state.slots.acquire().await else {
+            break;
+        };
+        slot.forget();
+        let Ok(prefetch) = state.prefetch.acquire().await else {
+            break;
+        };
+        prefetch.forget();
+
+        let delay = *state.lock_backoff();
+        if !delay.is_zero()
+            && tokio::time::timeout(delay, state.closed.cancelled())
+                .await
+                .is_ok()
+        {
+            break;
+        }
+
+        let provider = provider.clone();
+        let country = country.clone();
+        let service = service.clone();
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let result = prefetch_one(&provider, country, service).await;
+
+            match result {
+                Ok(task) => {
+                    *state.lock_backoff() = Duration::ZERO;
+                    if let Some(task) = state.push(task) {
+                        cancel(&provider, &task.task_id).await;
+                    }
+                }
+                Err(Some(e)) if !e.is_retryable() && !e.should_retry_operation() => {
+                    #[cfg(feature = "tracing")]
+                    warn!(error = %e, "Pre-fetching failed permanently, stopping the pool");
+
+                    state.fail(NumberPoolError::Provider(Arc::new(e)));
+                }
+                Err(e) => {
+                    state.record_failure(e.as_ref().and_then(P::Error::retry_after));
+                    state.slots.add_permits(1);
+                }
+            }
+            state.prefetch.add_permits(1);
+        });
+    }
+
+    let task_ids: Vec<TaskId> = state
+        .queue
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .drain(..)
+        .map(|task| task.task_id)
+        .collect();
+    if task_ids.is_empty() {
+        return;
+    }
+
+    #[cfg(feature = "tracing")]
+    debug!(
+        count = task_ids.len(),
+        "Pool closed, cancelling pre-fetched activations"
+    );
+
+    let _results = provider.cancel_all_pending(&task_ids).await;
+
+    #[cfg(feature = "tracing")]
+    for (task_id, result) in task_ids.iter().zip(&_results) {
+        if let Err(e) = result {
+            warn!(task_id = %task_id, error = %e, "Failed to cancel pre-fetched activation");
+        }
+    }
+}
+
+/// Acquire one number, cancelling it if it cannot be used.
+///
+/// Fails with the provider error, or `None` if the number was unusable.
+async fn prefetch_one<P: Provider>(
+    provider: &P,
+    country: Country,
+    service: P::Service,
+) -> Result<SmsTaskResult, Option<P::Error>> {
+    let (task_id, full_number, metadata) = provider
+        .get_phone_number_with_metadata(country.clone(), service)
+        .await
+        .map_err(|e| {
+            #[cfg(feature = "tracing")]
+            warn!(error = %e, "Failed to pre-fetch a number");
+            Some(e)
+        })?;
+
+    let dial_code = DialCode::from(&country);
+    let number = match Number::from_full_number(&full_number, &dial_code) {
+        Ok(number) if provider.is_dial_code_supported(&dial_code) => number,
+        _ => {
+            #[cfg(feature = "tracing")]
+            warn!(task_id = %task_id, "Pre-fetched number is unusable, cancelling");
+            cancel(provider, &task_id).await;
+            return Err(None);
+        }
+    };
+
+    Ok(SmsTaskResult {
+        task_id,
+        dial_code,
+        number,
+        full_number,
+        country,
+        metadata,
+    })
+}
+
+async fn cancel<P: Provider>(provider: &P, task_id: &TaskId) {
+    if let Err(_e) = provider.cancel_activation(task_id).await {
+        #[cfg(feature = "tracing")]
+        warn!(task_id = %task_id, error = %_e, "Failed to cancel pre-fetched activation");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockError, MockProvider, MockService};
+    use crate::types::ActivationMetadata;
+    use keshvar::Alpha2;
+    use tokio::time::Instant;
+
+    const FETCH_DELAY: Duration = Duration::from_millis(300);
+
+    fn mock() -> MockProvider {
+        MockProvider::builder()
+            .get_number_delay(FETCH_DELAY)
+            .build()
+    }
+
+    fn pool(
+        provider: &MockProvider,
+        target_size: usize,
+        max_prefetch: usize,
+    ) -> NumberPool<MockProvider> {
+        NumberPool::new(
+            provider.clone(),
+            Alpha2::UA.to_country(),
+            MockService,
+            target_size,
+            max_prefetch,
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_is_immediate_once_populated() {
+        let provider = mock();
+        let pool = pool(&provider, 3, 3);
+
+        let started = Instant::now();
+        let first = pool.acquire().await.unwrap();
+        assert_eq!(started.elapsed(), FETCH_DELAY);
+        assert_eq!(first.number.as_str(), "501234567");
+        assert_eq!(first.country.alpha2(), Alpha2::UA);
+
+        tokio::time::sleep(FETCH_DELAY * 2).await;
+        assert_eq!(pool.len(), 3);
+
+        let started = Instant::now();
+        for _ in 0..3 {
+            pool.acquire().await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pool_respects_target_size_and_prefetch_limit() {
+        let provider = mock();
+        let pool = pool(&provider, 2, 1);
+
+        tokio::time::sleep(FETCH_DELAY).await;
+        tokio::task::yield_now().await;
+        assert_eq!(pool.len(), 1);
+
+        tokio::time::sleep(FETCH_DELAY * 10).await;
+        assert_eq!(pool.len(), 2);
+        assert_eq!(provider.call_count_get_number(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_and_cancel() {
+        let provider = mock();
+        let pool = pool(&provider, 2, 2);
+
+        tokio::time::sleep(FETCH_DELAY * 2).await;
+        let results = pool.drain_and_cancel().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(provider.cancelled_tasks().len(), 2);
+        assert!(pool.is_empty());
+        assert!(matches!(pool.acquire().await, Err(NumberPoolError::Closed)));
+
+        tokio::time::sleep(FETCH_DELAY * 10).await;
+        assert_eq!(provider.call_count_get_number(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_cancels_in_flight_prefetch() {
+        let provider = mock();
+        let pool = pool(&provider, 1, 1);
+
+        tokio::task::yield_now().await;
+        assert!(pool.drain_and_cancel().await.is_empty());
+
+        tokio::time::sleep(FETCH_DELAY * 2).await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_prefetch_backs_off() {
+        let provider = MockProvider::builder()
+            .get_number_delay(FETCH_DELAY)
+            .next_get_number_response(Err(MockError::Transient))
+            .next_get_number_response(Err(MockError::Transient))
+            .next_get_number_response(Err(MockError::Transient))
+            .build();
+        let pool = pool(&provider, 1, 1);
+
+        let started = Instant::now();
+        let task = pool.acquire().await.unwrap();
+
+        // Four fetches, separated by backoffs of 1s, 2s and 4s
+        assert_eq!(started.elapsed(), FETCH_DELAY * 4 + Duration::from_secs(7));
+        assert_eq!(task.task_id.as_ref(), "mock-task-4");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_permanent_error_stops_pool() {
+        let provider = MockProvider::builder()
+            .get_number_delay(FETCH_DELAY)
+            .next_get_number_response(Err(MockError::Permanent("BAD_KEY".into())))
+            .build();
+        let pool = pool(&provider, 2, 1);
+
+        assert!(matches!(
+            pool.acquire().await,
+            Err(NumberPoolError::Provider(ref e)) if e.to_string() == "Mock error: BAD_KEY"
+        ));
+
+        tokio::time::sleep(FETCH_DELAY * 10).await;
+        assert_eq!(provider.call_count_get_number(), 1);
+        assert!(matches!(
+            pool.acquire().await,
+            Err(NumberPoolError::Provider(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_skips_expired_numbers() {
+        let provider = mock();
+        provider.set_metadata(Some(ActivationMetadata {
+            expires_at: Some("2000-01-01 00:00:00".to_string()),
+            ..Default::default()
+        }));
+        let pool = pool(&provider, 1, 1);
+
+        // Only the first prefetch gets an already expired number
+        tokio::task::yield_now().await;
+        provider.set_metadata(None);
+        let task = pool.acquire().await.unwrap();
+
+        assert!(!task.is_expired());
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drop_cancels_queued_numbers() {
+        let provider = mock();
+        let pool = pool(&provider, 2, 2);
+
+        tokio::time::sleep(FETCH_DELAY * 2).await;
+        assert_eq!(pool.len(), 2);
+        drop(pool);
+
+        tokio::time::sleep(FETCH_DELAY).await;
+        assert_eq!(provider.cancelled_tasks().len(), 2);
+        assert_eq!(provider.call_count_get_number(), 2);
+    }
+}
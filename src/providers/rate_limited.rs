@@ -0,0 +1,386 @@
+//! Provider wrapper that spaces out API calls to a fixed rate.
+
+use super::traits::{Provider, SupportsIdempotencyKey};
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+
+/// Token bucket shared by the clones of a [`RateLimitedProvider`].
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens ready to be spent; one is consumed per call.
+    tokens: Arc<Semaphore>,
+    /// Task adding one token per refill period.
+    refiller: JoinHandle<()>,
+}
+
+impl TokenBucket {
+    fn new(requests_per_second: f64, burst_size: u32) -> Self {
+        assert!(
+            requests_per_second.is_finite() && requests_per_second > 0.0,
+            "requests_per_second must be positive and finite, got {requests_per_second}"
+        );
+
+        let burst_size = burst_size.max(1) as usize;
+        let period = Duration::from_secs_f64(1.0 / requests_per_second);
+        let tokens = Arc::new(Semaphore::new(burst_size));
+        let refiller = tokio::spawn(refill(Arc::clone(&tokens), period, burst_size));
+
+        Self { tokens, refiller }
+    }
+
+    /// Wait for a token and spend it.
+    async fn take(&self) {
+        // The semaphore is never closed.
+        if let Ok(permit) = self.tokens.acquire().await {
+            permit.forget();
+        }
+    }
+}
+
+impl Drop for TokenBucket {
+    fn drop(&mut self) {
+        self.refiller.abort();
+    }
+}
+
+/// Add one token every `period`, keeping at most `burst_size` in the bucket.
+async fn refill(tokens: Arc<Semaphore>, period: Duration, burst_size: usize) {
+    let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        if tokens.available_permits() < burst_size {
+            tokens.add_permits(1);
+        }
+    }
+}
+
+/// Wrapper that limits how often the inner provider is called.
+///
+/// Some accounts get banned for exceeding a request rate long before the
+/// API starts rejecting requests. Every async provider call first takes a
+/// token from a bucket refilled at `requests_per_second`; calls over the
+/// budget wait for the next token instead of failing. Up to `burst_size`
+/// unused tokens are saved for short bursts.
+///
+/// Clones share the same budget. The refill task is stopped once the last
+/// clone is dropped.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::RateLimitedProvider;
+///
+/// // At most one call every two seconds, allowing bursts of 3
+/// let provider = RateLimitedProvider::with_burst_size(base_provider, 0.5, 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimitedProvider<P: Provider> {
+    inner: P,
+    bucket: Arc<TokenBucket>,
+}
+
+impl<P: Provider> RateLimitedProvider<P> {
+    /// Wrap a provider, allowing `requests_per_second` calls per second
+    /// without bursts.
+    ///
+    /// Fractional rates such as `0.5` are allowed. Must be called from
+    /// within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not positive and finite.
+    pub fn new(inner: P, requests_per_second: f64) -> Self {
+        Self::with_burst_size(inner, requests_per_second, 1)
+    }
+
+    /// Wrap a provider, allowing `requests_per_second` calls per second and
+    /// bursts of up to `burst_size` calls.
+    ///
+    /// The bucket starts full. A `burst_size` of 0 is treated as 1. Must be
+    /// called from within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not positive and finite.
+    pub fn with_burst_size(inner: P, requests_per_second: f64, burst_size: u32) -> Self {
+        Self {
+            inner,
+            bucket: Arc::new(TokenBucket::new(requests_per_second, burst_size)),
+        }
+    }
+
+    /// Get a reference to the inner provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: Provider> Provider for RateLimitedProvider<P> {
+    type Error = P::Error;
+    type Service = P::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_phone_number(country, service).await
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.bucket.take().await;
+        self.inner
+            .get_phone_number_with_metadata(country, service)
+            .await
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.bucket.take().await;
+        self.inner
+            .get_phone_number_with_idempotency_key(country, service, idempotency_key)
+            .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_phone_number_for_any_country(service).await
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_sms_code(task_id).await
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_sms_code_with_text(task_id).await
+    }
+
+    /// Takes one token per task, since the inner provider may poll each
+    /// task with its own request.
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        for _ in task_ids {
+            self.bucket.take().await;
+        }
+        self.inner.get_sms_codes_batch(task_ids).await
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_all_sms_codes(task_id).await
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_all_sms_codes_with_text(task_id).await
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.get_activation_status(task_id).await
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.bucket.take().await;
+        self.inner.request_another_code(task_id).await
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.bucket.take().await;
+        self.inner.finish_activation(task_id).await
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.bucket.take().await;
+        self.inner.cancel_activation(task_id).await
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.bucket.take().await;
+        self.inner.is_task_alive(task_id).await
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.bucket.take().await;
+        self.inner.warmup().await
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.account_reputation().await
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.country_prices(service).await
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.bucket.take().await;
+        self.inner.available_countries_with_counts(service).await
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.bucket.take().await;
+        self.inner.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.inner.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.inner.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        self.inner.available_countries(service)
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.inner.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.inner.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+impl<P: SupportsIdempotencyKey> SupportsIdempotencyKey for RateLimitedProvider<P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockProvider;
+    use futures::future::join_all;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_calls_over_budget_are_delayed() {
+        let provider = RateLimitedProvider::new(MockProvider::default(), 2.0);
+        let task_id = TaskId::new("task");
+        let started = Instant::now();
+
+        for _ in 0..5 {
+            provider.get_sms_code(&task_id).await.unwrap();
+        }
+
+        // One token up front, then one every 500ms
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        assert_eq!(provider.inner().call_count_get_sms_code(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fractional_rate() {
+        let provider = RateLimitedProvider::new(MockProvider::default(), 0.25);
+        let task_id = TaskId::new("task");
+        let started = Instant::now();
+
+        provider.get_sms_code(&task_id).await.unwrap();
+        provider.get_sms_code(&task_id).await.unwrap();
+
+        assert_eq!(started.elapsed(), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_is_not_delayed() {
+        let provider = RateLimitedProvider::with_burst_size(MockProvider::default(), 1.0, 3);
+        let task_id = TaskId::new("task");
+        let started = Instant::now();
+
+        for _ in 0..3 {
+            provider.get_sms_code(&task_id).await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+
+        provider.get_sms_code(&task_id).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_time_refills_up_to_burst_size() {
+        let provider = RateLimitedProvider::with_burst_size(MockProvider::default(), 1.0, 2);
+        let task_id = TaskId::new("task");
+
+        provider.get_sms_code(&task_id).await.unwrap();
+        provider.get_sms_code(&task_id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10_500)).await;
+
+        let started = Instant::now();
+        provider.get_sms_code(&task_id).await.unwrap();
+        provider.get_sms_code(&task_id).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::ZERO);
+
+        provider.get_sms_code(&task_id).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_calls_share_budget_and_all_complete() {
+        let provider = RateLimitedProvider::new(MockProvider::default(), 10.0);
+        let clone = provider.clone();
+        let task_id = TaskId::new("task");
+        let started = Instant::now();
+
+        let results = join_all((0..10).map(|i| {
+            let provider = if i % 2 == 0 { &provider } else { &clone };
+            provider.get_sms_code(&task_id)
+        }))
+        .await;
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(provider.inner().call_count_get_sms_code(), 10);
+        assert_eq!(started.elapsed(), Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "requests_per_second must be positive")]
+    async fn test_rejects_zero_rate() {
+        RateLimitedProvider::new(MockProvider::default(), 0.0);
+    }
+}
@@ -1,18 +1,106 @@
 //! Retryable provider wrapper.
 
-use super::traits::Provider;
+#[cfg(feature = "hero-sms")]
+use super::hero_sms::{HeroSmsError, HeroSmsErrorCode, HeroSmsProvider};
+use super::traits::{Provider, SupportsIdempotencyKey};
 use crate::errors::RetryableError;
-use crate::types::{DialCode, FullNumber, SmsCode, TaskId};
-use crate::utils::retry::RetryConfig;
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use crate::utils::retry::{RetryBudget, RetryConfig};
 use backon::Retryable;
+use futures::future::join_all;
 use keshvar::Country;
+#[cfg(feature = "hero-sms")]
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use thiserror::Error;
 
 #[cfg(feature = "tracing")]
 use tracing::debug;
 
+/// Errors returned by [`SmsRetryableProvider`].
+#[derive(Debug, Error)]
+pub enum SmsRetryableError<E> {
+    /// A retry was needed, but the retry budget for the current window is
+    /// used up.
+    #[error("Retry budget exhausted, retries are allowed again after the window resets")]
+    RetryBudgetExhausted {
+        /// When the budget is refilled.
+        resets_at: Instant,
+    },
+
+    /// The wrapped provider returned an error.
+    #[error(transparent)]
+    Provider(E),
+}
+
+impl<E> SmsRetryableError<E> {
+    /// Returns true if the call failed because the retry budget ran out.
+    pub fn is_budget_exhausted(&self) -> bool {
+        matches!(self, SmsRetryableError::RetryBudgetExhausted { .. })
+    }
+
+    /// Returns the provider error, if the call did not run out of budget.
+    pub fn into_provider_error(self) -> Option<E> {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => None,
+            SmsRetryableError::Provider(e) => Some(e),
+        }
+    }
+}
+
+impl<E: RetryableError> RetryableError for SmsRetryableError<E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => false,
+            SmsRetryableError::Provider(e) => e.is_retryable(),
+        }
+    }
+
+    fn should_retry_operation(&self) -> bool {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => false,
+            SmsRetryableError::Provider(e) => e.should_retry_operation(),
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { resets_at } => {
+                Some(resets_at.saturating_duration_since(Instant::now()))
+            }
+            SmsRetryableError::Provider(e) => e.retry_after(),
+        }
+    }
+
+    fn is_provider_failure(&self) -> bool {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => false,
+            SmsRetryableError::Provider(e) => e.is_provider_failure(),
+        }
+    }
+
+    fn metric_label(&self) -> &'static str {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => "retry_budget_exhausted",
+            SmsRetryableError::Provider(e) => e.metric_label(),
+        }
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            SmsRetryableError::RetryBudgetExhausted { .. } => None,
+            SmsRetryableError::Provider(e) => e.live_task_id(),
+        }
+    }
+}
+
 /// Callback type for retry notifications.
 ///
 /// This callback is invoked each time a retry is attempted.
@@ -31,6 +119,33 @@ use tracing::debug;
 /// ```
 pub type OnRetryCallback<E> = Arc<dyn Fn(&E, Duration) + Send + Sync>;
 
+/// Decides whether a failed call should be retried.
+///
+/// Replaces the default `is_retryable()` check when set on a
+/// [`SmsRetryableProvider`].
+pub struct RetryPredicate<E>(Arc<dyn Fn(&E) -> bool + Send + Sync>);
+
+impl<E> RetryPredicate<E> {
+    /// Create a predicate from a closure.
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    /// Returns true if the error should be retried.
+    pub fn should_retry(&self, err: &E) -> bool {
+        (self.0)(err)
+    }
+}
+
+impl<E> Clone for RetryPredicate<E> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
 /// Wrapper that adds automatic retry logic to any Provider.
 ///
 /// This wrapper implements the same `Provider` trait but adds configurable
@@ -61,6 +176,11 @@ pub struct SmsRetryableProvider<P: Provider> {
     inner: Arc<P>,
     retry_config: RetryConfig,
     on_retry: Option<OnRetryCallback<P::Error>>,
+    retry_predicate: Option<RetryPredicate<P::Error>>,
+    retry_budget: Option<RetryBudget>,
+    total_retries: Arc<AtomicU64>,
+    total_retries_for_get_phone_number: Arc<AtomicU64>,
+    total_retries_for_get_sms_code: Arc<AtomicU64>,
 }
 
 impl<P: Provider> Clone for SmsRetryableProvider<P> {
@@ -69,6 +189,13 @@ impl<P: Provider> Clone for SmsRetryableProvider<P> {
             inner: Arc::clone(&self.inner),
             retry_config: self.retry_config.clone(),
             on_retry: self.on_retry.clone(),
+            retry_predicate: self.retry_predicate.clone(),
+            retry_budget: self.retry_budget.clone(),
+            total_retries: Arc::clone(&self.total_retries),
+            total_retries_for_get_phone_number: Arc::clone(
+                &self.total_retries_for_get_phone_number,
+            ),
+            total_retries_for_get_sms_code: Arc::clone(&self.total_retries_for_get_sms_code),
         }
     }
 }
@@ -79,6 +206,12 @@ impl<P: Provider + Debug> Debug for SmsRetryableProvider<P> {
             .field("inner", &self.inner)
             .field("retry_config", &self.retry_config)
             .field("on_retry", &self.on_retry.as_ref().map(|_| "..."))
+            .field(
+                "retry_predicate",
+                &self.retry_predicate.as_ref().map(|_| "..."),
+            )
+            .field("retry_budget", &self.retry_budget)
+            .field("total_retries", &self.total_retries())
             .finish()
     }
 }
@@ -90,6 +223,11 @@ impl<P: Provider> SmsRetryableProvider<P> {
             inner: Arc::new(inner),
             retry_config: RetryConfig::default(),
             on_retry: None,
+            retry_predicate: None,
+            retry_budget: None,
+            total_retries: Arc::default(),
+            total_retries_for_get_phone_number: Arc::default(),
+            total_retries_for_get_sms_code: Arc::default(),
         }
     }
 
@@ -99,6 +237,11 @@ impl<P: Provider> SmsRetryableProvider<P> {
             inner: Arc::new(inner),
             retry_config,
             on_retry: None,
+            retry_predicate: None,
+            retry_budget: None,
+            total_retries: Arc::default(),
+            total_retries_for_get_phone_number: Arc::default(),
+            total_retries_for_get_sms_code: Arc::default(),
         }
     }
 
@@ -123,6 +266,22 @@ impl<P: Provider> SmsRetryableProvider<P> {
         self
     }
 
+    /// Replace the default `is_retryable()` check with a custom predicate.
+    pub fn with_retry_predicate(mut self, predicate: RetryPredicate<P::Error>) -> Self {
+        self.retry_predicate = Some(predicate);
+        self
+    }
+
+    /// Limit the total number of retries within a time window.
+    ///
+    /// Once the budget is exhausted, calls that would retry fail right away
+    /// with [`SmsRetryableError::RetryBudgetExhausted`] until the window
+    /// resets.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
     /// Get reference to the inner provider.
     pub fn inner(&self) -> &P {
         &self.inner
@@ -132,40 +291,163 @@ impl<P: Provider> SmsRetryableProvider<P> {
     pub fn retry_config(&self) -> &RetryConfig {
         &self.retry_config
     }
+
+    /// Total retries performed since this provider was created.
+    ///
+    /// Clones share the counter.
+    pub fn total_retries(&self) -> u64 {
+        self.total_retries.load(Ordering::Relaxed)
+    }
+
+    /// Retries performed by `get_phone_number` since this provider was created.
+    pub fn total_retries_for_get_phone_number(&self) -> u64 {
+        self.total_retries_for_get_phone_number
+            .load(Ordering::Relaxed)
+    }
+
+    /// Retries performed by `get_sms_code` since this provider was created.
+    pub fn total_retries_for_get_sms_code(&self) -> u64 {
+        self.total_retries_for_get_sms_code.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the error should be retried.
+    fn should_retry(&self, err: &P::Error) -> bool {
+        match &self.retry_predicate {
+            Some(predicate) => predicate.should_retry(err),
+            None => err.is_retryable(),
+        }
+    }
+
+    /// Delay before the next retry, or `None` to stop retrying.
+    ///
+    /// Prefers the delay requested by the error, capped at
+    /// [`RetryConfig::max_delay`], over the backoff delay and takes one retry
+    /// from the budget, if any. Sets `budget_exhausted` when the budget stops
+    /// the retry.
+    fn next_delay(
+        &self,
+        err: &P::Error,
+        delay: Option<Duration>,
+        budget_exhausted: &AtomicBool,
+    ) -> Option<Duration> {
+        let delay = err.retry_after().map_or(delay?, |retry_after| {
+            retry_after.min(self.retry_config.max_delay)
+        });
+
+        if let Some(budget) = &self.retry_budget
+            && !budget.try_acquire()
+        {
+            #[cfg(feature = "tracing")]
+            debug!(error = ?err, "Retry budget exhausted, not retrying");
+
+            budget_exhausted.store(true, Ordering::Relaxed);
+            return None;
+        }
+
+        Some(delay)
+    }
+
+    /// Invoke the give-up callback if `result` failed after exhausting all
+    /// retries, and wrap its error.
+    fn finish<T>(
+        &self,
+        result: Result<T, P::Error>,
+        retries: usize,
+        budget_exhausted: bool,
+    ) -> Result<T, SmsRetryableError<P::Error>> {
+        if let (Err(err), Some(callback)) = (&result, &self.retry_config.on_give_up)
+            && retries == self.retry_config.max_retries
+            && self.should_retry(err)
+        {
+            #[cfg(feature = "tracing")]
+            debug!(error = ?err, retries, "Giving up after exhausting retries");
+
+            callback(err);
+        }
+
+        result.map_err(|err| match &self.retry_budget {
+            Some(budget) if budget_exhausted => SmsRetryableError::RetryBudgetExhausted {
+                resets_at: budget.resets_at(),
+            },
+            _ => SmsRetryableError::Provider(err),
+        })
+    }
 }
 
-impl<P: Provider> Provider for SmsRetryableProvider<P>
+impl<P: SupportsIdempotencyKey> SupportsIdempotencyKey for SmsRetryableProvider<P> where
+    P::Error: Debug
+{
+}
+
+#[cfg(feature = "hero-sms")]
+impl SmsRetryableProvider<HeroSmsProvider> {
+    /// Retry only on the given Hero SMS error codes.
+    ///
+    /// Codes are matched by name, so `Banned` matches regardless of its
+    /// `until` value. Errors without a service code are never retried.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let provider = SmsRetryableProvider::new(hero_provider).with_retry_on_code(vec![
+    ///     HeroSmsErrorCode::NoNumbers,
+    ///     HeroSmsErrorCode::ChannelsLimit,
+    /// ]);
+    /// ```
+    pub fn with_retry_on_code(self, codes: Vec<HeroSmsErrorCode>) -> Self {
+        let names: HashSet<String> = codes
+            .iter()
+            .map(|code| code.code_name().to_string())
+            .collect();
+        self.with_retry_predicate(RetryPredicate::new(move |err: &HeroSmsError| {
+            err.as_service_code()
+                .is_some_and(|code| names.contains(code.code_name()))
+        }))
+    }
+}
+
+impl<P: Provider> SmsRetryableProvider<P>
 where
     P::Error: Debug,
 {
-    type Error = P::Error;
-    type Service = P::Service;
-
-    #[cfg_attr(
-        feature = "tracing",
-        tracing::instrument(
-            name = "SmsRetryableProvider::get_phone_number",
-            skip_all,
-            fields(country = %country.iso_short_name())
-        )
-    )]
-    async fn get_phone_number(
+    /// Acquire a number with retries, passing the idempotency key if given.
+    async fn acquire_with_retries(
         &self,
         country: Country,
-        service: Self::Service,
-    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        service: P::Service,
+        idempotency_key: Option<&str>,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), SmsRetryableError<P::Error>> {
         let inner = Arc::clone(&self.inner);
         let on_retry = self.on_retry.clone();
         let country_name = country.iso_short_name().to_string();
-        (|| {
+        let total_retries = Arc::clone(&self.total_retries);
+        let operation_retries = Arc::clone(&self.total_retries_for_get_phone_number);
+        let retries = AtomicUsize::new(0);
+        let retries_ref = &retries;
+        let budget_exhausted = AtomicBool::new(false);
+        let result = (|| {
             let inner = Arc::clone(&inner);
             let svc = service.clone();
             let c = country.clone();
-            async move { inner.get_phone_number(c, svc).await }
+            async move {
+                match idempotency_key {
+                    Some(key) => {
+                        inner
+                            .get_phone_number_with_idempotency_key(c, svc, key)
+                            .await
+                    }
+                    None => inner.get_phone_number_with_metadata(c, svc).await,
+                }
+            }
         })
         .retry(self.retry_config.build_strategy())
-        .when(|err: &Self::Error| err.is_retryable())
+        .when(|err: &P::Error| self.should_retry(err))
+        .adjust(|err: &P::Error, delay| self.next_delay(err, delay, &budget_exhausted))
         .notify(move |err, duration| {
+            retries_ref.fetch_add(1, Ordering::Relaxed);
+            total_retries.fetch_add(1, Ordering::Relaxed);
+            operation_retries.fetch_add(1, Ordering::Relaxed);
+
             // Call user callback if set
             if let Some(ref callback) = on_retry {
                 callback(err, duration);
@@ -179,7 +461,65 @@ where
                 "Retrying get_phone_number"
             );
         })
-        .await
+        .await;
+
+        self.finish(result, retries.into_inner(), budget_exhausted.into_inner())
+    }
+}
+
+impl<P: Provider> Provider for SmsRetryableProvider<P>
+where
+    P::Error: Debug,
+{
+    type Error = SmsRetryableError<P::Error>;
+    type Service = P::Service;
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsRetryableProvider::get_phone_number",
+            skip_all,
+            fields(country = %country.iso_short_name())
+        )
+    )]
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        let (task_id, full_number, _) = self
+            .get_phone_number_with_metadata(country, service)
+            .await?;
+        Ok((task_id, full_number))
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.acquire_with_retries(country, service, None).await
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        // The same key is sent on every attempt, so retries cannot buy twice
+        self.acquire_with_retries(country, service, Some(idempotency_key))
+            .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        self.inner
+            .get_phone_number_for_any_country(service)
+            .await
+            .map_err(SmsRetryableError::Provider)
     }
 
     #[cfg_attr(
@@ -191,18 +531,38 @@ where
         )
     )]
     async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        Ok(self
+            .get_sms_code_with_text(task_id)
+            .await?
+            .map(|(code, _)| code))
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
         let inner = Arc::clone(&self.inner);
         let task_id_owned = task_id.clone();
         let task_id_for_notify = task_id.clone();
         let on_retry = self.on_retry.clone();
-        (|| {
+        let total_retries = Arc::clone(&self.total_retries);
+        let operation_retries = Arc::clone(&self.total_retries_for_get_sms_code);
+        let retries = AtomicUsize::new(0);
+        let retries_ref = &retries;
+        let budget_exhausted = AtomicBool::new(false);
+        let result = (|| {
             let inner = Arc::clone(&inner);
             let task_id = task_id_owned.clone();
-            async move { inner.get_sms_code(&task_id).await }
+            async move { inner.get_sms_code_with_text(&task_id).await }
         })
         .retry(self.retry_config.build_strategy())
-        .when(|err: &Self::Error| err.is_retryable())
+        .when(|err: &P::Error| self.should_retry(err))
+        .adjust(|err: &P::Error, delay| self.next_delay(err, delay, &budget_exhausted))
         .notify(move |err, duration| {
+            retries_ref.fetch_add(1, Ordering::Relaxed);
+            total_retries.fetch_add(1, Ordering::Relaxed);
+            operation_retries.fetch_add(1, Ordering::Relaxed);
+
             // Call user callback if set
             if let Some(ref callback) = on_retry {
                 callback(err, duration);
@@ -216,15 +576,132 @@ where
                 "Retrying get_sms_code"
             );
         })
-        .await
+        .await;
+
+        self.finish(result, retries.into_inner(), budget_exhausted.into_inner())
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        self.inner
+            .get_all_sms_codes(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        self.inner
+            .get_all_sms_codes_with_text(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        self.inner
+            .get_activation_status(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner
+            .request_another_code(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
     }
 
     async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
-        self.inner.finish_activation(task_id).await
+        self.inner
+            .finish_activation(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
     }
 
     async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
-        self.inner.cancel_activation(task_id).await
+        self.inner
+            .cancel_activation(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    /// Polls with one batch call to the inner provider, then retries the
+    /// tasks whose poll failed with a retryable error one by one.
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        let results = self.inner.get_sms_codes_batch(task_ids).await;
+        join_all(
+            task_ids
+                .iter()
+                .zip(results)
+                .map(|(task_id, result)| async move {
+                    match result {
+                        Err(e) if self.should_retry(&e) => self.get_sms_code(task_id).await,
+                        result => result.map_err(SmsRetryableError::Provider),
+                    }
+                }),
+        )
+        .await
+    }
+
+    async fn cancel_all_pending(&self, task_ids: &[TaskId]) -> Vec<Result<(), Self::Error>> {
+        self.inner
+            .cancel_all_pending(task_ids)
+            .await
+            .into_iter()
+            .map(|result| result.map_err(SmsRetryableError::Provider))
+            .collect()
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner
+            .is_task_alive(task_id)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.inner
+            .warmup()
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.inner
+            .account_reputation()
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        self.inner
+            .country_prices(service)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.inner
+            .available_countries_with_counts(service)
+            .await
+            .map_err(SmsRetryableError::Provider)
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.inner.health_report().await
     }
 
     fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
@@ -242,4 +719,262 @@ where
     fn supported_services(&self) -> Vec<Self::Service> {
         self.inner.supported_services()
     }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.inner.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+    use std::sync::Mutex;
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("mock error (retryable: {0})")]
+    struct MockError(bool, Option<Duration>);
+
+    impl RetryableError for MockError {
+        fn is_retryable(&self) -> bool {
+            self.0
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            self.1
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct FailingProvider {
+        retryable: bool,
+        retry_after: Option<Duration>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Provider for FailingProvider {
+        type Error = MockError;
+        type Service = ();
+
+        async fn get_phone_number(
+            &self,
+            _country: Country,
+            _service: Self::Service,
+        ) -> Result<(TaskId, FullNumber), Self::Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Err(MockError(self.retryable, self.retry_after))
+        }
+
+        async fn get_sms_code(&self, _task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Err(MockError(self.retryable, self.retry_after))
+        }
+
+        async fn finish_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn cancel_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn provider(
+        retryable: bool,
+    ) -> (
+        SmsRetryableProvider<FailingProvider>,
+        Arc<AtomicUsize>,
+        Arc<Mutex<Vec<String>>>,
+    ) {
+        let inner = FailingProvider {
+            retryable,
+            ..Default::default()
+        };
+        let calls = Arc::clone(&inner.calls);
+        let given_up = Arc::new(Mutex::new(Vec::new()));
+        let given_up_clone = Arc::clone(&given_up);
+        let config = RetryConfig::default()
+            .with_min_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(1))
+            .with_max_retries(2)
+            .with_on_give_up(move |err| given_up_clone.lock().unwrap().push(err.to_string()));
+        (
+            SmsRetryableProvider::with_config(inner, config),
+            calls,
+            given_up,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_on_give_up_called_after_exhausting_retries() {
+        let (provider, calls, given_up) = provider(true);
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            *given_up.lock().unwrap(),
+            vec!["mock error (retryable: true)"]
+        );
+
+        let result = provider.get_sms_code(&TaskId::from("task123")).await;
+        assert!(result.is_err());
+        assert_eq!(given_up.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_on_give_up_not_called_for_permanent_errors() {
+        let (provider, calls, given_up) = provider(false);
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+        assert!(matches!(result, Err(SmsRetryableError::Provider(_))));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(given_up.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_overrides_backoff() {
+        let inner = FailingProvider {
+            retryable: true,
+            retry_after: Some(Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let delays_clone = Arc::clone(&delays);
+        let config = RetryConfig::default()
+            .with_min_delay(Duration::from_secs(60))
+            .with_max_retries(2);
+        let provider = SmsRetryableProvider::with_config(inner, config)
+            .with_on_retry(move |_, delay| delays_clone.lock().unwrap().push(delay));
+
+        let result = provider.get_sms_code(&TaskId::from("task123")).await;
+        assert!(result.is_err());
+        assert_eq!(
+            *delays.lock().unwrap(),
+            vec![Duration::from_millis(5), Duration::from_millis(5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_capped_at_max_delay() {
+        let inner = FailingProvider {
+            retryable: true,
+            retry_after: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let delays_clone = Arc::clone(&delays);
+        let config = RetryConfig::default()
+            .with_min_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(5))
+            .with_max_retries(1);
+        let provider = SmsRetryableProvider::with_config(inner, config)
+            .with_on_retry(move |_, delay| delays_clone.lock().unwrap().push(delay));
+
+        let result = provider.get_sms_code(&TaskId::from("task123")).await;
+        assert!(result.is_err());
+        assert_eq!(*delays.lock().unwrap(), vec![Duration::from_millis(5)]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_predicate_overrides_is_retryable() {
+        let (provider, calls, given_up) = provider(false);
+        let provider = provider.with_retry_predicate(RetryPredicate::new(|_| true));
+
+        let result = provider.get_phone_number(Alpha2::UA.to_country(), ()).await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+        assert_eq!(given_up.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_total_retries_shared_across_clones() {
+        let (provider, _, _) = provider(true);
+        let clone = provider.clone();
+
+        assert!(
+            provider
+                .get_phone_number(Alpha2::UA.to_country(), ())
+                .await
+                .is_err()
+        );
+        assert!(clone.get_sms_code(&TaskId::from("task123")).await.is_err());
+
+        assert_eq!(provider.total_retries(), 4);
+        assert_eq!(clone.total_retries(), 4);
+        assert_eq!(clone.total_retries_for_get_phone_number(), 2);
+        assert_eq!(provider.total_retries_for_get_sms_code(), 2);
+    }
+
+    #[cfg(feature = "hero-sms")]
+    #[test]
+    fn test_with_retry_on_code() {
+        use crate::providers::hero_sms::{HeroSms, HeroSmsServiceError};
+
+        let client = HeroSms::new("http://127.0.0.1:9", "test_key").unwrap();
+        let provider =
+            SmsRetryableProvider::new(HeroSmsProvider::new(client)).with_retry_on_code(vec![
+                HeroSmsErrorCode::NoNumbers,
+                HeroSmsErrorCode::ChannelsLimit,
+            ]);
+        let service_error =
+            |code| HeroSmsError::Service(HeroSmsServiceError::new(code, String::new()));
+
+        assert!(provider.should_retry(&service_error(HeroSmsErrorCode::NoNumbers)));
+        assert!(provider.should_retry(&service_error(HeroSmsErrorCode::ChannelsLimit)));
+        assert!(!provider.should_retry(&service_error(HeroSmsErrorCode::ErrorSql)));
+        assert!(
+            !provider
+                .should_retry(&HeroSmsError::FailedToParseBalanceResponse { raw: String::new() })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget() {
+        let inner = FailingProvider {
+            retryable: true,
+            ..Default::default()
+        };
+        let calls = Arc::clone(&inner.calls);
+        let config = RetryConfig::default()
+            .with_min_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(1))
+            .with_max_retries(5);
+        let budget = RetryBudget::new(3, Duration::from_millis(200));
+        let provider =
+            SmsRetryableProvider::with_config(inner, config).with_retry_budget(budget.clone());
+        let task_id = TaskId::from("task123");
+
+        let err = provider.get_sms_code(&task_id).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+        assert_eq!(budget.remaining(), 0);
+        assert!(matches!(
+            err,
+            SmsRetryableError::RetryBudgetExhausted { resets_at } if resets_at == budget.resets_at()
+        ));
+        assert!(!err.is_retryable());
+        assert_eq!(err.metric_label(), "retry_budget_exhausted");
+
+        // Exhausted: fails without retrying
+        let err = provider.get_sms_code(&task_id).await.unwrap_err();
+        assert!(err.is_budget_exhausted());
+        assert_eq!(calls.load(Ordering::Relaxed), 5);
+
+        // Permanent errors are not retried, so they do not touch the budget
+        let permanent =
+            SmsRetryableProvider::new(FailingProvider::default()).with_retry_budget(budget.clone());
+        let err = permanent.get_sms_code(&task_id).await.unwrap_err();
+        assert!(matches!(err, SmsRetryableError::Provider(_)));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(budget.remaining(), 3);
+        let err = provider.get_sms_code(&task_id).await.unwrap_err();
+        assert!(err.is_budget_exhausted());
+        assert_eq!(calls.load(Ordering::Relaxed), 9);
+    }
 }
@@ -0,0 +1,508 @@
+//! Provider wrapper that records call counts and latencies in memory.
+
+use super::traits::{Provider, SupportsIdempotencyKey};
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use keshvar::Country;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Number of recent latencies kept per operation by default.
+const DEFAULT_LATENCY_WINDOW: usize = 100;
+
+/// Counters and recent latencies of one operation.
+#[derive(Debug, Default)]
+struct OperationRecorder {
+    calls: AtomicU64,
+    failures: AtomicU64,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl OperationRecorder {
+    fn record(&self, latency: Duration, success: bool, window: usize) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if latencies.len() >= window {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    fn snapshot(&self) -> OperationSnapshot {
+        let calls = self.calls.load(Ordering::Relaxed);
+        let failures = self.failures.load(Ordering::Relaxed);
+        let mut latencies: Vec<Duration> = self
+            .latencies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .copied()
+            .collect();
+        latencies.sort_unstable();
+
+        OperationSnapshot {
+            calls,
+            failures,
+            failure_rate: if calls == 0 {
+                0.0
+            } else {
+                failures as f64 / calls as f64
+            },
+            p50_latency: percentile(&latencies, 0.50),
+            p95_latency: percentile(&latencies, 0.95),
+        }
+    }
+
+    fn reset(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+        self.failures.store(0, Ordering::Relaxed);
+        self.latencies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+#[derive(Debug, Default)]
+struct Recorders {
+    get_phone_number: OperationRecorder,
+    get_sms_code: OperationRecorder,
+    finish_activation: OperationRecorder,
+    cancel_activation: OperationRecorder,
+}
+
+/// Call statistics of one provider operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSnapshot {
+    /// Total calls.
+    pub calls: u64,
+    /// Calls that returned an error.
+    pub failures: u64,
+    /// `failures / calls`, or 0 without calls.
+    pub failure_rate: f64,
+    /// Median latency of the recent calls.
+    pub p50_latency: Option<Duration>,
+    /// 95th percentile latency of the recent calls.
+    pub p95_latency: Option<Duration>,
+}
+
+/// Point-in-time copy of the statistics of a [`ProviderStatistics`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatisticsSnapshot {
+    /// Number acquisitions, including the metadata, idempotency key and
+    /// any-country variants.
+    pub get_phone_number: OperationSnapshot,
+    /// SMS polls, including [`Provider::get_sms_code_with_text`].
+    pub get_sms_code: OperationSnapshot,
+    /// Finished activations.
+    pub finish_activation: OperationSnapshot,
+    /// Cancelled activations.
+    pub cancel_activation: OperationSnapshot,
+}
+
+/// Wrapper that records call counts, failure rates and latencies of the
+/// core provider operations.
+///
+/// A lightweight alternative to the `metrics` feature when no
+/// OpenTelemetry pipeline is available. Only the last `latency_window`
+/// latencies of each operation are kept for the percentile estimates.
+/// Clones share the same statistics.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::ProviderStatistics;
+///
+/// let provider = ProviderStatistics::new(base_provider);
+/// // ...
+/// let snapshot = provider.snapshot();
+/// println!("p95 acquire latency: {:?}", snapshot.get_phone_number.p95_latency);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProviderStatistics<P: Provider> {
+    inner: P,
+    latency_window: usize,
+    recorders: Arc<Recorders>,
+}
+
+impl<P: Provider> ProviderStatistics<P> {
+    /// Wrap a provider, keeping the last 100 latencies per operation.
+    pub fn new(inner: P) -> Self {
+        Self::with_latency_window(inner, DEFAULT_LATENCY_WINDOW)
+    }
+
+    /// Wrap a provider, keeping the last `latency_window` latencies per
+    /// operation.
+    ///
+    /// A window of 0 is treated as 1.
+    pub fn with_latency_window(inner: P, latency_window: usize) -> Self {
+        Self {
+            inner,
+            latency_window: latency_window.max(1),
+            recorders: Arc::default(),
+        }
+    }
+
+    /// Get a reference to the inner provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Copy the current statistics.
+    pub fn snapshot(&self) -> StatisticsSnapshot {
+        StatisticsSnapshot {
+            get_phone_number: self.recorders.get_phone_number.snapshot(),
+            get_sms_code: self.recorders.get_sms_code.snapshot(),
+            finish_activation: self.recorders.finish_activation.snapshot(),
+            cancel_activation: self.recorders.cancel_activation.snapshot(),
+        }
+    }
+
+    /// Clear all counters and latencies.
+    pub fn reset(&self) {
+        self.recorders.get_phone_number.reset();
+        self.recorders.get_sms_code.reset();
+        self.recorders.finish_activation.reset();
+        self.recorders.cancel_activation.reset();
+    }
+
+    /// Run `call` and record its outcome with `recorder`.
+    async fn record<T>(
+        &self,
+        recorder: &OperationRecorder,
+        call: impl Future<Output = Result<T, P::Error>>,
+    ) -> Result<T, P::Error> {
+        let started = Instant::now();
+        let result = call.await;
+        recorder.record(started.elapsed(), result.is_ok(), self.latency_window);
+        result
+    }
+}
+
+impl<P: Provider> Provider for ProviderStatistics<P> {
+    type Error = P::Error;
+    type Service = P::Service;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        self.record(
+            &self.recorders.get_phone_number,
+            self.inner.get_phone_number(country, service),
+        )
+        .await
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.record(
+            &self.recorders.get_phone_number,
+            self.inner.get_phone_number_with_metadata(country, service),
+        )
+        .await
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.record(
+            &self.recorders.get_phone_number,
+            self.inner
+                .get_phone_number_with_idempotency_key(country, service, idempotency_key),
+        )
+        .await
+    }
+
+    async fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> Result<Option<(TaskId, FullNumber, Country)>, Self::Error> {
+        self.record(
+            &self.recorders.get_phone_number,
+            self.inner.get_phone_number_for_any_country(service),
+        )
+        .await
+    }
+
+    async fn get_sms_code(&self, task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        self.record(
+            &self.recorders.get_sms_code,
+            self.inner.get_sms_code(task_id),
+        )
+        .await
+    }
+
+    async fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<(SmsCode, String)>, Self::Error> {
+        self.record(
+            &self.recorders.get_sms_code,
+            self.inner.get_sms_code_with_text(task_id),
+        )
+        .await
+    }
+
+    /// Records one `get_sms_code` call per task, each with the latency of
+    /// the whole batch.
+    async fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> Vec<Result<Option<SmsCode>, Self::Error>> {
+        let started = Instant::now();
+        let results = self.inner.get_sms_codes_batch(task_ids).await;
+        let elapsed = started.elapsed();
+        for result in &results {
+            self.recorders
+                .get_sms_code
+                .record(elapsed, result.is_ok(), self.latency_window);
+        }
+        results
+    }
+
+    async fn get_all_sms_codes(&self, task_id: &TaskId) -> Result<Vec<SmsCode>, Self::Error> {
+        self.inner.get_all_sms_codes(task_id).await
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        self.inner.get_all_sms_codes_with_text(task_id).await
+    }
+
+    async fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        self.inner.get_activation_status(task_id).await
+    }
+
+    async fn request_another_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner.request_another_code(task_id).await
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.record(
+            &self.recorders.finish_activation,
+            self.inner.finish_activation(task_id),
+        )
+        .await
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.record(
+            &self.recorders.cancel_activation,
+            self.inner.cancel_activation(task_id),
+        )
+        .await
+    }
+
+    async fn is_task_alive(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.inner.is_task_alive(task_id).await
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.inner.warmup().await
+    }
+
+    async fn account_reputation(&self) -> Result<Option<ReputationLevel>, Self::Error> {
+        self.inner.account_reputation().await
+    }
+
+    async fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> Result<Vec<(Country, PriceEntry)>, Self::Error> {
+        self.inner.country_prices(service).await
+    }
+
+    async fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.inner.available_countries_with_counts(service).await
+    }
+
+    async fn health_report(&self) -> HealthReport {
+        self.inner.health_report().await
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        self.inner.is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        self.inner.supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        self.inner.available_countries(service)
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        self.inner.supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        self.inner.service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+impl<P: SupportsIdempotencyKey> SupportsIdempotencyKey for ProviderStatistics<P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockError, MockProvider, MockService};
+    use keshvar::Alpha2;
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 0.5), Some(Duration::from_millis(10)));
+        assert_eq!(
+            percentile(&latencies, 0.95),
+            Some(Duration::from_millis(19))
+        );
+        assert_eq!(
+            percentile(&latencies[..1], 0.95),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(percentile(&[], 0.5), None);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_counts_successes_and_failures() {
+        let mock = MockProvider::builder()
+            .country_number_response(
+                Alpha2::RU.to_country(),
+                Duration::ZERO,
+                Err(MockError::Permanent("NO_NUMBERS".into())),
+            )
+            .sms_code("123456")
+            .sms_code_response(Err(MockError::Permanent("BANNED".into())))
+            .cancel_response(Err(MockError::Permanent("NO_ACTIVATION".into())))
+            .build();
+        let provider = ProviderStatistics::new(mock);
+
+        provider
+            .get_phone_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        provider
+            .get_phone_number_with_metadata(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        provider
+            .get_phone_number(Alpha2::RU.to_country(), MockService)
+            .await
+            .unwrap_err();
+        provider.get_sms_code(&TaskId::new("ok")).await.unwrap();
+        provider
+            .get_sms_code(&TaskId::new("bad"))
+            .await
+            .unwrap_err();
+        provider
+            .finish_activation(&TaskId::new("ok"))
+            .await
+            .unwrap();
+        provider
+            .cancel_activation(&TaskId::new("bad"))
+            .await
+            .unwrap_err();
+
+        let snapshot = provider.snapshot();
+        assert_eq!(snapshot.get_phone_number.calls, 3);
+        assert_eq!(snapshot.get_phone_number.failures, 1);
+        assert!((snapshot.get_phone_number.failure_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(snapshot.get_sms_code.calls, 2);
+        assert_eq!(snapshot.get_sms_code.failure_rate, 0.5);
+        assert_eq!(snapshot.finish_activation.calls, 1);
+        assert_eq!(snapshot.finish_activation.failure_rate, 0.0);
+        assert_eq!(snapshot.cancel_activation.calls, 1);
+        assert_eq!(snapshot.cancel_activation.failure_rate, 1.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_snapshot_latency_percentiles() {
+        let mock = MockProvider::new();
+        let provider = ProviderStatistics::new(mock.clone());
+
+        for millis in 1..=20 {
+            mock.set_get_number_delay(Duration::from_millis(millis));
+            provider
+                .get_phone_number(Alpha2::UA.to_country(), MockService)
+                .await
+                .unwrap();
+        }
+
+        let snapshot = provider.snapshot().get_phone_number;
+        assert_eq!(snapshot.p50_latency, Some(Duration::from_millis(10)));
+        assert_eq!(snapshot.p95_latency, Some(Duration::from_millis(19)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_latency_window_keeps_recent_calls() {
+        let mock = MockProvider::new();
+        let provider = ProviderStatistics::with_latency_window(mock.clone(), 2);
+
+        for millis in [100, 1, 2] {
+            mock.set_get_number_delay(Duration::from_millis(millis));
+            provider
+                .get_phone_number(Alpha2::UA.to_country(), MockService)
+                .await
+                .unwrap();
+        }
+
+        let snapshot = provider.snapshot().get_phone_number;
+        assert_eq!(snapshot.calls, 3);
+        assert_eq!(snapshot.p95_latency, Some(Duration::from_millis(2)));
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_statistics_and_reset() {
+        let provider = ProviderStatistics::new(MockProvider::new());
+        let clone = provider.clone();
+
+        clone.finish_activation(&TaskId::new("ok")).await.unwrap();
+        assert_eq!(provider.snapshot().finish_activation.calls, 1);
+
+        provider.reset();
+        let snapshot = clone.snapshot();
+        assert_eq!(snapshot.finish_activation.calls, 0);
+        assert_eq!(snapshot.finish_activation.p50_latency, None);
+    }
+}
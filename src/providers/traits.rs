@@ -1,10 +1,16 @@
 //! Provider trait definition.
 
 use crate::errors::RetryableError;
-use crate::types::{DialCode, FullNumber, SmsCode, TaskId};
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, DialCode, FullNumber, HealthReport,
+    PriceEntry, ReputationLevel, SmsCode, TaskId,
+};
+use crate::utils::ProviderKind;
+use futures::future::join_all;
 use keshvar::Country;
 use std::error::Error as StdError;
 use std::future::Future;
+use std::sync::Arc;
 
 /// Core trait that all SMS providers must implement.
 ///
@@ -80,6 +86,55 @@ pub trait Provider: Send + Sync + Clone {
         service: Self::Service,
     ) -> impl Future<Output = Result<(TaskId, FullNumber), Self::Error>> + Send;
 
+    /// Get a phone number from any country, letting the provider pick one,
+    /// usually the cheapest.
+    ///
+    /// Returns the country of the number along with it. Default
+    /// implementation returns `None` for providers without such a mode.
+    fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<Option<(TaskId, FullNumber, Country)>, Self::Error>> + Send
+    {
+        let _ = service;
+        async { Ok(None) }
+    }
+
+    /// Get a phone number along with provider-reported activation details.
+    ///
+    /// Default implementation calls [`Provider::get_phone_number`] and
+    /// reports no metadata. Providers that know e.g. the activation expiry
+    /// should override this.
+    fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error>> + Send
+    {
+        async move {
+            let (task_id, full_number) = self.get_phone_number(country, service).await?;
+            Ok((task_id, full_number, None))
+        }
+    }
+
+    /// Get a phone number, passing a client-chosen idempotency key.
+    ///
+    /// Repeating a request with the same key should return the same
+    /// activation instead of buying another number. Default implementation
+    /// ignores the key and calls [`Provider::get_phone_number_with_metadata`];
+    /// providers that honor it should override this and implement
+    /// [`SupportsIdempotencyKey`].
+    fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> impl Future<Output = Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error>> + Send
+    {
+        let _ = idempotency_key;
+        self.get_phone_number_with_metadata(country, service)
+    }
+
     /// Check if SMS code has been received for the given task.
     ///
     /// # Arguments
@@ -93,6 +148,95 @@ pub trait Provider: Send + Sync + Clone {
         task_id: &TaskId,
     ) -> impl Future<Output = Result<Option<SmsCode>, Self::Error>> + Send;
 
+    /// Check for an SMS and return the code along with the full message text.
+    ///
+    /// Useful when OTP parsing rules are specific to the target service.
+    ///
+    /// Default implementation calls [`Provider::get_sms_code`] and uses the
+    /// code itself as the text. Providers that receive the full message
+    /// should override this.
+    fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Option<(SmsCode, String)>, Self::Error>> + Send {
+        async move {
+            let code = self.get_sms_code(task_id).await?;
+            Ok(code.map(|code| {
+                let text = code.to_string();
+                (code, text)
+            }))
+        }
+    }
+
+    /// Get all SMS codes received for the activation, oldest first.
+    ///
+    /// Default implementation returns the codes of
+    /// [`get_all_sms_codes_with_text`](Self::get_all_sms_codes_with_text).
+    fn get_all_sms_codes(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Vec<SmsCode>, Self::Error>> + Send {
+        async move {
+            let messages = self.get_all_sms_codes_with_text(task_id).await?;
+            Ok(messages.into_iter().map(|(code, _)| code).collect())
+        }
+    }
+
+    /// Get all SMS codes received for the activation along with their
+    /// message text, oldest first.
+    ///
+    /// Default implementation returns the latest SMS from
+    /// [`get_sms_code_with_text`](Self::get_sms_code_with_text), if any.
+    /// Providers that can list every SMS of an activation should override
+    /// this.
+    fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Vec<(SmsCode, String)>, Self::Error>> + Send {
+        async move {
+            let latest = self.get_sms_code_with_text(task_id).await?;
+            Ok(latest.into_iter().collect())
+        }
+    }
+
+    /// Poll several activations for an SMS code concurrently.
+    ///
+    /// Returns one result per task ID, in the same order, so a failing
+    /// activation does not hide the codes of the others. Default
+    /// implementation calls [`Provider::get_sms_code`] for each ID;
+    /// providers with a batch status endpoint can override this.
+    fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> impl Future<Output = Vec<Result<Option<SmsCode>, Self::Error>>> + Send {
+        join_all(task_ids.iter().map(|task_id| self.get_sms_code(task_id)))
+    }
+
+    /// Get the lifecycle state of the activation.
+    ///
+    /// Default implementation returns `Ok(None)`, meaning the provider cannot
+    /// report it. Polling stops early once a terminal state is reported.
+    fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Option<ActivationState>, Self::Error>> + Send {
+        let _ = task_id;
+        async { Ok(None) }
+    }
+
+    /// Ask the provider to deliver another SMS code to the same number.
+    ///
+    /// Returns `Ok(true)` once the request is accepted. Default
+    /// implementation returns `Ok(false)`, meaning the provider does not
+    /// support it.
+    fn request_another_code(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        let _ = task_id;
+        async { Ok(false) }
+    }
+
     /// Mark the activation as successfully completed.
     ///
     /// Call this after successfully using the SMS code.
@@ -112,6 +256,106 @@ pub trait Provider: Send + Sync + Clone {
         task_id: &TaskId,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 
+    /// Cancel several activations concurrently.
+    ///
+    /// Returns one result per task ID, in the same order. Default
+    /// implementation calls [`Provider::cancel_activation`] for each ID;
+    /// providers with a bulk cancel endpoint can override this.
+    fn cancel_all_pending(
+        &self,
+        task_ids: &[TaskId],
+    ) -> impl Future<Output = Vec<Result<(), Self::Error>>> + Send {
+        join_all(
+            task_ids
+                .iter()
+                .map(|task_id| self.cancel_activation(task_id)),
+        )
+    }
+
+    /// Check whether the activation can still receive SMS.
+    ///
+    /// Must not have side effects such as consuming a queued code, since it
+    /// runs before polling starts. Default implementation asks
+    /// [`Provider::get_activation_status`] and treats a terminal state as not
+    /// alive; if the provider cannot report a status the activation is
+    /// assumed alive. Providers that can recognize expired or unknown
+    /// activations more directly should override this and return `Ok(false)`
+    /// for them.
+    fn is_task_alive(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        async move {
+            let status = self.get_activation_status(task_id).await?;
+            Ok(!status.is_some_and(|state| state.is_terminal()))
+        }
+    }
+
+    /// Perform one-time initialization before first use.
+    ///
+    /// Use this for work that should not happen at construction time, such as
+    /// authenticating, loading country lists, or checking quotas.
+    ///
+    /// Default implementation does nothing.
+    fn warmup(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Get the account's reputation level.
+    ///
+    /// Default implementation returns `None` for providers without a
+    /// reputation system.
+    fn account_reputation(
+        &self,
+    ) -> impl Future<Output = Result<Option<ReputationLevel>, Self::Error>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Get the price and stock of a service in every country it is sold in.
+    ///
+    /// Default implementation returns an empty list for providers without
+    /// a price list.
+    fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<Vec<(Country, PriceEntry)>, Self::Error>> + Send {
+        let _ = service;
+        async { Ok(Vec::new()) }
+    }
+
+    /// Get the stock of a service in every country that currently has numbers.
+    ///
+    /// Unlike [`available_countries`](Self::available_countries), this asks
+    /// the provider for live counts. Default implementation returns an empty
+    /// list for providers without stock reporting.
+    fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> impl Future<Output = Result<Vec<CountryAvailability>, Self::Error>> + Send {
+        let _ = service;
+        async { Ok(Vec::new()) }
+    }
+
+    /// Collect health signals of the provider account.
+    ///
+    /// Default implementation times [`warmup`](Self::warmup) and reports
+    /// healthy if it succeeds, without balance or activation counts.
+    fn health_report(&self) -> impl Future<Output = HealthReport> + Send {
+        async move {
+            let checked_at = std::time::SystemTime::now();
+            let started = std::time::Instant::now();
+            let is_healthy = self.warmup().await.is_ok();
+
+            HealthReport {
+                balance: None,
+                active_activations: None,
+                api_latency: started.elapsed(),
+                is_healthy,
+                checked_at,
+            }
+        }
+    }
+
     /// Check if the provider supports the given dial code.
     ///
     /// This method allows providers to implement their own filtering logic,
@@ -157,4 +401,198 @@ pub trait Provider: Send + Sync + Clone {
     fn supported_services(&self) -> Vec<Self::Service> {
         Vec::new()
     }
+
+    /// Human-readable name of a service, used in errors and logs.
+    ///
+    /// Default implementation returns the service type name.
+    fn service_name(&self, service: &Self::Service) -> String {
+        let _ = service;
+        std::any::type_name::<Self::Service>().to_string()
+    }
+
+    /// Which provider this is, used to tag logs.
+    ///
+    /// Default implementation returns [`ProviderKind::Custom`].
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Custom
+    }
 }
+
+/// Marker for providers whose
+/// [`get_phone_number_with_idempotency_key`](Provider::get_phone_number_with_idempotency_key)
+/// actually sends the key, rather than ignoring it.
+pub trait SupportsIdempotencyKey: Provider {}
+
+/// Shares one provider between tasks without cloning it.
+///
+/// Every method is forwarded to the wrapped provider.
+impl<P: Provider> Provider for Arc<P> {
+    type Error = P::Error;
+    type Service = P::Service;
+
+    fn get_phone_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<(TaskId, FullNumber), Self::Error>> + Send {
+        (**self).get_phone_number(country, service)
+    }
+
+    fn get_phone_number_for_any_country(
+        &self,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<Option<(TaskId, FullNumber, Country)>, Self::Error>> + Send
+    {
+        (**self).get_phone_number_for_any_country(service)
+    }
+
+    fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error>> + Send
+    {
+        (**self).get_phone_number_with_metadata(country, service)
+    }
+
+    fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> impl Future<Output = Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error>> + Send
+    {
+        (**self).get_phone_number_with_idempotency_key(country, service, idempotency_key)
+    }
+
+    fn get_sms_code(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Option<SmsCode>, Self::Error>> + Send {
+        (**self).get_sms_code(task_id)
+    }
+
+    fn get_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Option<(SmsCode, String)>, Self::Error>> + Send {
+        (**self).get_sms_code_with_text(task_id)
+    }
+
+    fn get_all_sms_codes(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Vec<SmsCode>, Self::Error>> + Send {
+        (**self).get_all_sms_codes(task_id)
+    }
+
+    fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Vec<(SmsCode, String)>, Self::Error>> + Send {
+        (**self).get_all_sms_codes_with_text(task_id)
+    }
+
+    fn get_sms_codes_batch(
+        &self,
+        task_ids: &[TaskId],
+    ) -> impl Future<Output = Vec<Result<Option<SmsCode>, Self::Error>>> + Send {
+        (**self).get_sms_codes_batch(task_ids)
+    }
+
+    fn get_activation_status(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<Option<ActivationState>, Self::Error>> + Send {
+        (**self).get_activation_status(task_id)
+    }
+
+    fn request_another_code(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        (**self).request_another_code(task_id)
+    }
+
+    fn finish_activation(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (**self).finish_activation(task_id)
+    }
+
+    fn cancel_activation(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (**self).cancel_activation(task_id)
+    }
+
+    fn cancel_all_pending(
+        &self,
+        task_ids: &[TaskId],
+    ) -> impl Future<Output = Vec<Result<(), Self::Error>>> + Send {
+        (**self).cancel_all_pending(task_ids)
+    }
+
+    fn is_task_alive(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        (**self).is_task_alive(task_id)
+    }
+
+    fn warmup(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (**self).warmup()
+    }
+
+    fn account_reputation(
+        &self,
+    ) -> impl Future<Output = Result<Option<ReputationLevel>, Self::Error>> + Send {
+        (**self).account_reputation()
+    }
+
+    fn country_prices(
+        &self,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<Vec<(Country, PriceEntry)>, Self::Error>> + Send {
+        (**self).country_prices(service)
+    }
+
+    fn available_countries_with_counts(
+        &self,
+        service: &Self::Service,
+    ) -> impl Future<Output = Result<Vec<CountryAvailability>, Self::Error>> + Send {
+        (**self).available_countries_with_counts(service)
+    }
+
+    fn health_report(&self) -> impl Future<Output = HealthReport> + Send {
+        (**self).health_report()
+    }
+
+    fn is_dial_code_supported(&self, dial_code: &DialCode) -> bool {
+        (**self).is_dial_code_supported(dial_code)
+    }
+
+    fn supports_service(&self, service: &Self::Service) -> bool {
+        (**self).supports_service(service)
+    }
+
+    fn available_countries(&self, service: &Self::Service) -> Vec<Country> {
+        (**self).available_countries(service)
+    }
+
+    fn supported_services(&self) -> Vec<Self::Service> {
+        (**self).supported_services()
+    }
+
+    fn service_name(&self, service: &Self::Service) -> String {
+        (**self).service_name(service)
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        (**self).provider_kind()
+    }
+}
+
+impl<P: SupportsIdempotencyKey> SupportsIdempotencyKey for Arc<P> {}
@@ -1,8 +1,12 @@
 //! Service configuration types.
 
+use keshvar::{Alpha2, Country};
 use std::time::Duration;
 use thiserror::Error;
 
+#[cfg(feature = "tracing")]
+use tracing::warn;
+
 /// Error when validating service configuration.
 #[derive(Debug, Clone, Error)]
 pub enum ConfigError {
@@ -30,14 +34,123 @@ pub enum ConfigError {
         /// The configured timeout.
         timeout: Duration,
     },
+    /// Poll interval is too large a fraction of the timeout.
+    #[error("Poll interval is {ratio:.2} of the timeout, must be at most {max_ratio:.2}")]
+    PollIntervalTooLargeRelativeToTimeout {
+        /// The configured `poll_interval / timeout` ratio.
+        ratio: f64,
+        /// The maximum allowed ratio.
+        max_ratio: f64,
+    },
+    /// Country-specific timeout is too short.
+    #[error("Timeout ({timeout:?}) for country {country:?} must be at least {min:?}")]
+    CountryTimeoutTooShort {
+        /// The country the timeout applies to.
+        country: Alpha2,
+        /// The configured timeout.
+        timeout: Duration,
+        /// The minimum allowed timeout.
+        min: Duration,
+    },
+    /// Timeout is too long.
+    #[error("Timeout ({timeout:?}) must be at most {max:?}")]
+    TimeoutExceedsMaximum {
+        /// The configured timeout.
+        timeout: Duration,
+        /// The maximum allowed timeout.
+        max: Duration,
+    },
+    /// Country-specific timeout is too long.
+    #[error("Timeout ({timeout:?}) for country {country:?} must be at most {max:?}")]
+    CountryTimeoutExceedsMaximum {
+        /// The country the timeout applies to.
+        country: Alpha2,
+        /// The configured timeout.
+        timeout: Duration,
+        /// The maximum allowed timeout.
+        max: Duration,
+    },
+    /// Poll interval is too large a fraction of a country-specific timeout.
+    #[error(
+        "Poll interval is {ratio:.2} of the timeout for country {country:?}, must be at most {max_ratio:.2}"
+    )]
+    CountryPollIntervalTooLargeRelativeToTimeout {
+        /// The country the timeout applies to.
+        country: Alpha2,
+        /// The configured `poll_interval / timeout` ratio.
+        ratio: f64,
+        /// The maximum allowed ratio.
+        max_ratio: f64,
+    },
+    /// Timeout is not a multiple of the poll interval, so the last wait is cut short.
+    ///
+    /// Only returned by [`SmsSolverServiceConfig::validate_strict`].
+    #[error(
+        "Poll interval ({poll_interval:?}) does not divide timeout ({timeout:?}), {remainder:?} left over"
+    )]
+    PollIntervalNotEvenDivisor {
+        /// The configured timeout.
+        timeout: Duration,
+        /// The configured poll interval.
+        poll_interval: Duration,
+        /// What is left of the timeout after the last full poll interval.
+        remainder: Duration,
+    },
+    /// Backoff multiplier is below 1.0 or not finite.
+    #[error("Backoff multiplier ({multiplier}) must be a finite number of at least 1.0")]
+    InvalidBackoffMultiplier {
+        /// The configured multiplier.
+        multiplier: f64,
+    },
+    /// Max poll interval is shorter than the poll interval while backing off.
+    #[error(
+        "Max poll interval ({max_poll_interval:?}) must be at least the poll interval ({poll_interval:?})"
+    )]
+    MaxPollIntervalTooShort {
+        /// The configured max poll interval.
+        max_poll_interval: Duration,
+        /// The configured poll interval.
+        poll_interval: Duration,
+    },
+    /// Environment variable has an invalid value.
+    #[error("Environment variable {name} has invalid value '{value}'")]
+    InvalidEnvVar {
+        /// The variable name.
+        name: String,
+        /// The value that failed to parse.
+        value: String,
+    },
 }
 
 /// Minimum allowed timeout (10 seconds).
 pub const MIN_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Maximum allowed timeout (10 minutes).
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Maximum allowed ratio of poll interval to timeout.
+///
+/// Guarantees at least two polls fit within the timeout.
+pub const MAX_POLL_INTERVAL_RATIO: f64 = 0.5;
+
 /// Minimum allowed poll interval (100ms).
 pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
+/// Environment variable holding the timeout in seconds.
+const TIMEOUT_SECS_ENV: &str = "SMS_SOLVER_TIMEOUT_SECS";
+
+/// Environment variable holding the poll interval in milliseconds.
+const POLL_INTERVAL_MS_ENV: &str = "SMS_SOLVER_POLL_INTERVAL_MS";
+
+/// Environment variable enabling [`SmsSolverServiceConfig::use_full_sms_list`].
+const USE_FULL_SMS_LIST_ENV: &str = "SMS_SOLVER_USE_FULL_SMS_LIST";
+
+/// Environment variable enabling [`SmsSolverServiceConfig::redact_logs`].
+const REDACT_LOGS_ENV: &str = "SMS_SOLVER_REDACT_LOGS";
+
+/// Environment variable enabling [`SmsSolverServiceConfig::auto_finish`].
+const AUTO_FINISH_ENV: &str = "SMS_SOLVER_AUTO_FINISH";
+
 /// Configuration for the SMS Solver Service.
 ///
 /// Controls timeout and polling behavior when waiting for SMS codes.
@@ -45,8 +158,44 @@ pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 pub struct SmsSolverServiceConfig {
     /// Maximum time to wait for SMS code before timing out.
     pub timeout: Duration,
+    /// Timeouts overriding [`timeout`](Self::timeout) for activations in a
+    /// given country, at most one per country.
+    pub country_timeouts: Vec<(Alpha2, Duration)>,
     /// Interval between polling attempts when waiting for SMS.
     pub poll_interval: Duration,
+    /// Factor the poll interval is multiplied by after each empty poll.
+    ///
+    /// `1.0` keeps the interval constant.
+    pub backoff_multiplier: f64,
+    /// Upper bound for the poll interval when backing off.
+    pub max_poll_interval: Duration,
+    /// Poll with [`Provider::get_all_sms_codes`] instead of only the latest SMS.
+    ///
+    /// Lets a validator pick an earlier code when a later SMS is not an OTP.
+    ///
+    /// [`Provider::get_all_sms_codes`]: crate::Provider::get_all_sms_codes
+    pub use_full_sms_list: bool,
+    /// Allow requesting numbers for sanctioned countries.
+    ///
+    /// When false, `get_number` fails with
+    /// [`SmsSolverServiceError::CountryRestricted`](crate::SmsSolverServiceError::CountryRestricted)
+    /// before calling the provider.
+    pub allow_restricted_countries: bool,
+    /// Log phone numbers redacted with [`FullNumber::redact`].
+    ///
+    /// Off by default; enable where logs may hold personal data.
+    ///
+    /// [`FullNumber::redact`]: crate::FullNumber::redact
+    pub redact_logs: bool,
+    /// Finish the activation after
+    /// [`get_number_and_wait`](crate::SmsSolverServiceTrait::get_number_and_wait)
+    /// receives a code.
+    pub auto_finish: bool,
+    /// Number requests run at once by
+    /// [`get_number_from_any`](crate::SmsSolverServiceTrait::get_number_from_any).
+    ///
+    /// 0 is treated as 1.
+    pub max_concurrent: usize,
 }
 
 impl Default for SmsSolverServiceConfig {
@@ -83,10 +232,19 @@ impl SmsSolverServiceConfig {
     ///
     /// - Timeout: 60 seconds
     /// - Poll interval: 1 second
+    /// - Max poll interval: 5 seconds
     pub fn fast() -> Self {
         Self {
             timeout: Duration::from_secs(60),
+            country_timeouts: Vec::new(),
             poll_interval: Duration::from_secs(1),
+            backoff_multiplier: 1.0,
+            max_poll_interval: Duration::from_secs(5),
+            use_full_sms_list: false,
+            allow_restricted_countries: false,
+            redact_logs: false,
+            auto_finish: true,
+            max_concurrent: 3,
         }
     }
 
@@ -97,10 +255,19 @@ impl SmsSolverServiceConfig {
     ///
     /// - Timeout: 120 seconds
     /// - Poll interval: 3 seconds
+    /// - Max poll interval: 15 seconds
     pub fn balanced() -> Self {
         Self {
             timeout: Duration::from_secs(120),
+            country_timeouts: Vec::new(),
             poll_interval: Duration::from_secs(3),
+            backoff_multiplier: 1.0,
+            max_poll_interval: Duration::from_secs(15),
+            use_full_sms_list: false,
+            allow_restricted_countries: false,
+            redact_logs: false,
+            auto_finish: true,
+            max_concurrent: 3,
         }
     }
 
@@ -111,10 +278,19 @@ impl SmsSolverServiceConfig {
     ///
     /// - Timeout: 300 seconds (5 minutes)
     /// - Poll interval: 5 seconds
+    /// - Max poll interval: 30 seconds
     pub fn patient() -> Self {
         Self {
             timeout: Duration::from_secs(300),
+            country_timeouts: Vec::new(),
             poll_interval: Duration::from_secs(5),
+            backoff_multiplier: 1.0,
+            max_poll_interval: Duration::from_secs(30),
+            use_full_sms_list: false,
+            allow_restricted_countries: false,
+            redact_logs: false,
+            auto_finish: true,
+            max_concurrent: 3,
         }
     }
 
@@ -124,18 +300,145 @@ impl SmsSolverServiceConfig {
         self
     }
 
+    /// Create a new config that waits `timeout` for activations in `country`.
+    pub fn with_country_timeout(mut self, country: Country, timeout: Duration) -> Self {
+        set_country_timeout(&mut self.country_timeouts, country.alpha2(), timeout);
+        self
+    }
+
+    /// The timeout for activations in `country`.
+    ///
+    /// Returns the country-specific timeout if one is set, otherwise
+    /// [`timeout`](Self::timeout).
+    pub fn timeout_for(&self, country: &Country) -> Duration {
+        self.timeout_for_alpha2(country.alpha2())
+    }
+
+    /// The timeout for activations in the country with ISO alpha-2 code `alpha2`.
+    pub(crate) fn timeout_for_alpha2(&self, alpha2: Alpha2) -> Duration {
+        self.country_timeouts
+            .iter()
+            .find(|(country, _)| *country == alpha2)
+            .map_or(self.timeout, |(_, timeout)| *timeout)
+    }
+
     /// Create a new config with a custom poll interval.
     pub fn with_poll_interval(mut self, interval: Duration) -> Self {
         self.poll_interval = interval;
         self
     }
 
+    /// Create a new config that backs off polling by `multiplier`, up to
+    /// `max_interval`.
+    pub fn with_poll_backoff(mut self, multiplier: f64, max_interval: Duration) -> Self {
+        self.backoff_multiplier = multiplier;
+        self.max_poll_interval = max_interval;
+        self
+    }
+
+    /// Create a new config that polls the full SMS list.
+    pub fn with_full_sms_list(mut self, enabled: bool) -> Self {
+        self.use_full_sms_list = enabled;
+        self
+    }
+
+    /// Create a new config that allows sanctioned countries.
+    pub fn with_allow_restricted_countries(mut self, allow: bool) -> Self {
+        self.allow_restricted_countries = allow;
+        self
+    }
+
+    /// Overwrite the fields that are set in `patch`, keeping the others.
+    ///
+    /// Patches can be chained to layer config sources, later ones winning:
+    ///
+    /// ```rust
+    /// use sms_solvers::{SmsSolverServiceConfig, SmsSolverServiceConfigPatch};
+    /// use std::time::Duration;
+    ///
+    /// let file = SmsSolverServiceConfigPatch {
+    ///     timeout: Some(Duration::from_secs(180)),
+    ///     ..Default::default()
+    /// };
+    /// let cli = SmsSolverServiceConfigPatch {
+    ///     redact_logs: Some(true),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let config = SmsSolverServiceConfig::default().apply_patch(file).apply_patch(cli);
+    /// assert_eq!(config.timeout, Duration::from_secs(180));
+    /// assert!(config.redact_logs);
+    /// ```
+    pub fn apply_patch(mut self, patch: SmsSolverServiceConfigPatch) -> Self {
+        if let Some(timeout) = patch.timeout {
+            self.timeout = timeout;
+        }
+        for (country, timeout) in patch.country_timeouts.into_iter().flatten() {
+            set_country_timeout(&mut self.country_timeouts, country, timeout);
+        }
+        if let Some(poll_interval) = patch.poll_interval {
+            self.poll_interval = poll_interval;
+        }
+        if let Some(multiplier) = patch.backoff_multiplier {
+            self.backoff_multiplier = multiplier;
+        }
+        if let Some(max_poll_interval) = patch.max_poll_interval {
+            self.max_poll_interval = max_poll_interval;
+        }
+        if let Some(enabled) = patch.use_full_sms_list {
+            self.use_full_sms_list = enabled;
+        }
+        if let Some(allow) = patch.allow_restricted_countries {
+            self.allow_restricted_countries = allow;
+        }
+        if let Some(enabled) = patch.redact_logs {
+            self.redact_logs = enabled;
+        }
+        if let Some(enabled) = patch.auto_finish {
+            self.auto_finish = enabled;
+        }
+        if let Some(max_concurrent) = patch.max_concurrent {
+            self.max_concurrent = max_concurrent;
+        }
+        self
+    }
+
+    /// Create a new config that redacts phone numbers in logs.
+    pub fn with_redact_logs(mut self, enabled: bool) -> Self {
+        self.redact_logs = enabled;
+        self
+    }
+
+    /// Create a new config that sets whether activations are finished
+    /// automatically after a code is received.
+    pub fn with_auto_finish(mut self, enabled: bool) -> Self {
+        self.auto_finish = enabled;
+        self
+    }
+
+    /// Create a new config that runs up to `max_concurrent` number requests
+    /// at once when racing countries.
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent;
+        self
+    }
+
     /// Validate the configuration.
     ///
+    /// Rejects configurations that cannot work. A timeout that is not a
+    /// multiple of the poll interval is only logged as a warning; use
+    /// [`validate_strict`](Self::validate_strict) to reject it too.
+    ///
     /// Returns an error if:
     /// - Timeout is less than 10 seconds
+    /// - Timeout is greater than 10 minutes
+    /// - A country-specific timeout is less than 10 seconds, greater than
+    ///   10 minutes, or less than twice the poll interval
     /// - Poll interval is less than 100ms
     /// - Poll interval is greater than or equal to timeout
+    /// - Poll interval is more than half of the timeout
+    /// - Backoff multiplier is below 1.0 or not finite
+    /// - Max poll interval is less than the poll interval while backing off
     ///
     /// # Example
     ///
@@ -162,6 +465,13 @@ impl SmsSolverServiceConfig {
             });
         }
 
+        if self.timeout > MAX_TIMEOUT {
+            return Err(ConfigError::TimeoutExceedsMaximum {
+                timeout: self.timeout,
+                max: MAX_TIMEOUT,
+            });
+        }
+
         if self.poll_interval < MIN_POLL_INTERVAL {
             return Err(ConfigError::PollIntervalTooShort {
                 poll_interval: self.poll_interval,
@@ -176,8 +486,215 @@ impl SmsSolverServiceConfig {
             });
         }
 
+        let ratio = self.poll_interval.as_secs_f64() / self.timeout.as_secs_f64();
+        if ratio > MAX_POLL_INTERVAL_RATIO {
+            return Err(ConfigError::PollIntervalTooLargeRelativeToTimeout {
+                ratio,
+                max_ratio: MAX_POLL_INTERVAL_RATIO,
+            });
+        }
+
+        for &(country, timeout) in &self.country_timeouts {
+            if timeout < MIN_TIMEOUT {
+                return Err(ConfigError::CountryTimeoutTooShort {
+                    country,
+                    timeout,
+                    min: MIN_TIMEOUT,
+                });
+            }
+
+            if timeout > MAX_TIMEOUT {
+                return Err(ConfigError::CountryTimeoutExceedsMaximum {
+                    country,
+                    timeout,
+                    max: MAX_TIMEOUT,
+                });
+            }
+
+            let ratio = self.poll_interval.as_secs_f64() / timeout.as_secs_f64();
+            if ratio > MAX_POLL_INTERVAL_RATIO {
+                return Err(ConfigError::CountryPollIntervalTooLargeRelativeToTimeout {
+                    country,
+                    ratio,
+                    max_ratio: MAX_POLL_INTERVAL_RATIO,
+                });
+            }
+        }
+
+        if !self.backoff_multiplier.is_finite() || self.backoff_multiplier < 1.0 {
+            return Err(ConfigError::InvalidBackoffMultiplier {
+                multiplier: self.backoff_multiplier,
+            });
+        }
+
+        if self.backoff_multiplier > 1.0 && self.max_poll_interval < self.poll_interval {
+            return Err(ConfigError::MaxPollIntervalTooShort {
+                max_poll_interval: self.max_poll_interval,
+                poll_interval: self.poll_interval,
+            });
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let remainder = self.poll_remainder();
+            if !remainder.is_zero() {
+                warn!(
+                    timeout = ?self.timeout,
+                    poll_interval = ?self.poll_interval,
+                    remainder = ?remainder,
+                    "Poll interval does not divide timeout, the last wait is cut short"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the configuration, also rejecting settings that are valid
+    /// but likely unintentional.
+    ///
+    /// In addition to [`validate`](Self::validate), returns
+    /// [`ConfigError::PollIntervalNotEvenDivisor`] if the timeout is not a
+    /// multiple of the poll interval.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::SmsSolverServiceConfig;
+    /// use std::time::Duration;
+    ///
+    /// let config = SmsSolverServiceConfig::builder()
+    ///     .timeout(Duration::from_secs(60))
+    ///     .poll_interval(Duration::from_secs(7))
+    ///     .build();
+    /// assert!(config.validate().is_ok());
+    /// assert!(config.validate_strict().is_err());
+    /// ```
+    pub fn validate_strict(&self) -> Result<(), ConfigError> {
+        self.validate()?;
+
+        let remainder = self.poll_remainder();
+        if !remainder.is_zero() {
+            return Err(ConfigError::PollIntervalNotEvenDivisor {
+                timeout: self.timeout,
+                poll_interval: self.poll_interval,
+                remainder,
+            });
+        }
+
         Ok(())
     }
+
+    /// The interval to wait after an empty poll that waited `current`.
+    pub(crate) fn next_poll_interval(&self, current: Duration) -> Duration {
+        if self.backoff_multiplier.is_nan() || self.backoff_multiplier <= 1.0 {
+            return current;
+        }
+        let max = self.max_poll_interval.max(self.poll_interval);
+        Duration::try_from_secs_f64(current.as_secs_f64() * self.backoff_multiplier)
+            .map_or(max, |next| next.min(max))
+    }
+
+    /// What is left of the timeout after the last full poll interval.
+    fn poll_remainder(&self) -> Duration {
+        let poll_interval = self.poll_interval.as_nanos();
+        if poll_interval == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.timeout.as_nanos() % poll_interval) as u64)
+    }
+}
+
+/// Partial [`SmsSolverServiceConfig`] for layered config loading.
+///
+/// Only fields set to `Some` are applied by
+/// [`SmsSolverServiceConfig::apply_patch`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SmsSolverServiceConfigPatch {
+    /// Overrides [`SmsSolverServiceConfig::timeout`].
+    pub timeout: Option<Duration>,
+    /// Sets entries of [`SmsSolverServiceConfig::country_timeouts`], keeping
+    /// those of other countries.
+    pub country_timeouts: Option<Vec<(Alpha2, Duration)>>,
+    /// Overrides [`SmsSolverServiceConfig::poll_interval`].
+    pub poll_interval: Option<Duration>,
+    /// Overrides [`SmsSolverServiceConfig::backoff_multiplier`].
+    pub backoff_multiplier: Option<f64>,
+    /// Overrides [`SmsSolverServiceConfig::max_poll_interval`].
+    pub max_poll_interval: Option<Duration>,
+    /// Overrides [`SmsSolverServiceConfig::use_full_sms_list`].
+    pub use_full_sms_list: Option<bool>,
+    /// Overrides [`SmsSolverServiceConfig::allow_restricted_countries`].
+    ///
+    /// Never read from the environment; set it in code.
+    pub allow_restricted_countries: Option<bool>,
+    /// Overrides [`SmsSolverServiceConfig::redact_logs`].
+    pub redact_logs: Option<bool>,
+    /// Overrides [`SmsSolverServiceConfig::auto_finish`].
+    pub auto_finish: Option<bool>,
+    /// Overrides [`SmsSolverServiceConfig::max_concurrent`].
+    pub max_concurrent: Option<usize>,
+}
+
+impl SmsSolverServiceConfigPatch {
+    /// Create a patch from the environment variables that are set.
+    ///
+    /// Reads `SMS_SOLVER_TIMEOUT_SECS`, `SMS_SOLVER_POLL_INTERVAL_MS` and the
+    /// boolean `SMS_SOLVER_USE_FULL_SMS_LIST`, `SMS_SOLVER_REDACT_LOGS` and
+    /// `SMS_SOLVER_AUTO_FINISH` (`true`/`false` or `1`/`0`). Unset variables
+    /// leave their field as `None`.
+    ///
+    /// [`allow_restricted_countries`](Self::allow_restricted_countries) is
+    /// left as `None` so that restricted countries can only be enabled in
+    /// code.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let duration = |name: &str, from: fn(u64) -> Duration| {
+            var(name)
+                .map(|value| parse_env_value(name, &value, |v| v.parse().ok().map(from)))
+                .transpose()
+        };
+        let flag = |name: &str| {
+            var(name)
+                .map(|value| {
+                    parse_env_value(name, &value, |v| match v {
+                        "true" | "1" => Some(true),
+                        "false" | "0" => Some(false),
+                        _ => None,
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            timeout: duration(TIMEOUT_SECS_ENV, Duration::from_secs)?,
+            country_timeouts: None,
+            poll_interval: duration(POLL_INTERVAL_MS_ENV, Duration::from_millis)?,
+            backoff_multiplier: None,
+            max_poll_interval: None,
+            use_full_sms_list: flag(USE_FULL_SMS_LIST_ENV)?,
+            allow_restricted_countries: None,
+            redact_logs: flag(REDACT_LOGS_ENV)?,
+            auto_finish: flag(AUTO_FINISH_ENV)?,
+            max_concurrent: None,
+        })
+    }
+}
+
+/// Parse a trimmed environment variable value, reporting failures as
+/// [`ConfigError::InvalidEnvVar`].
+fn parse_env_value<T>(
+    name: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<T, ConfigError> {
+    parse(value.trim()).ok_or_else(|| ConfigError::InvalidEnvVar {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
 }
 
 /// Builder for SmsSolverServiceConfig.
@@ -186,15 +703,37 @@ impl SmsSolverServiceConfig {
 #[derive(Debug, Clone)]
 pub struct SmsSolverServiceConfigBuilder {
     pub(crate) timeout: Duration,
+    pub(crate) country_timeouts: Vec<(Alpha2, Duration)>,
     pub(crate) poll_interval: Duration,
+    pub(crate) backoff_multiplier: f64,
+    pub(crate) max_poll_interval: Duration,
+    pub(crate) use_full_sms_list: bool,
+    pub(crate) allow_restricted_countries: bool,
+    pub(crate) redact_logs: bool,
+    pub(crate) auto_finish: bool,
+    pub(crate) max_concurrent: usize,
 }
 
 impl Default for SmsSolverServiceConfigBuilder {
     fn default() -> Self {
-        let config = SmsSolverServiceConfig::balanced();
+        SmsSolverServiceConfig::balanced().into()
+    }
+}
+
+impl From<SmsSolverServiceConfig> for SmsSolverServiceConfigBuilder {
+    /// Start a builder from an existing configuration.
+    fn from(config: SmsSolverServiceConfig) -> Self {
         Self {
             timeout: config.timeout,
+            country_timeouts: config.country_timeouts,
             poll_interval: config.poll_interval,
+            backoff_multiplier: config.backoff_multiplier,
+            max_poll_interval: config.max_poll_interval,
+            use_full_sms_list: config.use_full_sms_list,
+            allow_restricted_countries: config.allow_restricted_countries,
+            redact_logs: config.redact_logs,
+            auto_finish: config.auto_finish,
+            max_concurrent: config.max_concurrent,
         }
     }
 }
@@ -213,6 +752,15 @@ impl SmsSolverServiceConfigBuilder {
         self
     }
 
+    /// Set the timeout for waiting for SMS codes in `country`, overriding
+    /// [`timeout`](Self::timeout).
+    ///
+    /// Default: none
+    pub fn country_timeout(mut self, country: Country, timeout: Duration) -> Self {
+        set_country_timeout(&mut self.country_timeouts, country.alpha2(), timeout);
+        self
+    }
+
     /// Set the polling interval when waiting for SMS codes.
     ///
     /// Default: 3 seconds
@@ -221,6 +769,62 @@ impl SmsSolverServiceConfigBuilder {
         self
     }
 
+    /// Multiply the polling interval by `multiplier` after each empty poll.
+    ///
+    /// Default: 1.0 (constant interval)
+    pub fn poll_backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.backoff_multiplier = multiplier;
+        self
+    }
+
+    /// Cap the polling interval when backing off.
+    ///
+    /// Default: 15 seconds
+    pub fn max_poll_interval(mut self, interval: Duration) -> Self {
+        self.max_poll_interval = interval;
+        self
+    }
+
+    /// Poll the full SMS list instead of only the latest SMS.
+    ///
+    /// Default: false
+    pub fn use_full_sms_list(mut self, enabled: bool) -> Self {
+        self.use_full_sms_list = enabled;
+        self
+    }
+
+    /// Allow requesting numbers for sanctioned countries.
+    ///
+    /// Default: false
+    pub fn allow_restricted_countries(mut self, allow: bool) -> Self {
+        self.allow_restricted_countries = allow;
+        self
+    }
+
+    /// Redact phone numbers in logs.
+    ///
+    /// Default: false
+    pub fn redact_logs(mut self, enabled: bool) -> Self {
+        self.redact_logs = enabled;
+        self
+    }
+
+    /// Finish activations automatically after a code is received.
+    ///
+    /// Default: true
+    pub fn auto_finish(mut self, enabled: bool) -> Self {
+        self.auto_finish = enabled;
+        self
+    }
+
+    /// Set how many number requests run at once when racing countries.
+    ///
+    /// Default: 3
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent;
+        self
+    }
+
     /// Build the SmsSolverServiceConfig.
     ///
     /// Note: This does not validate the configuration. Use `try_build()`
@@ -228,7 +832,15 @@ impl SmsSolverServiceConfigBuilder {
     pub fn build(self) -> SmsSolverServiceConfig {
         SmsSolverServiceConfig {
             timeout: self.timeout,
+            country_timeouts: self.country_timeouts,
             poll_interval: self.poll_interval,
+            backoff_multiplier: self.backoff_multiplier,
+            max_poll_interval: self.max_poll_interval,
+            use_full_sms_list: self.use_full_sms_list,
+            allow_restricted_countries: self.allow_restricted_countries,
+            redact_logs: self.redact_logs,
+            auto_finish: self.auto_finish,
+            max_concurrent: self.max_concurrent,
         }
     }
 
@@ -263,6 +875,17 @@ impl SmsSolverServiceConfigBuilder {
     }
 }
 
+/// Set the timeout for `country`, replacing any earlier one.
+fn set_country_timeout(timeouts: &mut Vec<(Alpha2, Duration)>, country: Alpha2, timeout: Duration) {
+    match timeouts
+        .iter_mut()
+        .find(|(existing, _)| *existing == country)
+    {
+        Some((_, existing)) => *existing = timeout,
+        None => timeouts.push((country, timeout)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,6 +897,93 @@ mod tests {
         assert_eq!(config.poll_interval, Duration::from_secs(3));
     }
 
+    #[test]
+    fn test_apply_patch_layers() {
+        let env = SmsSolverServiceConfigPatch::from_vars(|name| match name {
+            TIMEOUT_SECS_ENV => Some("90".to_string()),
+            AUTO_FINISH_ENV => Some("0".to_string()),
+            "SMS_SOLVER_ALLOW_RESTRICTED_COUNTRIES" => Some("1".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(env.poll_interval, None);
+        assert_eq!(env.allow_restricted_countries, None);
+
+        let cli = SmsSolverServiceConfigPatch {
+            poll_interval: Some(Duration::from_secs(2)),
+            auto_finish: Some(true),
+            ..Default::default()
+        };
+        let config = SmsSolverServiceConfig::patient()
+            .apply_patch(env)
+            .apply_patch(cli);
+
+        assert_eq!(config.timeout, Duration::from_secs(90));
+        assert_eq!(config.poll_interval, Duration::from_secs(2));
+        assert!(config.auto_finish);
+        assert!(!config.redact_logs);
+    }
+
+    #[test]
+    fn test_apply_patch_polling_and_concurrency() {
+        let config = SmsSolverServiceConfig::default()
+            .with_country_timeout(Alpha2::RU.to_country(), Duration::from_secs(200))
+            .with_country_timeout(Alpha2::CN.to_country(), Duration::from_secs(300))
+            .apply_patch(SmsSolverServiceConfigPatch {
+                country_timeouts: Some(vec![
+                    (Alpha2::CN, Duration::from_secs(400)),
+                    (Alpha2::IN, Duration::from_secs(180)),
+                ]),
+                backoff_multiplier: Some(1.5),
+                max_poll_interval: Some(Duration::from_secs(20)),
+                max_concurrent: Some(5),
+                ..Default::default()
+            });
+
+        assert_eq!(
+            config.country_timeouts,
+            vec![
+                (Alpha2::RU, Duration::from_secs(200)),
+                (Alpha2::CN, Duration::from_secs(400)),
+                (Alpha2::IN, Duration::from_secs(180)),
+            ]
+        );
+        assert_eq!(config.backoff_multiplier, 1.5);
+        assert_eq!(config.max_poll_interval, Duration::from_secs(20));
+        assert_eq!(config.max_concurrent, 5);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_patch_from_vars_rejects_invalid_value() {
+        let err = SmsSolverServiceConfigPatch::from_vars(|name| {
+            (name == REDACT_LOGS_ENV).then(|| "yes".to_string())
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidEnvVar { ref name, ref value }
+                if name == REDACT_LOGS_ENV && value == "yes"
+        ));
+    }
+
+    #[test]
+    fn test_validate_strict_rejects_uneven_poll_interval() {
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_secs(7))
+            .build();
+
+        assert!(config.validate().is_ok());
+        assert!(matches!(
+            config.validate_strict(),
+            Err(ConfigError::PollIntervalNotEvenDivisor { remainder, .. })
+                if remainder == Duration::from_secs(4)
+        ));
+
+        assert!(SmsSolverServiceConfig::balanced().validate_strict().is_ok());
+    }
+
     #[test]
     fn test_config_presets() {
         let fast = SmsSolverServiceConfig::fast();
@@ -289,6 +999,96 @@ mod tests {
         assert_eq!(patient.poll_interval, Duration::from_secs(5));
     }
 
+    #[test]
+    fn test_country_timeouts() {
+        for preset in [
+            SmsSolverServiceConfig::fast(),
+            SmsSolverServiceConfig::patient(),
+        ] {
+            assert!(preset.country_timeouts.is_empty());
+            assert_eq!(preset.timeout_for(&Alpha2::RU.to_country()), preset.timeout);
+            assert!(preset.validate().is_ok());
+        }
+
+        let config = SmsSolverServiceConfig::patient()
+            .with_country_timeout(Alpha2::CN.to_country(), Duration::from_secs(400));
+        assert_eq!(
+            config.timeout_for(&Alpha2::CN.to_country()),
+            Duration::from_secs(400)
+        );
+        assert_eq!(
+            config.timeout_for(&Alpha2::US.to_country()),
+            Duration::from_secs(300)
+        );
+
+        let config = SmsSolverServiceConfig::builder()
+            .country_timeout(Alpha2::RU.to_country(), Duration::from_secs(240))
+            .build();
+        assert_eq!(
+            config.timeout_for(&Alpha2::RU.to_country()),
+            Duration::from_secs(240)
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_validation_country_timeout_too_short() {
+        let config = SmsSolverServiceConfig::default()
+            .with_country_timeout(Alpha2::RU.to_country(), Duration::from_secs(5));
+        match config.validate() {
+            Err(ConfigError::CountryTimeoutTooShort {
+                country,
+                timeout,
+                min,
+            }) => {
+                assert_eq!(country, Alpha2::RU);
+                assert_eq!(timeout, Duration::from_secs(5));
+                assert_eq!(min, MIN_TIMEOUT);
+            }
+            other => panic!("Expected CountryTimeoutTooShort, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_config_validation_country_timeout_too_long() {
+        let config = SmsSolverServiceConfig::default()
+            .with_country_timeout(Alpha2::RU.to_country(), Duration::from_secs(900));
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::CountryTimeoutExceedsMaximum {
+                country: Alpha2::RU,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_config_validation_country_timeout_poll_ratio() {
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(120))
+            .poll_interval(Duration::from_secs(8))
+            .country_timeout(Alpha2::RU.to_country(), Duration::from_secs(15))
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::CountryPollIntervalTooLargeRelativeToTimeout {
+                country: Alpha2::RU,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_country_timeout_replaces_earlier_one() {
+        let config = SmsSolverServiceConfig::default()
+            .with_country_timeout(Alpha2::RU.to_country(), Duration::from_secs(200))
+            .with_country_timeout(Alpha2::RU.to_country(), Duration::from_secs(240));
+        assert_eq!(
+            config.country_timeouts,
+            vec![(Alpha2::RU, Duration::from_secs(240))]
+        );
+    }
+
     #[test]
     fn test_config_builder() {
         let config = SmsSolverServiceConfig::builder()
@@ -334,6 +1134,59 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_presets_keep_constant_poll_interval() {
+        for config in [
+            SmsSolverServiceConfig::fast(),
+            SmsSolverServiceConfig::balanced(),
+            SmsSolverServiceConfig::patient(),
+        ] {
+            assert!(config.validate().is_ok());
+            assert_eq!(
+                config.next_poll_interval(config.poll_interval),
+                config.poll_interval
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_poll_interval_caps_at_max() {
+        let config = SmsSolverServiceConfig::builder()
+            .poll_interval(Duration::from_secs(2))
+            .poll_backoff_multiplier(1.5)
+            .max_poll_interval(Duration::from_secs(4))
+            .build();
+
+        assert_eq!(
+            config.next_poll_interval(Duration::from_secs(2)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            config.next_poll_interval(Duration::from_secs(3)),
+            Duration::from_secs(4)
+        );
+    }
+
+    #[test]
+    fn test_config_validation_backoff() {
+        let config = SmsSolverServiceConfig::builder()
+            .poll_backoff_multiplier(0.5)
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidBackoffMultiplier { .. })
+        ));
+
+        let config = SmsSolverServiceConfig::builder()
+            .poll_backoff_multiplier(2.0)
+            .max_poll_interval(Duration::from_secs(1))
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::MaxPollIntervalTooShort { .. })
+        ));
+    }
+
     #[test]
     fn test_config_validation_poll_interval_too_short() {
         let config = SmsSolverServiceConfig::builder()
@@ -357,6 +1210,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_config_validation_poll_interval_too_large_relative_to_timeout() {
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(120))
+            .poll_interval(Duration::from_secs(119))
+            .build();
+        match config.validate() {
+            Err(ConfigError::PollIntervalTooLargeRelativeToTimeout { ratio, max_ratio }) => {
+                assert!(ratio > 0.99);
+                assert_eq!(max_ratio, MAX_POLL_INTERVAL_RATIO);
+            }
+            other => panic!(
+                "Expected PollIntervalTooLargeRelativeToTimeout, got {:?}",
+                other
+            ),
+        }
+
+        // Exactly half is still allowed
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(120))
+            .poll_interval(Duration::from_secs(60))
+            .build();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_validation_timeout_exceeds_maximum() {
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(3 * 60 * 60))
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::TimeoutExceedsMaximum { .. })
+        ));
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(MAX_TIMEOUT)
+            .build();
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_try_build_success() {
         let config = SmsSolverServiceConfig::builder()
@@ -1,10 +1,13 @@
 //! Service-level error types.
 
+use super::config::ConfigError;
+use super::hooks::HookError;
 use crate::errors::RetryableError;
-use crate::types::{DialCode, TaskId};
+use crate::types::{ActivationState, DialCode, TaskId};
 use keshvar::Country;
 use std::error::Error as StdError;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 /// Service-level errors that wrap provider errors.
@@ -39,6 +42,17 @@ pub enum SmsSolverServiceError {
         message: String,
     },
 
+    /// No number was acquired before the deadline.
+    #[error("Timed out acquiring a {service_name} number for {}", country.iso_short_name())]
+    AcquisitionTimeout {
+        /// The requested country.
+        country: Box<Country>,
+        /// Name of the requested service.
+        service_name: String,
+        /// The deadline that was reached.
+        deadline: Instant,
+    },
+
     /// Timeout waiting for SMS code.
     #[error(
         "Timeout waiting for SMS code after {:.1}s (polled {} times); Task id: {task_id}",
@@ -78,7 +92,7 @@ pub enum SmsSolverServiceError {
     },
 
     /// The dial code is blacklisted.
-    #[error("Dial code +{dial_code} is blacklisted")]
+    #[error("Dial code +{} is blacklisted", dial_code.as_str())]
     DialCodeBlacklisted {
         /// The blacklisted dial code.
         dial_code: DialCode,
@@ -89,20 +103,153 @@ pub enum SmsSolverServiceError {
     /// No available dial codes after filtering.
     #[error("No available dial codes after filtering")]
     NoAvailableDialCodes,
+
+    /// None of the candidate countries has numbers in stock.
+    #[error("No numbers in stock in any of the {candidates} candidate countries")]
+    NoCandidateCountryInStock {
+        /// Number of candidate countries checked.
+        candidates: usize,
+    },
+
+    /// The provider has no price list to pick the cheapest country from.
+    #[error("The provider does not report country prices")]
+    CountryPricesUnsupported,
+
+    /// An argument was rejected before any request was made.
+    #[error("Invalid argument: {reason}")]
+    InvalidArgument {
+        /// Why the argument was rejected.
+        reason: &'static str,
+    },
+
+    /// Every number request raced by
+    /// [`get_number_from_any`](crate::SmsSolverServiceTrait::get_number_from_any) failed.
+    #[error("All {attempted} country requests failed")]
+    AllCountriesFailed {
+        /// Number of country and service pairs tried.
+        attempted: usize,
+        /// Errors of the failed requests, in the order they completed.
+        errors: Vec<SmsSolverServiceError>,
+    },
+
+    /// Only codes rejected by the validator were received before timeout.
+    #[error("No valid SMS code received ({codes_seen} rejected); Task id: {task_id}")]
+    NoValidCodeReceived {
+        /// Number of distinct codes rejected by the validator.
+        codes_seen: u32,
+        /// The task ID that timed out.
+        task_id: TaskId,
+    },
+
+    /// Polling failed permanently with an error reporting the activation as
+    /// live (see [`RetryableError::live_task_id`]), and it could not be
+    /// cancelled.
+    ///
+    /// The phone number is still held; use `task_id` to cancel it.
+    #[error("Polling failed for task {task_id} and the activation is still live: {source}")]
+    PhoneAcquiredButPollingFailed {
+        /// The task ID of the live activation.
+        task_id: TaskId,
+        /// The polling error.
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+        /// Whether a fresh operation might succeed.
+        should_retry_operation: bool,
+    },
+
+    /// The activation expired or no longer exists, so no SMS can arrive.
+    #[error("Activation is no longer alive; Task id: {task_id}")]
+    ActivationExpired {
+        /// The expired task ID.
+        task_id: TaskId,
+    },
+
+    /// The activation was finished or cancelled before an SMS arrived.
+    #[error("Activation ended as {state:?} before an SMS arrived; Task id: {task_id}")]
+    ActivationEnded {
+        /// The ended task ID.
+        task_id: TaskId,
+        /// The terminal state reported by the provider.
+        state: ActivationState,
+    },
+
+    /// A deduplicated request failed; the error is shared by all its callers.
+    #[error(transparent)]
+    Shared(Arc<SmsSolverServiceError>),
+
+    /// The country is restricted and no request was made.
+    #[error("Country {} is restricted: {reason}", country.iso_short_name())]
+    CountryRestricted {
+        /// The restricted country.
+        country: Box<Country>,
+        /// Why the country is restricted.
+        reason: &'static str,
+    },
+
+    /// A `get_number` hook rejected the operation.
+    #[error("Rejected by hook: {source}")]
+    HookRejected {
+        /// The hook error.
+        #[source]
+        source: HookError,
+        /// The cancelled activation, if a post-get hook rejected it.
+        task_id: Option<TaskId>,
+    },
+}
+
+/// Errors returned when building a [`SmsSolverService`](super::SmsSolverService).
+#[derive(Debug, Error)]
+pub enum SmsSolverServiceBuildError {
+    /// The service configuration is invalid.
+    #[error("Invalid service configuration: {0}")]
+    Config(#[from] ConfigError),
+
+    /// The provider failed to warm up.
+    #[error("Provider warmup failed: {0}")]
+    Warmup(#[source] Box<dyn StdError + Send + Sync>),
+}
+
+impl SmsSolverServiceError {
+    /// Whether this error, returned while waiting for a code, means the
+    /// activation was already cancelled or has ended.
+    pub(crate) fn activation_released(&self) -> bool {
+        match self {
+            SmsSolverServiceError::Shared(error) => error.activation_released(),
+            SmsSolverServiceError::SmsTimeout { .. }
+            | SmsSolverServiceError::NoValidCodeReceived { .. }
+            | SmsSolverServiceError::Cancelled { .. }
+            | SmsSolverServiceError::ActivationExpired { .. }
+            | SmsSolverServiceError::ActivationEnded { .. }
+            | SmsSolverServiceError::Provider { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 impl RetryableError for SmsSolverServiceError {
     fn is_retryable(&self) -> bool {
         match self {
             SmsSolverServiceError::Provider { is_retryable, .. } => *is_retryable,
+            SmsSolverServiceError::Shared(error) => error.is_retryable(),
             SmsSolverServiceError::SmsTimeout { .. }
+            | SmsSolverServiceError::AcquisitionTimeout { .. }
             | SmsSolverServiceError::Cancelled { .. }
             | SmsSolverServiceError::CancelFailed { .. }
             | SmsSolverServiceError::NoNumbersAvailable { .. }
             | SmsSolverServiceError::InvalidDialCode { .. }
             | SmsSolverServiceError::NumberParse { .. }
             | SmsSolverServiceError::DialCodeBlacklisted { .. }
-            | SmsSolverServiceError::NoAvailableDialCodes => false,
+            | SmsSolverServiceError::NoAvailableDialCodes
+            | SmsSolverServiceError::NoCandidateCountryInStock { .. }
+            | SmsSolverServiceError::CountryPricesUnsupported
+            | SmsSolverServiceError::InvalidArgument { .. }
+            | SmsSolverServiceError::AllCountriesFailed { .. }
+            | SmsSolverServiceError::NoValidCodeReceived { .. }
+            | SmsSolverServiceError::PhoneAcquiredButPollingFailed { .. }
+            | SmsSolverServiceError::ActivationExpired { .. }
+            | SmsSolverServiceError::ActivationEnded { .. }
+            | SmsSolverServiceError::CountryRestricted { .. }
+            | SmsSolverServiceError::HookRejected { .. } => false,
         }
     }
 
@@ -111,15 +258,40 @@ impl RetryableError for SmsSolverServiceError {
             SmsSolverServiceError::Provider {
                 should_retry_operation,
                 ..
+            }
+            | SmsSolverServiceError::PhoneAcquiredButPollingFailed {
+                should_retry_operation,
+                ..
             } => *should_retry_operation,
+            SmsSolverServiceError::Shared(error) => error.should_retry_operation(),
             SmsSolverServiceError::SmsTimeout { .. } => true,
+            SmsSolverServiceError::AcquisitionTimeout { .. } => true,
             SmsSolverServiceError::NoNumbersAvailable { .. } => true,
+            SmsSolverServiceError::NoCandidateCountryInStock { .. } => true,
+            SmsSolverServiceError::AllCountriesFailed { errors, .. } => {
+                errors.iter().any(|e| e.should_retry_operation())
+            }
+            SmsSolverServiceError::NoValidCodeReceived { .. } => true,
+            SmsSolverServiceError::ActivationExpired { .. } => true,
+            SmsSolverServiceError::ActivationEnded { .. } => true,
             SmsSolverServiceError::Cancelled { .. }
             | SmsSolverServiceError::CancelFailed { .. }
             | SmsSolverServiceError::InvalidDialCode { .. }
             | SmsSolverServiceError::NumberParse { .. }
             | SmsSolverServiceError::DialCodeBlacklisted { .. }
-            | SmsSolverServiceError::NoAvailableDialCodes => false,
+            | SmsSolverServiceError::NoAvailableDialCodes
+            | SmsSolverServiceError::CountryPricesUnsupported
+            | SmsSolverServiceError::InvalidArgument { .. }
+            | SmsSolverServiceError::CountryRestricted { .. }
+            | SmsSolverServiceError::HookRejected { .. } => false,
+        }
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            SmsSolverServiceError::PhoneAcquiredButPollingFailed { task_id, .. } => Some(task_id),
+            SmsSolverServiceError::Shared(error) => error.live_task_id(),
+            _ => None,
         }
     }
 }
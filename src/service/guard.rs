@@ -0,0 +1,205 @@
+//! Scoped guards around an acquired activation.
+
+use super::error::SmsSolverServiceError;
+use super::structure::SmsSolverService;
+use super::traits::SmsSolverServiceTrait;
+use crate::errors::RetryableError;
+use crate::providers::drop_cancel::{cancel, spawn_cleanup};
+use crate::providers::traits::Provider;
+use crate::types::{SmsCode, SmsTaskResult};
+use std::fmt::{Debug, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Guarded activation and whether it still has to be cancelled on drop.
+struct Guarded {
+    task: SmsTaskResult,
+    done: AtomicBool,
+}
+
+impl Guarded {
+    fn new(task: SmsTaskResult) -> Self {
+        Self {
+            task,
+            done: AtomicBool::new(false),
+        }
+    }
+
+    fn disarm(&self) {
+        self.done.store(true, Ordering::SeqCst);
+    }
+
+    async fn wait_for_code<P>(
+        &self,
+        service: &SmsSolverService<P>,
+    ) -> Result<SmsCode, SmsSolverServiceError>
+    where
+        P: Provider + 'static,
+        P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+    {
+        let result = service.wait_for_sms_code(&self.task.task_id).await;
+        if result
+            .as_ref()
+            .is_err_and(SmsSolverServiceError::activation_released)
+        {
+            self.disarm();
+        }
+        result
+    }
+
+    async fn finish<P>(&self, service: &SmsSolverService<P>) -> Result<(), SmsSolverServiceError>
+    where
+        P: Provider + 'static,
+        P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+    {
+        service.finish_activation(&self.task.task_id).await?;
+        self.disarm();
+        Ok(())
+    }
+
+    async fn cancel<P>(&self, service: &SmsSolverService<P>) -> Result<(), SmsSolverServiceError>
+    where
+        P: Provider + 'static,
+        P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+    {
+        service.cancel_activation(&self.task.task_id).await?;
+        self.disarm();
+        Ok(())
+    }
+
+    /// Cancel the activation on a background task unless it was disarmed.
+    fn cancel_on_drop<P: Provider + 'static>(&self, service: &SmsSolverService<P>) {
+        if self.done.load(Ordering::SeqCst) {
+            return;
+        }
+        let provider = service.provider().clone();
+        let task_id = self.task.task_id.clone();
+        spawn_cleanup(async move { cancel(&provider, &task_id).await });
+    }
+}
+
+/// An activation borrowed from a [`SmsSolverService`], created with
+/// [`SmsSolverService::guard`] or [`SmsSolverService::acquire_for_pipeline`].
+///
+/// Unless [`finish`](Self::finish), [`cancel`](Self::cancel) or
+/// [`defuse`](Self::defuse) is called, the activation is cancelled on a
+/// background task when the guard is dropped. This requires a Tokio runtime
+/// at drop time.
+pub struct ActivationGuard<'a, P: Provider + 'static> {
+    service: &'a SmsSolverService<P>,
+    guarded: Guarded,
+}
+
+impl<'a, P> ActivationGuard<'a, P>
+where
+    P: Provider + 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    pub(crate) fn new(service: &'a SmsSolverService<P>, task: SmsTaskResult) -> Self {
+        Self {
+            service,
+            guarded: Guarded::new(task),
+        }
+    }
+
+    /// The acquired activation.
+    pub fn task(&self) -> &SmsTaskResult {
+        &self.guarded.task
+    }
+
+    /// Wait for the SMS code of this activation.
+    ///
+    /// If polling times out, is cancelled or fails with a provider error,
+    /// the service has already cancelled the activation and the guard will
+    /// not cancel it again.
+    pub async fn wait_for_code(&self) -> Result<SmsCode, SmsSolverServiceError> {
+        self.guarded.wait_for_code(self.service).await
+    }
+
+    /// Mark the activation as successfully completed.
+    pub async fn finish(&self) -> Result<(), SmsSolverServiceError> {
+        self.guarded.finish(self.service).await
+    }
+
+    /// Cancel the activation now instead of on drop.
+    pub async fn cancel(&self) -> Result<(), SmsSolverServiceError> {
+        self.guarded.cancel(self.service).await
+    }
+
+    /// Keep the activation alive when the guard is dropped.
+    ///
+    /// Use this when the activation was finished or handed off elsewhere.
+    pub fn defuse(&self) {
+        self.guarded.disarm();
+    }
+}
+
+impl<P: Provider + 'static> Drop for ActivationGuard<'_, P> {
+    fn drop(&mut self) {
+        self.guarded.cancel_on_drop(self.service);
+    }
+}
+
+/// An activation together with the [`SmsSolverService`] it came from,
+/// created with [`SmsSolverService::into_guard`].
+///
+/// Behaves like [`ActivationGuard`] but owns the service, so it can be moved
+/// into spawned tasks or returned from functions.
+pub struct OwnedActivationGuard<P: Provider + 'static> {
+    service: SmsSolverService<P>,
+    guarded: Guarded,
+}
+
+impl<P> OwnedActivationGuard<P>
+where
+    P: Provider + 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    pub(crate) fn new(service: SmsSolverService<P>, task: SmsTaskResult) -> Self {
+        Self {
+            service,
+            guarded: Guarded::new(task),
+        }
+    }
+
+    /// The acquired activation.
+    pub fn task(&self) -> &SmsTaskResult {
+        &self.guarded.task
+    }
+
+    /// The service the activation came from.
+    pub fn service(&self) -> &SmsSolverService<P> {
+        &self.service
+    }
+
+    /// Wait for the SMS code of this activation.
+    ///
+    /// If polling times out, is cancelled or fails with a provider error,
+    /// the service has already cancelled the activation and the guard will
+    /// not cancel it again.
+    pub async fn wait_for_code(&self) -> Result<SmsCode, SmsSolverServiceError> {
+        self.guarded.wait_for_code(&self.service).await
+    }
+
+    /// Mark the activation as successfully completed.
+    pub async fn finish(&self) -> Result<(), SmsSolverServiceError> {
+        self.guarded.finish(&self.service).await
+    }
+
+    /// Cancel the activation now instead of on drop.
+    pub async fn cancel(&self) -> Result<(), SmsSolverServiceError> {
+        self.guarded.cancel(&self.service).await
+    }
+
+    /// Keep the activation alive when the guard is dropped.
+    ///
+    /// Use this when the activation was finished or handed off elsewhere.
+    pub fn defuse(&self) {
+        self.guarded.disarm();
+    }
+}
+
+impl<P: Provider + 'static> Drop for OwnedActivationGuard<P> {
+    fn drop(&mut self) {
+        self.guarded.cancel_on_drop(&self.service);
+    }
+}
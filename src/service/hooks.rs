@@ -0,0 +1,441 @@
+//! Hooks around `get_number` for middleware-style extension.
+
+use super::error::SmsSolverServiceError;
+use super::structure::{SmsSolverService, get_number_from_any};
+use super::traits::SmsSolverServiceTrait;
+use crate::errors::RetryableError;
+use crate::providers::traits::Provider;
+use crate::types::{CountryAvailability, SmsCode, SmsTaskResult, TaskId};
+use keshvar::Country;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "streams")]
+use futures::Stream;
+
+#[cfg(feature = "tracing")]
+use tracing::warn;
+
+/// Hook invoked before a number is requested from the provider.
+pub type PreGetHook<S> = Arc<dyn Fn(&Country, &S) -> Result<(), HookError> + Send + Sync>;
+
+/// Hook invoked after a number has been acquired.
+pub type PostGetHook = Arc<dyn Fn(&SmsTaskResult) -> Result<(), HookError> + Send + Sync>;
+
+/// Error returned by a hook to reject a `get_number` call.
+#[derive(Debug, Clone, Error)]
+#[error("{message}")]
+pub struct HookError {
+    message: String,
+}
+
+impl HookError {
+    /// Create a hook error with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// The rejection message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// [`SmsSolverService`] with hooks around `get_number`.
+///
+/// Pre-get hooks run in order before the provider is called; the first
+/// error aborts the call without contacting the provider. Post-get hooks run
+/// in order after a number is acquired; the first error cancels the
+/// activation. Either way the call fails with
+/// [`SmsSolverServiceError::HookRejected`].
+///
+/// Created via [`SmsSolverService::with_pre_get_hook`] or
+/// [`SmsSolverService::with_post_get_hook`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::HookError;
+///
+/// let service = SmsSolverService::with_provider(provider)
+///     .with_pre_get_hook(|country, _service| {
+///         if breaker.is_open() {
+///             return Err(HookError::new("circuit open"));
+///         }
+///         Ok(())
+///     })
+///     .with_post_get_hook(|result| {
+///         println!("Acquired {} for {}", result.full_number, result.task_id);
+///         Ok(())
+///     });
+/// ```
+#[derive(Clone)]
+pub struct HookedSmsSolverService<P: Provider> {
+    service: SmsSolverService<P>,
+    pre_get_hooks: Vec<PreGetHook<P::Service>>,
+    post_get_hooks: Vec<PostGetHook>,
+}
+
+impl<P: Provider + Debug> Debug for HookedSmsSolverService<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HookedSmsSolverService")
+            .field("service", &self.service)
+            .field("pre_get_hooks", &self.pre_get_hooks.len())
+            .field("post_get_hooks", &self.post_get_hooks.len())
+            .finish()
+    }
+}
+
+impl<P: Provider> HookedSmsSolverService<P>
+where
+    P::Error: Debug + Display + RetryableError,
+{
+    /// Wrap a service without any hooks.
+    pub fn new(service: SmsSolverService<P>) -> Self {
+        Self {
+            service,
+            pre_get_hooks: Vec::new(),
+            post_get_hooks: Vec::new(),
+        }
+    }
+
+    /// Add a hook that runs before each number request.
+    pub fn with_pre_get_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Country, &P::Service) -> Result<(), HookError> + Send + Sync + 'static,
+    {
+        self.pre_get_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Add a hook that runs after each acquired number.
+    pub fn with_post_get_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SmsTaskResult) -> Result<(), HookError> + Send + Sync + 'static,
+    {
+        self.post_get_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Get reference to the wrapped service.
+    pub fn inner(&self) -> &SmsSolverService<P> {
+        &self.service
+    }
+
+    /// Unwrap the service, dropping all hooks.
+    pub fn into_inner(self) -> SmsSolverService<P> {
+        self.service
+    }
+}
+
+impl<P: Provider> SmsSolverService<P>
+where
+    P::Error: Debug + Display + RetryableError,
+{
+    /// Add a hook that runs before each number request.
+    ///
+    /// See [`HookedSmsSolverService`].
+    pub fn with_pre_get_hook<F>(self, hook: F) -> HookedSmsSolverService<P>
+    where
+        F: Fn(&Country, &P::Service) -> Result<(), HookError> + Send + Sync + 'static,
+    {
+        HookedSmsSolverService::new(self).with_pre_get_hook(hook)
+    }
+
+    /// Add a hook that runs after each acquired number.
+    ///
+    /// See [`HookedSmsSolverService`].
+    pub fn with_post_get_hook<F>(self, hook: F) -> HookedSmsSolverService<P>
+    where
+        F: Fn(&SmsTaskResult) -> Result<(), HookError> + Send + Sync + 'static,
+    {
+        HookedSmsSolverService::new(self).with_post_get_hook(hook)
+    }
+}
+
+impl<P: Provider> HookedSmsSolverService<P>
+where
+    P: 'static,
+    P::Service: 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    /// Run the hooks around acquiring a number, passing the idempotency key
+    /// to the inner service if given.
+    async fn get_number_hooked(
+        &self,
+        country: Country,
+        service: P::Service,
+        idempotency_key: Option<&str>,
+    ) -> Result<SmsTaskResult, SmsSolverServiceError> {
+        for hook in &self.pre_get_hooks {
+            hook(&country, &service).map_err(|source| SmsSolverServiceError::HookRejected {
+                source,
+                task_id: None,
+            })?;
+        }
+
+        let result = match idempotency_key {
+            Some(key) => {
+                self.service
+                    .get_number_with_idempotency_key(country, service, key)
+                    .await?
+            }
+            None => self.service.get_number(country, service).await?,
+        };
+
+        for hook in &self.post_get_hooks {
+            if let Err(source) = hook(&result) {
+                #[cfg(feature = "tracing")]
+                warn!(
+                    task_id = %result.task_id,
+                    error = %source,
+                    "Post-get hook rejected number, cancelling activation"
+                );
+
+                if let Err(e) = self
+                    .service
+                    .provider()
+                    .cancel_activation(&result.task_id)
+                    .await
+                {
+                    return Err(SmsSolverServiceError::CancelFailed {
+                        task_id: result.task_id,
+                        message: e.to_string(),
+                    });
+                }
+
+                return Err(SmsSolverServiceError::HookRejected {
+                    source,
+                    task_id: Some(result.task_id),
+                });
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl<P: Provider> SmsSolverServiceTrait for HookedSmsSolverService<P>
+where
+    P: 'static,
+    P::Service: 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    type Error = SmsSolverServiceError;
+    type Service = P::Service;
+
+    async fn get_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        self.get_number_hooked(country, service, None).await
+    }
+
+    async fn get_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        self.get_number_hooked(country, service, Some(idempotency_key))
+            .await
+    }
+
+    /// Races the requests like [`SmsSolverService`], running the hooks for
+    /// each.
+    async fn get_number_from_any(
+        &self,
+        countries: Vec<(Country, Self::Service)>,
+        cancel_on_first: bool,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        get_number_from_any(
+            self,
+            countries,
+            cancel_on_first,
+            self.service.config().max_concurrent,
+        )
+        .await
+    }
+
+    async fn wait_for_sms_code(&self, task_id: &TaskId) -> Result<SmsCode, Self::Error> {
+        self.service.wait_for_sms_code(task_id).await
+    }
+
+    async fn wait_for_sms_code_cancellable(
+        &self,
+        task_id: &TaskId,
+        cancel_token: CancellationToken,
+    ) -> Result<SmsCode, Self::Error> {
+        self.service
+            .wait_for_sms_code_cancellable(task_id, cancel_token)
+            .await
+    }
+
+    async fn wait_for_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<(SmsCode, String), Self::Error> {
+        self.service.wait_for_sms_code_with_text(task_id).await
+    }
+
+    #[cfg(feature = "streams")]
+    fn wait_for_sms_code_stream<'a>(
+        &'a self,
+        task_id: &'a TaskId,
+    ) -> impl Stream<Item = Result<Option<SmsCode>, Self::Error>> + Send + 'a {
+        self.service.wait_for_sms_code_stream(task_id)
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.service.finish_activation(task_id).await
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.service.cancel_activation(task_id).await
+    }
+
+    async fn request_another_sms_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.service.request_another_sms_code(task_id).await
+    }
+
+    async fn get_available_countries(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.service.get_available_countries(service).await
+    }
+
+    fn is_activation_released(&self, error: &Self::Error) -> bool {
+        error.activation_released()
+    }
+
+    fn auto_finish(&self) -> bool {
+        self.service.auto_finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockError, MockProvider, MockService};
+    use keshvar::Alpha2;
+    use std::sync::Mutex;
+
+    fn mock() -> MockProvider {
+        MockProvider::builder()
+            .number("task123", "380501234567")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_in_order() {
+        let provider = mock();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (o1, o2, o3) = (order.clone(), order.clone(), order.clone());
+
+        let service = SmsSolverService::with_provider(provider.clone())
+            .with_pre_get_hook(move |_, _| {
+                o1.lock().unwrap().push("pre1");
+                Ok(())
+            })
+            .with_pre_get_hook(move |_, _| {
+                o2.lock().unwrap().push("pre2");
+                Ok(())
+            })
+            .with_post_get_hook(move |result| {
+                assert_eq!(result.task_id.as_ref(), "task123");
+                o3.lock().unwrap().push("post");
+                Ok(())
+            });
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(result.number.as_str(), "501234567");
+        assert_eq!(*order.lock().unwrap(), vec!["pre1", "pre2", "post"]);
+        assert!(provider.cancelled_tasks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pre_get_hook_error_skips_provider() {
+        let provider = mock();
+        let service = SmsSolverService::with_provider(provider.clone())
+            .with_pre_get_hook(|_, _| Err(HookError::new("circuit open")));
+
+        let err = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::HookRejected { task_id: None, .. }
+        ));
+        assert!(!err.is_retryable());
+        assert_eq!(provider.call_count_get_number(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_post_get_hook_error_cancels_activation() {
+        let provider = mock();
+        let service = SmsSolverService::with_provider(provider.clone())
+            .with_post_get_hook(|_| Err(HookError::new("budget exceeded")));
+
+        let err = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+        match err {
+            SmsSolverServiceError::HookRejected { source, task_id } => {
+                assert_eq!(source.message(), "budget exceeded");
+                assert_eq!(task_id, Some(TaskId::from("task123")));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert_eq!(provider.cancelled_tasks(), vec![TaskId::from("task123")]);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_from_any_runs_hooks_for_each_request() {
+        let provider = MockProvider::builder()
+            .get_number_response(Err(MockError::Permanent("No numbers".into())))
+            .build();
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        let service =
+            SmsSolverService::with_provider(provider.clone()).with_pre_get_hook(move |_, _| {
+                *counter.lock().unwrap() += 1;
+                Ok(())
+            });
+
+        let err = service
+            .get_number_from_any(
+                vec![
+                    (Alpha2::UA.to_country(), MockService),
+                    (Alpha2::GB.to_country(), MockService),
+                ],
+                true,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::AllCountriesFailed { attempted: 2, .. }
+        ));
+        assert_eq!(*calls.lock().unwrap(), 2);
+
+        let err = service
+            .get_number_from_any(Vec::new(), true)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::AllCountriesFailed { attempted: 0, .. }
+        ));
+    }
+}
@@ -2,10 +2,18 @@
 
 pub(crate) mod config;
 pub(crate) mod error;
+pub(crate) mod guard;
+pub(crate) mod hooks;
+pub(crate) mod statistics;
 pub(crate) mod structure;
 pub(crate) mod traits;
 
-pub use config::{ConfigError, SmsSolverServiceConfig, SmsSolverServiceConfigBuilder};
-pub use error::SmsSolverServiceError;
-pub use structure::{SmsSolverService, SmsSolverServiceBuilder};
+pub use config::{
+    ConfigError, SmsSolverServiceConfig, SmsSolverServiceConfigBuilder, SmsSolverServiceConfigPatch,
+};
+pub use error::{SmsSolverServiceBuildError, SmsSolverServiceError};
+pub use guard::{ActivationGuard, OwnedActivationGuard};
+pub use hooks::{HookError, HookedSmsSolverService};
+pub use statistics::ServiceStatistics;
+pub use structure::{CancelResult, PollProgress, SmsSolverService, SmsSolverServiceBuilder};
 pub use traits::SmsSolverServiceTrait;
@@ -0,0 +1,33 @@
+//! Session statistics for the SMS service.
+
+use serde::{Deserialize, Serialize};
+
+/// Aggregate of operation outcomes recorded by an [`SmsSolverService`].
+///
+/// Counters start at zero when the service is created and can be reset with
+/// [`SmsSolverService::reset_statistics`].
+///
+/// [`SmsSolverService`]: crate::SmsSolverService
+/// [`SmsSolverService::reset_statistics`]: crate::SmsSolverService::reset_statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStatistics {
+    /// Phone numbers successfully acquired.
+    pub numbers_acquired: u32,
+    /// SMS codes successfully received.
+    pub codes_received: u32,
+    /// Waits that ended in a timeout.
+    pub timeouts: u32,
+    /// Waits that were cancelled.
+    pub cancellations: u32,
+    /// Waits that ended in a permanent provider error.
+    pub errors: u32,
+    /// Sum of reported activation costs, if any provider reported one.
+    pub total_spend: Option<f64>,
+}
+
+impl ServiceStatistics {
+    /// Add an activation cost to the running total.
+    pub(crate) fn add_spend(&mut self, cost: f64) {
+        *self.total_spend.get_or_insert(0.0) += cost;
+    }
+}
@@ -1,17 +1,32 @@
 //! Main service implementation.
 
 use super::config::{SmsSolverServiceConfig, SmsSolverServiceConfigBuilder};
-use super::error::SmsSolverServiceError;
+use super::error::{SmsSolverServiceBuildError, SmsSolverServiceError};
+use super::guard::{ActivationGuard, OwnedActivationGuard};
+use super::statistics::ServiceStatistics;
 use super::traits::SmsSolverServiceTrait;
 use crate::errors::RetryableError;
+use crate::providers::drop_cancel::{cancel, spawn_cleanup};
 use crate::providers::traits::Provider;
-use crate::types::{Number, SmsCode, SmsTaskResult, TaskId};
-use keshvar::Country;
+use crate::types::{
+    ActivationMetadata, ActivationState, CountryAvailability, FullNumber, HealthReport, Number,
+    PriceEntry, ReputationLevel, SmsCode, SmsTaskResult, TaskId,
+};
+use crate::utils::is_sanctioned_country;
+use futures::future::select_all;
+use keshvar::{Alpha2, Country};
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt::{Debug, Display};
-use std::time::Instant;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
+#[cfg(feature = "streams")]
+use futures::Stream;
+
 #[cfg(feature = "tracing")]
 use tracing::{debug, error, info, warn};
 
@@ -25,6 +40,12 @@ use crate::DialCode;
 #[cfg(feature = "metrics")]
 use std::sync::OnceLock;
 
+/// Deadline applied by `get_number` to the provider call.
+const DEFAULT_ACQUISITION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of empty polls between checks of the activation status.
+const STATUS_CHECK_POLLS: u32 = 5;
+
 /// Metrics for the SMS Solver service.
 #[cfg(feature = "metrics")]
 struct ServiceMetrics {
@@ -84,6 +105,98 @@ impl ServiceMetrics {
     }
 }
 
+/// Outcome of cancelling one activation via [`SmsSolverService::cancel_all`].
+#[derive(Debug)]
+pub struct CancelResult {
+    /// The activation that was cancelled.
+    pub task_id: TaskId,
+    /// Result of the cancellation.
+    pub result: Result<(), SmsSolverServiceError>,
+}
+
+/// Progress of SMS polling, passed to the callback of
+/// [`SmsSolverService::wait_for_sms_code_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollProgress {
+    /// Number of polls made so far, starting at 1.
+    pub attempt: u32,
+    /// Time spent polling so far.
+    pub elapsed: Duration,
+    /// Time left until the timeout, if polling is time-limited.
+    pub remaining: Option<Duration>,
+}
+
+/// Outcome of a deduplicated `get_number` call, shared with waiting callers.
+type SharedTaskResult = Result<SmsTaskResult, Arc<SmsSolverServiceError>>;
+
+/// Activation details used to pick the polling timeout and attached to
+/// polling log events, when known.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+struct PollContext {
+    /// ISO alpha-2 code of the activation's country.
+    country: Option<String>,
+    /// Provider service name.
+    service: Option<String>,
+}
+
+/// State of one run of SMS polling, shared by the poll loop and the stream.
+struct PollRun {
+    /// Time allowed for the whole run.
+    timeout: Duration,
+    /// Interval to wait after the next empty poll.
+    poll_interval: Duration,
+    /// Time to wait before the next poll.
+    wait: Duration,
+    start: Instant,
+    poll_count: u32,
+    /// Distinct codes rejected by the validator.
+    codes_seen: u32,
+    last_rejected: Option<SmsCode>,
+}
+
+/// How long the context of an acquired activation is kept if it is never
+/// finished, cancelled or polled to a failure.
+const TASK_CONTEXT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Context of the activations acquired through the service, keyed by task
+/// ID, along with when it was stored.
+type TaskContexts = Arc<Mutex<HashMap<TaskId, (PollContext, Instant)>>>;
+
+/// In-flight `get_number_idempotent` calls, keyed by caller-supplied key.
+type InFlightRequests = Arc<Mutex<HashMap<String, Vec<oneshot::Sender<SharedTaskResult>>>>>;
+
+/// Removes an in-flight entry when the leading call finishes or is dropped.
+struct InFlightGuard<'a> {
+    in_flight: &'a InFlightRequests,
+    key: &'a str,
+    done: bool,
+}
+
+impl InFlightGuard<'_> {
+    /// Remove the entry and return the callers waiting on it.
+    fn finish(mut self) -> Vec<oneshot::Sender<SharedTaskResult>> {
+        self.done = true;
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(self.key)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            // Dropping the senders wakes waiters so one of them can take over.
+            self.in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(self.key);
+        }
+    }
+}
+
 /// Generic SMS service that works with any Provider implementation.
 ///
 /// This service handles high-level SMS operations like:
@@ -118,10 +231,15 @@ impl ServiceMetrics {
 /// let code = service.wait_for_sms_code(&result.task_id).await?;
 /// println!("Got code: {}", code);
 /// ```
+///
+/// Clones share the same [`ServiceStatistics`].
 #[derive(Debug, Clone)]
 pub struct SmsSolverService<P: Provider> {
     provider: P,
     config: SmsSolverServiceConfig,
+    stats: Arc<Mutex<ServiceStatistics>>,
+    in_flight: InFlightRequests,
+    contexts: TaskContexts,
 }
 
 impl<P: Provider> SmsSolverService<P>
@@ -130,7 +248,24 @@ where
 {
     /// Create a new SMS service with a custom provider and configuration.
     pub fn new(provider: P, config: SmsSolverServiceConfig) -> Self {
-        Self { provider, config }
+        Self {
+            provider,
+            config,
+            stats: Arc::new(Mutex::new(ServiceStatistics::default())),
+            in_flight: Arc::default(),
+            contexts: Arc::default(),
+        }
+    }
+
+    /// Create a new SMS service after warming up the provider.
+    ///
+    /// Calls [`Provider::warmup`] and returns the service only if it succeeds.
+    pub async fn new_warmed_up(
+        provider: P,
+        config: SmsSolverServiceConfig,
+    ) -> Result<Self, P::Error> {
+        provider.warmup().await?;
+        Ok(Self::new(provider, config))
     }
 
     /// Create a new SMS service with default configuration.
@@ -153,6 +288,33 @@ where
         &mut self.provider
     }
 
+    /// Replace the provider with the result of `f`, keeping the config.
+    ///
+    /// Statistics carry over to the returned service.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use sms_solvers::{SmsRetryableProvider, SmsSolverService};
+    ///
+    /// let service = SmsSolverService::with_provider(provider)
+    ///     .map_provider(SmsRetryableProvider::new);
+    /// ```
+    pub fn map_provider<Q, F>(self, f: F) -> SmsSolverService<Q>
+    where
+        F: FnOnce(P) -> Q,
+        Q: Provider,
+        Q::Error: Debug + Display + RetryableError,
+    {
+        SmsSolverService {
+            provider: f(self.provider),
+            config: self.config,
+            stats: self.stats,
+            in_flight: self.in_flight,
+            contexts: self.contexts,
+        }
+    }
+
     /// Get reference to the service configuration.
     pub fn config(&self) -> &SmsSolverServiceConfig {
         &self.config
@@ -168,6 +330,83 @@ where
         self.config = config;
     }
 
+    /// Get a copy of the outcomes recorded since the service was created or
+    /// last reset.
+    pub fn statistics(&self) -> ServiceStatistics {
+        self.stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Alias of [`statistics`](Self::statistics), which already returns a
+    /// copy that later calls do not change.
+    pub fn statistics_snapshot(&self) -> ServiceStatistics {
+        self.statistics()
+    }
+
+    /// Reset all statistics to zero.
+    pub fn reset_statistics(&self) {
+        self.record(|stats| *stats = ServiceStatistics::default());
+    }
+
+    /// Apply an update to the statistics.
+    fn record(&self, update: impl FnOnce(&mut ServiceStatistics)) {
+        update(&mut self.stats.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Collect health signals of the provider account.
+    pub async fn health_report(&self) -> HealthReport {
+        self.provider.health_report().await
+    }
+
+    /// Returns true if the provider reports a healthy account.
+    pub async fn is_healthy(&self) -> bool {
+        self.health_report().await.is_healthy
+    }
+
+    /// Get the account's reputation level from the provider.
+    ///
+    /// Returns `None` if the provider has no reputation system.
+    pub async fn account_reputation(
+        &self,
+    ) -> Result<Option<ReputationLevel>, SmsSolverServiceError> {
+        self.provider.account_reputation().await.map_err(|e| {
+            let is_retryable = e.is_retryable();
+            let should_retry_operation = e.should_retry_operation();
+            SmsSolverServiceError::Provider {
+                source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                is_retryable,
+                should_retry_operation,
+            }
+        })
+    }
+
+    /// Cancel several activations at once, e.g. on shutdown.
+    ///
+    /// Returns one [`CancelResult`] per task ID, in the same order. Failures
+    /// are reported as [`SmsSolverServiceError::CancelFailed`].
+    pub async fn cancel_all(&self, task_ids: &[TaskId]) -> Vec<CancelResult> {
+        self.provider
+            .cancel_all_pending(task_ids)
+            .await
+            .into_iter()
+            .zip(task_ids)
+            .map(|(result, task_id)| {
+                if result.is_ok() {
+                    self.forget_task(task_id);
+                }
+                CancelResult {
+                    task_id: task_id.clone(),
+                    result: result.map_err(|e| SmsSolverServiceError::CancelFailed {
+                        task_id: task_id.clone(),
+                        message: e.to_string(),
+                    }),
+                }
+            })
+            .collect()
+    }
+
     /// Filter dial codes to only include those supported by the provider.
     ///
     /// This method filters out blacklisted dial codes using the provider's
@@ -234,646 +473,3181 @@ where
             .cloned()
             .ok_or(SmsSolverServiceError::NoAvailableDialCodes)
     }
-}
-
-impl<P: Provider> SmsSolverServiceTrait for SmsSolverService<P>
-where
-    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
-{
-    type Error = SmsSolverServiceError;
-    type Service = P::Service;
 
+    /// Wait for an SMS code that satisfies `validator`.
+    ///
+    /// Codes rejected by the validator (e.g. a marketing SMS instead of an
+    /// OTP) are ignored and polling continues until a valid code arrives or
+    /// the timeout is reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SmsSolverServiceError::NoValidCodeReceived` if only invalid
+    /// codes were received before the timeout.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let code = service
+    ///     .wait_for_sms_code_with_validator(&task_id, |code| {
+    ///         code.as_str().len() == 6 && code.as_str().chars().all(|c| c.is_ascii_digit())
+    ///     })
+    ///     .await?;
+    /// ```
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
-            name = "SmsSolverService::get_number",
+            name = "SmsSolverService::wait_for_sms_code_with_validator",
             skip_all,
-            fields(country = %country.iso_short_name())
+            fields(task_id = %task_id)
         )
     )]
-    async fn get_number(
+    pub async fn wait_for_sms_code_with_validator<F>(
         &self,
-        country: Country,
-        service: Self::Service,
-    ) -> Result<SmsTaskResult, Self::Error> {
-        #[cfg(feature = "tracing")]
-        debug!("Requesting phone number");
-
-        #[cfg(feature = "metrics")]
-        let country_alpha2 = country.alpha2().to_string();
-
-        #[cfg(feature = "metrics")]
-        ServiceMetrics::global()
-            .numbers_requested
-            .add(1, &[KeyValue::new("country", country_alpha2.clone())]);
-
-        let (task_id, full_number) = self
-            .provider
-            .get_phone_number(country.clone(), service)
+        task_id: &TaskId,
+        validator: F,
+    ) -> Result<SmsCode, SmsSolverServiceError>
+    where
+        F: Fn(&SmsCode) -> bool + Send + Sync,
+    {
+        self.poll_for_sms_code(task_id, CancellationToken::new(), &validator, &|_| {})
             .await
-            .map_err(|e| {
-                #[cfg(feature = "metrics")]
-                ServiceMetrics::global().errors.add(
-                    1,
-                    &[
-                        KeyValue::new("country", country_alpha2.clone()),
-                        KeyValue::new("operation", "get_number"),
-                    ],
-                );
-                let is_retryable = e.is_retryable();
-                let should_retry_operation = e.should_retry_operation();
-                SmsSolverServiceError::Provider {
-                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
-                    is_retryable,
-                    should_retry_operation,
-                }
-            })?;
-
-        let dial_code = DialCode::from(&country);
-
-        // Check if the dial code is blacklisted
-        if !self.provider.is_dial_code_supported(&dial_code) {
-            #[cfg(feature = "tracing")]
-            warn!(
-                task_id = %task_id,
-                dial_code = %dial_code,
-                "Dial code is blacklisted, cancelling activation"
-            );
-
-            // Cancel the activation since we won't use this number
-            if let Err(e) = self.provider.cancel_activation(&task_id).await {
-                #[cfg(feature = "tracing")]
-                warn!(error = %e, "Failed to cancel activation for blacklisted number");
-            }
-
-            return Err(SmsSolverServiceError::DialCodeBlacklisted { dial_code, task_id });
-        }
-
-        let number = Number::from_full_number(&full_number, &dial_code).map_err(|e| {
-            SmsSolverServiceError::NumberParse {
-                full_number: full_number.to_string(),
-                message: e.to_string(),
-            }
-        })?;
-
-        #[cfg(feature = "tracing")]
-        info!(
-            task_id = %task_id,
-            dial_code = %dial_code,
-            country = %country.iso_short_name(),
-            "Phone number acquired"
-        );
-
-        Ok(SmsTaskResult {
-            task_id,
-            dial_code,
-            number,
-            full_number,
-            country,
-        })
+            .map(|(code, _)| code)
     }
 
+    /// Wait for an SMS code, calling `on_poll` after each poll that found no SMS.
+    ///
+    /// The callback runs synchronously inside the polling loop, so it should
+    /// return quickly.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let code = service
+    ///     .wait_for_sms_code_with_progress(&task_id, |progress| {
+    ///         println!("Poll {} ({:?} elapsed)", progress.attempt, progress.elapsed);
+    ///     })
+    ///     .await?;
+    /// ```
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
-            name = "SmsSolverService::wait_for_sms_code",
+            name = "SmsSolverService::wait_for_sms_code_with_progress",
             skip_all,
             fields(task_id = %task_id)
         )
     )]
-    async fn wait_for_sms_code(&self, task_id: &TaskId) -> Result<SmsCode, Self::Error> {
-        self.wait_for_sms_code_cancellable(task_id, CancellationToken::new())
+    pub async fn wait_for_sms_code_with_progress<F>(
+        &self,
+        task_id: &TaskId,
+        on_poll: F,
+    ) -> Result<SmsCode, SmsSolverServiceError>
+    where
+        F: Fn(PollProgress) + Send + Sync,
+    {
+        self.poll_for_sms_code(task_id, CancellationToken::new(), &|_| true, &on_poll)
             .await
+            .map(|(code, _)| code)
     }
 
+    /// Wait until any of several activations receives an SMS code.
+    ///
+    /// Each poll checks every pending activation with one
+    /// [`Provider::get_sms_codes_batch`] call and returns the first
+    /// activation that has a code, along with the code; the others are left
+    /// running. Activations whose poll fails with a permanent error are
+    /// cancelled and dropped, and once none are left the last such error is
+    /// returned. If the configured timeout is reached first, the remaining
+    /// activations are cancelled and [`SmsSolverServiceError::SmsTimeout`] is
+    /// returned for the first of them.
+    ///
+    /// Returns [`SmsSolverServiceError::InvalidArgument`] if `task_ids` is
+    /// empty.
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(
-            name = "SmsSolverService::wait_for_sms_code_cancellable",
+            name = "SmsSolverService::wait_for_any_sms_code_batch",
             skip_all,
-            fields(task_id = %task_id)
+            fields(tasks = task_ids.len())
         )
     )]
-    async fn wait_for_sms_code_cancellable(
+    pub async fn wait_for_any_sms_code_batch(
         &self,
-        task_id: &TaskId,
-        cancel_token: CancellationToken,
-    ) -> Result<SmsCode, Self::Error> {
+        task_ids: &[TaskId],
+    ) -> Result<(TaskId, SmsCode), SmsSolverServiceError> {
+        if task_ids.is_empty() {
+            return Err(SmsSolverServiceError::InvalidArgument {
+                reason: "task_ids must not be empty",
+            });
+        }
+
         let timeout = self.config.timeout;
-        let poll_interval = self.config.poll_interval;
         let start = Instant::now();
-        let mut poll_count: u32 = 0;
-
-        #[cfg(feature = "tracing")]
-        debug!(timeout_secs = %timeout.as_secs_f64(), "Starting SMS code polling");
+        let mut pending = task_ids.to_vec();
+        let mut poll_interval = self.config.poll_interval;
+        let mut poll_count = 0;
 
         loop {
-            // Check for cancellation
-            if cancel_token.is_cancelled() {
-                let elapsed = start.elapsed();
-
-                #[cfg(feature = "tracing")]
-                info!(
-                    elapsed_secs = %elapsed.as_secs_f64(),
-                    poll_count = %poll_count,
-                    "Cancellation requested, cancelling activation"
-                );
-
-                #[cfg(feature = "metrics")]
-                {
-                    ServiceMetrics::global().cancellations.add(1, &[]);
-                    ServiceMetrics::global().sms_wait_time.record(
-                        elapsed.as_secs_f64(),
-                        &[KeyValue::new("outcome", "cancelled")],
-                    );
-                    ServiceMetrics::global()
-                        .poll_counts
-                        .record(poll_count as u64, &[KeyValue::new("outcome", "cancelled")]);
-                }
-
-                // Try to cancel the activation
-                if let Err(e) = self.provider.cancel_activation(task_id).await {
-                    #[cfg(feature = "tracing")]
-                    warn!(error = %e, "Failed to cancel activation after cancellation request");
-
-                    return Err(SmsSolverServiceError::CancelFailed {
-                        task_id: task_id.clone(),
-                        message: e.to_string(),
-                    });
-                }
-
-                return Err(SmsSolverServiceError::Cancelled {
-                    elapsed,
-                    poll_count,
-                    task_id: task_id.clone(),
-                });
-            }
-
-            // Check for timeout
             let elapsed = start.elapsed();
             if elapsed >= timeout {
                 #[cfg(feature = "tracing")]
-                warn!(
-                    timeout_secs = %timeout.as_secs_f64(),
-                    elapsed_secs = %elapsed.as_secs_f64(),
-                    poll_count = %poll_count,
-                    "Timeout reached, cancelling activation"
+                error!(
+                    reason = "timeout",
+                    pending = pending.len(),
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    poll_count,
+                    "Timeout reached, cancelling activations"
                 );
 
-                #[cfg(feature = "metrics")]
-                {
-                    ServiceMetrics::global().timeouts.add(1, &[]);
-                    ServiceMetrics::global().sms_wait_time.record(
-                        elapsed.as_secs_f64(),
-                        &[KeyValue::new("outcome", "timeout")],
-                    );
-                    ServiceMetrics::global()
-                        .poll_counts
-                        .record(poll_count as u64, &[KeyValue::new("outcome", "timeout")]);
-                }
-
-                // Try to cancel the activation
-                if let Err(e) = self.provider.cancel_activation(task_id).await {
-                    #[cfg(feature = "tracing")]
-                    warn!(error = %e, "Failed to cancel activation after timeout");
+                self.record(|stats| stats.timeouts += 1);
 
-                    return Err(SmsSolverServiceError::CancelFailed {
-                        task_id: task_id.clone(),
-                        message: e.to_string(),
-                    });
-                }
+                #[cfg(feature = "metrics")]
+                ServiceMetrics::global().timeouts.add(1, &[]);
 
+                self.cancel_dropped(&pending).await;
                 return Err(SmsSolverServiceError::SmsTimeout {
                     timeout,
                     elapsed,
                     poll_count,
-                    task_id: task_id.clone(),
+                    task_id: pending.swap_remove(0),
                 });
             }
 
             poll_count += 1;
-
-            match self.provider.get_sms_code(task_id).await {
-                Ok(Some(code)) => {
-                    let elapsed = start.elapsed();
-
-                    #[cfg(feature = "tracing")]
-                    info!(
-                        code = %code,
-                        elapsed_secs = %elapsed.as_secs_f64(),
-                        poll_count = %poll_count,
-                        "SMS code received"
-                    );
-
-                    #[cfg(feature = "metrics")]
-                    {
-                        ServiceMetrics::global().sms_codes_received.add(1, &[]);
-                        ServiceMetrics::global().sms_wait_time.record(
-                            elapsed.as_secs_f64(),
-                            &[KeyValue::new("outcome", "success")],
-                        );
-                        ServiceMetrics::global()
-                            .poll_counts
-                            .record(poll_count as u64, &[KeyValue::new("outcome", "success")]);
+            let results = self.provider.get_sms_codes_batch(&pending).await;
+
+            let mut received = None;
+            let mut failed = Vec::new();
+            let mut last_error = None;
+            for (task_id, result) in pending.iter().zip(results) {
+                match result {
+                    Ok(Some(code)) => {
+                        received.get_or_insert_with(|| (task_id.clone(), code));
                     }
+                    Ok(None) => {}
+                    Err(e) if !e.is_retryable() => {
+                        #[cfg(feature = "tracing")]
+                        error!(error = %e, task_id = %task_id, poll_count, "Permanent error during polling");
 
-                    return Ok(code);
-                }
-                Ok(None) => {
-                    // SMS not yet received, continue polling
+                        failed.push(task_id.clone());
+                        last_error = Some(e);
+                    }
+                    Err(_e) => {
+                        #[cfg(feature = "tracing")]
+                        warn!(error = %_e, task_id = %task_id, poll_count, "Transient error during polling, continuing");
+                    }
                 }
-                Err(e) if !e.is_retryable() => {
-                    let should_retry_operation = e.should_retry_operation();
-                    let elapsed = start.elapsed();
+            }
 
-                    #[cfg(feature = "tracing")]
-                    error!(
-                        error = %e,
-                        elapsed_secs = %elapsed.as_secs_f64(),
-                        poll_count = %poll_count,
-                        "Permanent error during polling"
-                    );
+            if !failed.is_empty() {
+                self.record(|stats| stats.errors += failed.len() as u32);
+                pending.retain(|task_id| !failed.contains(task_id));
+                self.cancel_dropped(&failed).await;
+            }
 
-                    #[cfg(feature = "metrics")]
+            if let Some((task_id, code)) = received {
+                #[cfg(feature = "tracing")]
+                info!(
+                    code = %code,
+                    task_id = %task_id,
+                    elapsed_ms = start.elapsed().as_millis() as u64,
+                    poll_count,
+                    "SMS code received"
+                );
+
+                self.record(|stats| stats.codes_received += 1);
+
+                #[cfg(feature = "metrics")]
+                ServiceMetrics::global().sms_codes_received.add(1, &[]);
+
+                return Ok((task_id, code));
+            }
+
+            if pending.is_empty()
+                && let Some(e) = last_error
+            {
+                let should_retry_operation = e.should_retry_operation();
+                return Err(SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable: false,
+                    should_retry_operation,
+                });
+            }
+
+            tokio::time::sleep(poll_interval).await;
+            poll_interval = self.config.next_poll_interval(poll_interval);
+        }
+    }
+
+    /// Cancel activations dropped from a batch wait, logging failures.
+    async fn cancel_dropped(&self, task_ids: &[TaskId]) {
+        for _result in self.cancel_all(task_ids).await {
+            #[cfg(feature = "tracing")]
+            if let Err(e) = &_result.result {
+                warn!(error = %e, "Failed to cancel activation");
+            }
+        }
+    }
+
+    /// Wait for an SMS code using the timeout configured for the
+    /// activation's country.
+    ///
+    /// Like [`wait_for_sms_code_cancellable`](SmsSolverServiceTrait::wait_for_sms_code_cancellable),
+    /// but uses [`SmsSolverServiceConfig::timeout_for`] the country of `task`
+    /// instead of the global timeout.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::wait_for_sms_code_for_country",
+            skip_all,
+            fields(task_id = %task.task_id)
+        )
+    )]
+    pub async fn wait_for_sms_code_for_country(
+        &self,
+        task: &SmsTaskResult,
+        cancel_token: CancellationToken,
+    ) -> Result<SmsCode, SmsSolverServiceError> {
+        let mut context = self.task_context(&task.task_id);
+        context.country = Some(task.country.alpha2().to_string());
+        self.remember_task(&task.task_id, context);
+
+        self.poll_for_sms_code(&task.task_id, cancel_token, &|_| true, &|_| {})
+            .await
+            .map(|(code, _)| code)
+    }
+
+    /// Fail fast if the provider reports the activation as gone.
+    ///
+    /// Only a task ID is available here, not the activation's
+    /// `ActivationMetadata::expires_at`, so the provider is asked directly.
+    /// A failed check says nothing about the activation, so polling goes
+    /// ahead and the poll loop handles whatever is actually wrong.
+    async fn ensure_task_alive(&self, task_id: &TaskId) -> Result<(), SmsSolverServiceError> {
+        match self.provider.is_task_alive(task_id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                #[cfg(feature = "tracing")]
+                warn!("Activation is no longer alive, skipping SMS polling");
+
+                Err(SmsSolverServiceError::ActivationExpired {
+                    task_id: task_id.clone(),
+                })
+            }
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                warn!(error = %_e, "Failed to check whether activation is alive, polling anyway");
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Ask the provider whether the activation ended, once every
+    /// [`STATUS_CHECK_POLLS`] empty polls.
+    ///
+    /// Returns the error to stop polling with if a terminal state is
+    /// reported. A failed status request is ignored; polling carries on.
+    async fn check_activation_ended(
+        &self,
+        task_id: &TaskId,
+        poll_count: u32,
+    ) -> Option<SmsSolverServiceError> {
+        if !poll_count.is_multiple_of(STATUS_CHECK_POLLS) {
+            return None;
+        }
+
+        let state = self
+            .provider
+            .get_activation_status(task_id)
+            .await
+            .ok()
+            .flatten()
+            .filter(ActivationState::is_terminal)?;
+
+        #[cfg(feature = "tracing")]
+        warn!(
+            state = ?state,
+            poll_count,
+            "Activation ended before an SMS arrived, stopping polling"
+        );
+
+        Some(match state {
+            ActivationState::Expired => SmsSolverServiceError::ActivationExpired {
+                task_id: task_id.clone(),
+            },
+            state => SmsSolverServiceError::ActivationEnded {
+                task_id: task_id.clone(),
+                state,
+            },
+        })
+    }
+
+    /// Remember the country and service of an activation for polling.
+    fn remember_task(&self, task_id: &TaskId, context: PollContext) {
+        let mut contexts = self.contexts.lock().unwrap_or_else(PoisonError::into_inner);
+        contexts.retain(|_, (_, stored_at)| stored_at.elapsed() < TASK_CONTEXT_TTL);
+        contexts.insert(task_id.clone(), (context, Instant::now()));
+    }
+
+    /// The remembered context of an activation, or an empty one for
+    /// activations not acquired through this service.
+    fn task_context(&self, task_id: &TaskId) -> PollContext {
+        self.contexts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(task_id)
+            .map(|(context, _)| context.clone())
+            .unwrap_or_default()
+    }
+
+    fn forget_task(&self, task_id: &TaskId) {
+        self.contexts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(task_id);
+    }
+
+    /// Poll the provider until a code accepted by `validator` is received,
+    /// the timeout is reached, or cancellation is requested.
+    ///
+    /// Returns the code along with the full SMS text. Codes rejected by the
+    /// validator are ignored and polling continues. The timeout and log
+    /// fields come from the context remembered when the number was acquired.
+    async fn poll_for_sms_code(
+        &self,
+        task_id: &TaskId,
+        cancel_token: CancellationToken,
+        validator: &(dyn Fn(&SmsCode) -> bool + Send + Sync),
+        on_empty_poll: &(dyn Fn(PollProgress) + Send + Sync),
+    ) -> Result<(SmsCode, String), SmsSolverServiceError> {
+        let context = self.task_context(task_id);
+        let result = self
+            .poll_with_context(task_id, cancel_token, validator, on_empty_poll, &context)
+            .await;
+        if result.is_err() {
+            self.forget_task(task_id);
+        }
+        result
+    }
+
+    async fn poll_with_context(
+        &self,
+        task_id: &TaskId,
+        cancel_token: CancellationToken,
+        validator: &(dyn Fn(&SmsCode) -> bool + Send + Sync),
+        on_empty_poll: &(dyn Fn(PollProgress) + Send + Sync),
+        context: &PollContext,
+    ) -> Result<(SmsCode, String), SmsSolverServiceError> {
+        let mut run = self.start_polling(task_id, context).await?;
+
+        loop {
+            if let Some(received) = self
+                .poll_step(
+                    task_id,
+                    &mut run,
+                    &cancel_token,
+                    validator,
+                    on_empty_poll,
+                    context,
+                )
+                .await?
+            {
+                return Ok(received);
+            }
+
+            tokio::time::sleep(run.wait).await;
+        }
+    }
+
+    /// Check that the activation is alive and set up a polling run.
+    async fn start_polling(
+        &self,
+        task_id: &TaskId,
+        context: &PollContext,
+    ) -> Result<PollRun, SmsSolverServiceError> {
+        let timeout = context
+            .country
+            .as_deref()
+            .and_then(|country| Alpha2::try_from(country).ok())
+            .map_or(self.config.timeout, |alpha2| {
+                self.config.timeout_for_alpha2(alpha2)
+            });
+
+        self.ensure_task_alive(task_id).await?;
+
+        #[cfg(feature = "tracing")]
+        debug!(timeout_secs = %timeout.as_secs_f64(), "Starting SMS code polling");
+
+        Ok(PollRun {
+            timeout,
+            poll_interval: self.config.poll_interval,
+            wait: self.config.poll_interval,
+            start: Instant::now(),
+            poll_count: 0,
+            codes_seen: 0,
+            last_rejected: None,
+        })
+    }
+
+    /// Make one poll of a polling run.
+    ///
+    /// Returns the code and SMS text once a code accepted by `validator`
+    /// arrives, or `None` if polling should go on after sleeping
+    /// `run.wait`. Cancellation, the timeout and permanent errors end the
+    /// run with an error, cancelling the activation.
+    async fn poll_step(
+        &self,
+        task_id: &TaskId,
+        run: &mut PollRun,
+        cancel_token: &CancellationToken,
+        validator: &(dyn Fn(&SmsCode) -> bool + Send + Sync),
+        on_empty_poll: &(dyn Fn(PollProgress) + Send + Sync),
+        context: &PollContext,
+    ) -> Result<Option<(SmsCode, String)>, SmsSolverServiceError> {
+        let timeout = run.timeout;
+        let poll_count = run.poll_count;
+
+        #[cfg(feature = "tracing")]
+        let provider_type = self.provider.provider_kind().as_str();
+
+        // Check for cancellation
+        if cancel_token.is_cancelled() {
+            let elapsed = run.start.elapsed();
+
+            #[cfg(feature = "tracing")]
+            error!(
+                reason = "cancelled",
+                task_id = %task_id,
+                provider_type,
+                country = context.country.as_deref(),
+                service = context.service.as_deref(),
+                timeout_ms = timeout.as_millis() as u64,
+                elapsed_ms = elapsed.as_millis() as u64,
+                poll_count,
+                "Cancellation requested, cancelling activation"
+            );
+
+            self.record(|stats| stats.cancellations += 1);
+
+            #[cfg(feature = "metrics")]
+            {
+                ServiceMetrics::global().cancellations.add(1, &[]);
+                ServiceMetrics::global().sms_wait_time.record(
+                    elapsed.as_secs_f64(),
+                    &[KeyValue::new("outcome", "cancelled")],
+                );
+                ServiceMetrics::global()
+                    .poll_counts
+                    .record(poll_count as u64, &[KeyValue::new("outcome", "cancelled")]);
+            }
+
+            // Try to cancel the activation
+            if let Err(e) = self.provider.cancel_activation(task_id).await {
+                #[cfg(feature = "tracing")]
+                warn!(error = %e, "Failed to cancel activation after cancellation request");
+
+                return Err(SmsSolverServiceError::CancelFailed {
+                    task_id: task_id.clone(),
+                    message: e.to_string(),
+                });
+            }
+
+            return Err(SmsSolverServiceError::Cancelled {
+                elapsed,
+                poll_count,
+                task_id: task_id.clone(),
+            });
+        }
+
+        // Check for timeout
+        let elapsed = run.start.elapsed();
+        if elapsed >= timeout {
+            #[cfg(feature = "tracing")]
+            error!(
+                reason = "timeout",
+                task_id = %task_id,
+                provider_type,
+                country = context.country.as_deref(),
+                service = context.service.as_deref(),
+                timeout_ms = timeout.as_millis() as u64,
+                elapsed_ms = elapsed.as_millis() as u64,
+                poll_count,
+                "Timeout reached, cancelling activation"
+            );
+
+            self.record(|stats| stats.timeouts += 1);
+
+            #[cfg(feature = "metrics")]
+            {
+                ServiceMetrics::global().timeouts.add(1, &[]);
+                ServiceMetrics::global().sms_wait_time.record(
+                    elapsed.as_secs_f64(),
+                    &[KeyValue::new("outcome", "timeout")],
+                );
+                ServiceMetrics::global()
+                    .poll_counts
+                    .record(poll_count as u64, &[KeyValue::new("outcome", "timeout")]);
+            }
+
+            // Try to cancel the activation
+            if let Err(e) = self.provider.cancel_activation(task_id).await {
+                #[cfg(feature = "tracing")]
+                warn!(error = %e, "Failed to cancel activation after timeout");
+
+                return Err(SmsSolverServiceError::CancelFailed {
+                    task_id: task_id.clone(),
+                    message: e.to_string(),
+                });
+            }
+
+            if run.codes_seen > 0 {
+                return Err(SmsSolverServiceError::NoValidCodeReceived {
+                    codes_seen: run.codes_seen,
+                    task_id: task_id.clone(),
+                });
+            }
+
+            return Err(SmsSolverServiceError::SmsTimeout {
+                timeout,
+                elapsed,
+                poll_count,
+                task_id: task_id.clone(),
+            });
+        }
+
+        run.poll_count += 1;
+        run.wait = run.poll_interval;
+        let poll_count = run.poll_count;
+
+        let polled = if self.config.use_full_sms_list {
+            self.provider
+                .get_all_sms_codes_with_text(task_id)
+                .await
+                .map(|mut messages| {
+                    // Prefer the newest code that passes the validator
+                    let newest = messages.len().checked_sub(1)?;
+                    let index = messages
+                        .iter()
+                        .rposition(|(code, _)| validator(code))
+                        .unwrap_or(newest);
+                    Some(messages.swap_remove(index))
+                })
+        } else {
+            self.provider.get_sms_code_with_text(task_id).await
+        };
+
+        match polled {
+            Ok(Some((code, _))) if !validator(&code) => {
+                // The provider keeps returning the last SMS, so only count new codes
+                if run.last_rejected.as_ref() != Some(&code) {
+                    run.codes_seen += 1;
+
+                    #[cfg(feature = "tracing")]
+                    debug!(
+                        code = %code,
+                        codes_seen = %run.codes_seen,
+                        "SMS code rejected by validator, continuing"
+                    );
+
+                    run.last_rejected = Some(code);
+                }
+                Ok(None)
+            }
+            Ok(Some((code, text))) => {
+                let elapsed = run.start.elapsed();
+
+                #[cfg(feature = "tracing")]
+                info!(
+                    code = %code,
+                    task_id = %task_id,
+                    provider_type,
+                    country = context.country.as_deref(),
+                    service = context.service.as_deref(),
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    poll_count,
+                    code_length = code.as_ref().len(),
+                    "SMS code received"
+                );
+
+                self.record(|stats| stats.codes_received += 1);
+
+                #[cfg(feature = "metrics")]
+                {
+                    ServiceMetrics::global().sms_codes_received.add(1, &[]);
+                    ServiceMetrics::global().sms_wait_time.record(
+                        elapsed.as_secs_f64(),
+                        &[KeyValue::new("outcome", "success")],
+                    );
+                    ServiceMetrics::global()
+                        .poll_counts
+                        .record(poll_count as u64, &[KeyValue::new("outcome", "success")]);
+                }
+
+                Ok(Some((code, text)))
+            }
+            Ok(None) => {
+                let elapsed = run.start.elapsed();
+                on_empty_poll(PollProgress {
+                    attempt: poll_count,
+                    elapsed,
+                    remaining: timeout.checked_sub(elapsed),
+                });
+
+                // SMS not yet received; stop early if the activation has ended
+                if let Some(err) = self.check_activation_ended(task_id, poll_count).await {
+                    self.record(|stats| stats.errors += 1);
+
+                    #[cfg(feature = "metrics")]
                     {
-                        ServiceMetrics::global()
-                            .errors
-                            .add(1, &[KeyValue::new("operation", "wait_for_sms_code")]);
+                        ServiceMetrics::global().errors.add(
+                            1,
+                            &[
+                                KeyValue::new("operation", "wait_for_sms_code"),
+                                KeyValue::new("error", "activation_ended"),
+                            ],
+                        );
                         ServiceMetrics::global()
                             .sms_wait_time
-                            .record(elapsed.as_secs_f64(), &[KeyValue::new("outcome", "error")]);
+                            .record(elapsed.as_secs_f64(), &[KeyValue::new("outcome", "ended")]);
                         ServiceMetrics::global()
                             .poll_counts
-                            .record(poll_count as u64, &[KeyValue::new("outcome", "error")]);
+                            .record(poll_count as u64, &[KeyValue::new("outcome", "ended")]);
                     }
 
-                    // Try to cancel the activation
-                    if let Err(cancel_err) = self.provider.cancel_activation(task_id).await {
-                        #[cfg(feature = "tracing")]
-                        warn!(error = %cancel_err, "Failed to cancel activation after error");
+                    return Err(err);
+                }
+
+                run.poll_interval = self.config.next_poll_interval(run.poll_interval);
+                Ok(None)
+            }
+            Err(e) if !e.is_retryable() => {
+                let should_retry_operation = e.should_retry_operation();
+                let elapsed = run.start.elapsed();
+
+                #[cfg(feature = "tracing")]
+                error!(
+                    error = %e,
+                    elapsed_secs = %elapsed.as_secs_f64(),
+                    poll_count = %poll_count,
+                    "Permanent error during polling"
+                );
+
+                self.record(|stats| stats.errors += 1);
+
+                #[cfg(feature = "metrics")]
+                {
+                    ServiceMetrics::global().errors.add(
+                        1,
+                        &[
+                            KeyValue::new("operation", "wait_for_sms_code"),
+                            KeyValue::new("error", e.metric_label()),
+                        ],
+                    );
+                    ServiceMetrics::global()
+                        .sms_wait_time
+                        .record(elapsed.as_secs_f64(), &[KeyValue::new("outcome", "error")]);
+                    ServiceMetrics::global()
+                        .poll_counts
+                        .record(poll_count as u64, &[KeyValue::new("outcome", "error")]);
+                }
+
+                // Try to cancel the activation
+                if let Err(cancel_err) = self.provider.cancel_activation(task_id).await {
+                    #[cfg(feature = "tracing")]
+                    warn!(error = %cancel_err, "Failed to cancel activation after error");
 
-                        return Err(SmsSolverServiceError::CancelFailed {
+                    // The provider reports the number as still held; keep the polling error
+                    if e.live_task_id().is_some() {
+                        return Err(SmsSolverServiceError::PhoneAcquiredButPollingFailed {
                             task_id: task_id.clone(),
-                            message: cancel_err.to_string(),
+                            source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                            should_retry_operation,
                         });
                     }
 
-                    return Err(SmsSolverServiceError::Provider {
-                        source: Box::new(e) as Box<dyn StdError + Send + Sync>,
-                        is_retryable: false,
-                        should_retry_operation,
+                    return Err(SmsSolverServiceError::CancelFailed {
+                        task_id: task_id.clone(),
+                        message: cancel_err.to_string(),
                     });
                 }
-                Err(_e) => {
-                    #[cfg(feature = "tracing")]
-                    warn!(error = %_e, poll_count = %poll_count, "Transient error during polling, continuing");
-                }
-            }
-
-            tokio::time::sleep(poll_interval).await;
-        }
-    }
-}
 
-/// Builder for SmsSolverService.
-///
-/// Provides a fluent API for constructing an SMS service with a provider
-/// and custom configuration.
-///
-/// # Example
-///
-/// ```rust,ignore
-/// use sms_solvers::{SmsSolverService, Provider};
-/// use std::time::Duration;
-///
-/// let service = SmsSolverService::builder(provider)
-///     .timeout(Duration::from_secs(180))
-///     .poll_interval(Duration::from_secs(5))
-///     .build();
-/// ```
-#[derive(Debug, Clone)]
-pub struct SmsSolverServiceBuilder<P: Provider> {
-    provider: P,
-    config_builder: SmsSolverServiceConfigBuilder,
-}
+                Err(SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable: false,
+                    should_retry_operation,
+                })
+            }
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                warn!(error = %_e, poll_count = %poll_count, "Transient error during polling, continuing");
 
-impl<P: Provider> SmsSolverServiceBuilder<P>
-where
-    P::Error: Debug + Display + RetryableError,
-{
-    /// Create a new builder with the given provider.
-    pub fn new(provider: P) -> Self {
-        Self {
-            provider,
-            config_builder: SmsSolverServiceConfigBuilder::default(),
+                Ok(None)
+            }
         }
     }
 
-    /// Set the timeout for waiting for SMS codes.
+    /// Get a phone number, giving up once `deadline` is reached.
     ///
-    /// Default: 120 seconds
-    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
-        self.config_builder = self.config_builder.timeout(timeout);
-        self
+    /// The deadline bounds the provider call including its retries. If it is
+    /// reached, [`SmsSolverServiceError::AcquisitionTimeout`] is returned
+    /// right away while the provider call finishes on a background task; a
+    /// number it acquires after the deadline is cancelled. This requires a
+    /// Tokio runtime.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::get_number_with_deadline",
+            skip_all,
+            fields(country = %country.iso_short_name())
+        )
+    )]
+    pub async fn get_number_with_deadline(
+        &self,
+        country: Country,
+        service: P::Service,
+        deadline: Instant,
+    ) -> Result<SmsTaskResult, SmsSolverServiceError>
+    where
+        P: 'static,
+        P::Service: 'static,
+    {
+        self.start_acquisition(&country)?;
+
+        let service_name = self.provider.service_name(&service);
+        let provider = self.provider.clone();
+        let requested = country.clone();
+        let mut request = Box::pin(async move {
+            provider
+                .get_phone_number_with_metadata(requested, service)
+                .await
+        });
+        let deadline_reached =
+            tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), &mut request).await;
+
+        match deadline_reached {
+            Ok(acquired) => {
+                self.complete_acquisition(country, service_name, acquired)
+                    .await
+            }
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                warn!(service = %service_name, "Deadline reached while acquiring a number");
+
+                let provider = self.provider.clone();
+                spawn_cleanup(async move {
+                    if let Ok((task_id, _, _)) = request.await {
+                        cancel(&provider, &task_id).await;
+                    }
+                });
+
+                Err(SmsSolverServiceError::AcquisitionTimeout {
+                    country: Box::new(country),
+                    service_name,
+                    deadline,
+                })
+            }
+        }
+    }
+
+    /// Acquire a number, passing the idempotency key to the provider.
+    async fn acquire_number(
+        &self,
+        country: Country,
+        service: P::Service,
+        idempotency_key: &str,
+    ) -> Result<SmsTaskResult, SmsSolverServiceError> {
+        self.start_acquisition(&country)?;
+
+        let service_name = self.provider.service_name(&service);
+        let acquired = self
+            .provider
+            .get_phone_number_with_idempotency_key(country.clone(), service, idempotency_key)
+            .await;
+        self.complete_acquisition(country, service_name, acquired)
+            .await
+    }
+
+    /// Check that `country` may be requested and count the request.
+    fn start_acquisition(&self, country: &Country) -> Result<(), SmsSolverServiceError> {
+        #[cfg(feature = "tracing")]
+        debug!("Requesting phone number");
+
+        if !self.config.allow_restricted_countries && is_sanctioned_country(country) {
+            return Err(SmsSolverServiceError::CountryRestricted {
+                country: Box::new(country.clone()),
+                reason: "country is under sanctions",
+            });
+        }
+
+        #[cfg(feature = "metrics")]
+        ServiceMetrics::global()
+            .numbers_requested
+            .add(1, &[KeyValue::new("country", country.alpha2().to_string())]);
+
+        Ok(())
+    }
+
+    /// Turn the provider's answer into a [`SmsTaskResult`], cancelling
+    /// numbers that cannot be used.
+    async fn complete_acquisition(
+        &self,
+        country: Country,
+        service_name: String,
+        acquired: Result<(TaskId, FullNumber, Option<ActivationMetadata>), P::Error>,
+    ) -> Result<SmsTaskResult, SmsSolverServiceError> {
+        #[cfg(feature = "metrics")]
+        let country_alpha2 = country.alpha2().to_string();
+
+        let (task_id, full_number, metadata) = acquired.map_err(|e| {
+            #[cfg(feature = "metrics")]
+            ServiceMetrics::global().errors.add(
+                1,
+                &[
+                    KeyValue::new("country", country_alpha2.clone()),
+                    KeyValue::new("operation", "get_number"),
+                    KeyValue::new("error", e.metric_label()),
+                ],
+            );
+            let is_retryable = e.is_retryable();
+            let should_retry_operation = e.should_retry_operation();
+            SmsSolverServiceError::Provider {
+                source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                is_retryable,
+                should_retry_operation,
+            }
+        })?;
+
+        let dial_code = DialCode::from(&country);
+
+        // Check if the dial code is blacklisted
+        if !self.provider.is_dial_code_supported(&dial_code) {
+            #[cfg(feature = "tracing")]
+            warn!(
+                task_id = %task_id,
+                dial_code = %dial_code,
+                "Dial code is blacklisted, cancelling activation"
+            );
+
+            // Cancel the activation since we won't use this number
+            if let Err(_e) = self.provider.cancel_activation(&task_id).await {
+                #[cfg(feature = "tracing")]
+                warn!(error = %_e, "Failed to cancel activation for blacklisted number");
+            }
+
+            return Err(SmsSolverServiceError::DialCodeBlacklisted { dial_code, task_id });
+        }
+
+        let number = Number::from_full_number(&full_number, &dial_code).map_err(|e| {
+            SmsSolverServiceError::NumberParse {
+                full_number: full_number.to_string(),
+                message: e.to_string(),
+            }
+        })?;
+
+        #[cfg(feature = "tracing")]
+        info!(
+            task_id = %task_id,
+            dial_code = %dial_code,
+            number = %if self.config.redact_logs {
+                full_number.redact()
+            } else {
+                full_number.to_string()
+            },
+            country = %country.iso_short_name(),
+            "Phone number acquired"
+        );
+
+        self.record(|stats| {
+            stats.numbers_acquired += 1;
+            if let Some(cost) = metadata.as_ref().and_then(|m| m.cost) {
+                stats.add_spend(cost);
+            }
+        });
+        self.remember_task(
+            &task_id,
+            PollContext {
+                country: Some(country.alpha2().to_string()),
+                service: Some(service_name),
+            },
+        );
+
+        Ok(SmsTaskResult {
+            task_id,
+            dial_code,
+            number,
+            full_number,
+            country,
+            metadata,
+        })
+    }
+}
+
+impl<P: Provider> SmsSolverService<P>
+where
+    P: 'static,
+    P::Service: 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    /// Get a phone number, sharing the request with concurrent callers using the same key.
+    ///
+    /// While a call with `key` is in flight, further calls with that key wait
+    /// for it and receive the same [`SmsTaskResult`] instead of buying another
+    /// number. If the call fails, every caller, including the one that made
+    /// the request, gets [`SmsSolverServiceError::Shared`] wrapping the same
+    /// error, whether or not anyone was waiting on it.
+    ///
+    /// If the leading call is dropped, one of the waiting callers retries.
+    pub async fn get_number_idempotent(
+        &self,
+        country: Country,
+        service: P::Service,
+        key: impl Into<String>,
+    ) -> Result<SmsTaskResult, SmsSolverServiceError> {
+        let key = key.into();
+
+        loop {
+            let receiver = {
+                let mut in_flight = self
+                    .in_flight
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                match in_flight.get_mut(&key) {
+                    Some(waiters) => {
+                        let (sender, receiver) = oneshot::channel();
+                        waiters.push(sender);
+                        receiver
+                    }
+                    None => {
+                        in_flight.insert(key.clone(), Vec::new());
+                        break;
+                    }
+                }
+            };
+
+            if let Ok(result) = receiver.await {
+                return result.map_err(SmsSolverServiceError::Shared);
+            }
+        }
+
+        let guard = InFlightGuard {
+            in_flight: &self.in_flight,
+            key: &key,
+            done: false,
+        };
+        let result = self.get_number(country, service).await;
+        let waiters = guard.finish();
+
+        let shared = result.map_err(Arc::new);
+        for waiter in waiters {
+            let _ = waiter.send(shared.clone());
+        }
+        shared.map_err(SmsSolverServiceError::Shared)
+    }
+
+    /// Get a phone number wrapped in an [`ActivationGuard`].
+    ///
+    /// The activation is cancelled when the guard is dropped unless it was
+    /// finished or cancelled explicitly, so a pipeline step that bails out
+    /// early does not leak a paid number.
+    pub async fn acquire_for_pipeline(
+        &self,
+        country: Country,
+        service: P::Service,
+    ) -> Result<ActivationGuard<'_, P>, SmsSolverServiceError> {
+        let task = self.get_number(country, service).await?;
+        Ok(ActivationGuard::new(self, task))
+    }
+
+    /// Guard an activation obtained from this service.
+    ///
+    /// Wrap the result of [`get_number`](SmsSolverServiceTrait::get_number)
+    /// right away: the activation is cancelled when the guard is dropped,
+    /// e.g. on an early return, unless it was finished, cancelled or
+    /// [defused](ActivationGuard::defuse).
+    pub fn guard(&self, result: &SmsTaskResult) -> ActivationGuard<'_, P> {
+        ActivationGuard::new(self, result.clone())
+    }
+
+    /// Guard an activation, moving the service into the guard.
+    ///
+    /// Like [`guard`](Self::guard), but the returned guard is not tied to
+    /// the lifetime of the service. Clone the service first to keep using it.
+    pub fn into_guard(self, result: SmsTaskResult) -> OwnedActivationGuard<P> {
+        OwnedActivationGuard::new(self, result)
+    }
+
+    /// Get a phone number from the cheapest of `candidates` that has numbers
+    /// in stock.
+    ///
+    /// Prices come from [`Provider::country_prices`]; candidates the provider
+    /// reports no price for are skipped. Returns
+    /// [`SmsSolverServiceError::NoCandidateCountryInStock`] if none is left,
+    /// or [`SmsSolverServiceError::CountryPricesUnsupported`] if the provider
+    /// reports no prices at all.
+    pub async fn get_number_cheapest_country(
+        &self,
+        service: P::Service,
+        candidates: &[Country],
+    ) -> Result<SmsTaskResult, SmsSolverServiceError> {
+        let prices = self
+            .provider
+            .country_prices(service.clone())
+            .await
+            .map_err(|e| {
+                let is_retryable = e.is_retryable();
+                let should_retry_operation = e.should_retry_operation();
+                SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable,
+                    should_retry_operation,
+                }
+            })?;
+
+        if prices.is_empty() {
+            return Err(SmsSolverServiceError::CountryPricesUnsupported);
+        }
+
+        let country = cheapest_in_stock(&prices, candidates).ok_or(
+            SmsSolverServiceError::NoCandidateCountryInStock {
+                candidates: candidates.len(),
+            },
+        )?;
+
+        #[cfg(feature = "tracing")]
+        debug!(country = ?country.alpha2(), "Selected cheapest candidate country");
+
+        self.get_number(country, service).await
+    }
+}
+
+/// Pick the cheapest of `candidates` with `count > 0` in `prices`.
+///
+/// Ties go to the candidate listed first.
+fn cheapest_in_stock(prices: &[(Country, PriceEntry)], candidates: &[Country]) -> Option<Country> {
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            prices
+                .iter()
+                .find(|(country, entry)| country.alpha2() == candidate.alpha2() && entry.count > 0)
+        })
+        .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+        .map(|(country, _)| country.clone())
+}
+
+/// Number request started by [`get_number_from_any`].
+///
+/// Yields `None` if the race was won before the request started.
+type RacedRequest = JoinHandle<Option<Result<SmsTaskResult, SmsSolverServiceError>>>;
+
+/// Race `service.get_number` over `countries`, running at most
+/// `max_concurrent` requests at once.
+///
+/// Each request runs on its own Tokio task with a child of one race token.
+/// Once a number is acquired and `cancel_on_first` is true, the token is
+/// cancelled: requests that have not started yet are skipped, and running
+/// requests are left to finish, then cancel the number they acquired. They
+/// are not aborted, since a request stopped mid-flight may already have
+/// bought a number that would then never be returned.
+///
+/// Shared by the `get_number_from_any` implementations of
+/// [`SmsSolverService`] and
+/// [`HookedSmsSolverService`](super::HookedSmsSolverService) so that each
+/// goes through its own `get_number`.
+pub(crate) async fn get_number_from_any<S>(
+    service: &S,
+    countries: Vec<(Country, S::Service)>,
+    cancel_on_first: bool,
+    max_concurrent: usize,
+) -> Result<SmsTaskResult, SmsSolverServiceError>
+where
+    S: SmsSolverServiceTrait<Error = SmsSolverServiceError> + Clone + 'static,
+    S::Service: 'static,
+{
+    let attempted = countries.len();
+    let race = CancellationToken::new();
+    let spawn = |(country, requested): (Country, S::Service)| -> RacedRequest {
+        let service = service.clone();
+        let token = race.child_token();
+        tokio::spawn(async move {
+            if token.is_cancelled() {
+                return None;
+            }
+            let result = service.get_number(country, requested).await;
+            if let Ok(task) = &result
+                && token.is_cancelled()
+            {
+                cancel_raced(&service, task).await;
+            }
+            Some(result)
+        })
+    };
+
+    let mut pending = countries.into_iter();
+    let mut running: Vec<RacedRequest> = pending
+        .by_ref()
+        .take(max_concurrent.max(1))
+        .map(spawn)
+        .collect();
+    let mut errors = Vec::new();
+
+    while !running.is_empty() {
+        let (joined, _, rest) = select_all(running).await;
+        running = rest;
+
+        match joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic())) {
+            Some(Ok(task)) => {
+                #[cfg(feature = "tracing")]
+                debug!(
+                    task_id = %task.task_id,
+                    country = %task.country.iso_short_name(),
+                    "Number acquired from raced countries"
+                );
+
+                if cancel_on_first {
+                    race.cancel();
+                }
+                return Ok(task);
+            }
+            Some(Err(e)) => errors.push(e),
+            None => {}
+        }
+        running.extend(pending.next().map(spawn));
+    }
+
+    Err(SmsSolverServiceError::AllCountriesFailed { attempted, errors })
+}
+
+/// Cancel a number acquired by a raced request that lost.
+async fn cancel_raced<S>(service: &S, task: &SmsTaskResult)
+where
+    S: SmsSolverServiceTrait<Error = SmsSolverServiceError>,
+{
+    if let Err(_e) = service.cancel_activation(&task.task_id).await {
+        #[cfg(feature = "tracing")]
+        warn!(
+            task_id = %task.task_id,
+            error = %_e,
+            "Failed to cancel number acquired by a losing request"
+        );
+    }
+}
+
+impl<P: Provider> SmsSolverServiceTrait for SmsSolverService<P>
+where
+    P: 'static,
+    P::Service: 'static,
+    P::Error: Debug + Display + RetryableError + Send + Sync + 'static,
+{
+    type Error = SmsSolverServiceError;
+    type Service = P::Service;
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::get_number",
+            skip_all,
+            fields(country = %country.iso_short_name())
+        )
+    )]
+    /// Gives up after 30 seconds; use
+    /// [`get_number_with_deadline`](SmsSolverService::get_number_with_deadline)
+    /// for another deadline.
+    async fn get_number(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        self.get_number_with_deadline(
+            country,
+            service,
+            Instant::now() + DEFAULT_ACQUISITION_TIMEOUT,
+        )
+        .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::get_number_with_idempotency_key",
+            skip_all,
+            fields(country = %country.iso_short_name())
+        )
+    )]
+    async fn get_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        self.acquire_number(country, service, idempotency_key).await
+    }
+
+    /// One Tokio task is spawned per request, with at most
+    /// [`max_concurrent`](SmsSolverServiceConfig::max_concurrent) running at
+    /// once; when a request fails, the next country is started. If every
+    /// request fails, or `countries` is empty,
+    /// [`SmsSolverServiceError::AllCountriesFailed`] is returned.
+    async fn get_number_from_any(
+        &self,
+        countries: Vec<(Country, Self::Service)>,
+        cancel_on_first: bool,
+    ) -> Result<SmsTaskResult, Self::Error> {
+        get_number_from_any(self, countries, cancel_on_first, self.config.max_concurrent).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::wait_for_sms_code",
+            skip_all,
+            fields(task_id = %task_id)
+        )
+    )]
+    async fn wait_for_sms_code(&self, task_id: &TaskId) -> Result<SmsCode, Self::Error> {
+        self.wait_for_sms_code_cancellable(task_id, CancellationToken::new())
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::wait_for_sms_code_cancellable",
+            skip_all,
+            fields(task_id = %task_id)
+        )
+    )]
+    async fn wait_for_sms_code_cancellable(
+        &self,
+        task_id: &TaskId,
+        cancel_token: CancellationToken,
+    ) -> Result<SmsCode, Self::Error> {
+        self.poll_for_sms_code(task_id, cancel_token, &|_| true, &|_| {})
+            .await
+            .map(|(code, _)| code)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "SmsSolverService::wait_for_sms_code_with_text",
+            skip_all,
+            fields(task_id = %task_id)
+        )
+    )]
+    async fn wait_for_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<(SmsCode, String), Self::Error> {
+        self.poll_for_sms_code(task_id, CancellationToken::new(), &|_| true, &|_| {})
+            .await
+    }
+
+    #[cfg(feature = "streams")]
+    fn wait_for_sms_code_stream<'a>(
+        &'a self,
+        task_id: &'a TaskId,
+    ) -> impl Stream<Item = Result<Option<SmsCode>, Self::Error>> + Send + 'a {
+        async_stream::stream! {
+            let context = self.task_context(task_id);
+            let cancel_token = CancellationToken::new();
+
+            let mut run = match self.start_polling(task_id, &context).await {
+                Ok(run) => run,
+                Err(e) => {
+                    self.forget_task(task_id);
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            loop {
+                match self
+                    .poll_step(task_id, &mut run, &cancel_token, &|_| true, &|_| {}, &context)
+                    .await
+                {
+                    Ok(Some((code, _))) => {
+                        yield Ok(Some(code));
+                        break;
+                    }
+                    Ok(None) => yield Ok(None),
+                    Err(e) => {
+                        self.forget_task(task_id);
+                        yield Err(e);
+                        break;
+                    }
+                }
+
+                tokio::time::sleep(run.wait).await;
+            }
+        }
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.provider
+            .finish_activation(task_id)
+            .await
+            .map_err(|e| {
+                let is_retryable = e.is_retryable();
+                let should_retry_operation = e.should_retry_operation();
+                SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable,
+                    should_retry_operation,
+                }
+            })?;
+        self.forget_task(task_id);
+        Ok(())
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        self.provider
+            .cancel_activation(task_id)
+            .await
+            .map_err(|e| {
+                let is_retryable = e.is_retryable();
+                let should_retry_operation = e.should_retry_operation();
+                SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable,
+                    should_retry_operation,
+                }
+            })?;
+        self.forget_task(task_id);
+        Ok(())
+    }
+
+    async fn request_another_sms_code(&self, task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.provider
+            .request_another_code(task_id)
+            .await
+            .map_err(|e| {
+                let is_retryable = e.is_retryable();
+                let should_retry_operation = e.should_retry_operation();
+                SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable,
+                    should_retry_operation,
+                }
+            })
+    }
+
+    async fn get_available_countries(
+        &self,
+        service: &Self::Service,
+    ) -> Result<Vec<CountryAvailability>, Self::Error> {
+        self.provider
+            .available_countries_with_counts(service)
+            .await
+            .map_err(|e| {
+                let is_retryable = e.is_retryable();
+                let should_retry_operation = e.should_retry_operation();
+                SmsSolverServiceError::Provider {
+                    source: Box::new(e) as Box<dyn StdError + Send + Sync>,
+                    is_retryable,
+                    should_retry_operation,
+                }
+            })
+    }
+
+    fn is_activation_released(&self, error: &Self::Error) -> bool {
+        error.activation_released()
+    }
+
+    fn auto_finish(&self) -> bool {
+        self.config.auto_finish
+    }
+}
+
+/// Builder for SmsSolverService.
+///
+/// Provides a fluent API for constructing an SMS service with a provider
+/// and custom configuration.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use sms_solvers::{SmsSolverService, Provider};
+/// use std::time::Duration;
+///
+/// let service = SmsSolverService::builder(provider)
+///     .timeout(Duration::from_secs(180))
+///     .poll_interval(Duration::from_secs(5))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmsSolverServiceBuilder<P: Provider> {
+    provider: P,
+    config_builder: SmsSolverServiceConfigBuilder,
+}
+
+impl<P: Provider> SmsSolverServiceBuilder<P>
+where
+    P::Error: Debug + Display + RetryableError,
+{
+    /// Create a new builder with the given provider.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            config_builder: SmsSolverServiceConfigBuilder::default(),
+        }
+    }
+
+    /// Set the timeout for waiting for SMS codes.
+    ///
+    /// Default: 120 seconds
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.config_builder = self.config_builder.timeout(timeout);
+        self
+    }
+
+    /// Set the timeout for waiting for SMS codes in `country`.
+    ///
+    /// Default: none
+    pub fn country_timeout(mut self, country: Country, timeout: std::time::Duration) -> Self {
+        self.config_builder = self.config_builder.country_timeout(country, timeout);
+        self
+    }
+
+    /// Set the polling interval when waiting for SMS codes.
+    ///
+    /// Default: 3 seconds
+    pub fn poll_interval(mut self, interval: std::time::Duration) -> Self {
+        self.config_builder = self.config_builder.poll_interval(interval);
+        self
+    }
+
+    /// Multiply the polling interval by `multiplier` after each empty poll.
+    ///
+    /// Default: 1.0 (constant interval)
+    pub fn poll_backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.config_builder = self.config_builder.poll_backoff_multiplier(multiplier);
+        self
+    }
+
+    /// Cap the polling interval when backing off.
+    ///
+    /// Default: 15 seconds
+    pub fn max_poll_interval(mut self, interval: std::time::Duration) -> Self {
+        self.config_builder = self.config_builder.max_poll_interval(interval);
+        self
+    }
+
+    /// Set the full configuration.
+    pub fn config(mut self, config: SmsSolverServiceConfig) -> Self {
+        self.config_builder = config.into();
+        self
+    }
+
+    /// Allow requesting numbers for sanctioned countries.
+    ///
+    /// Default: false
+    pub fn allow_restricted_countries(mut self, allow: bool) -> Self {
+        self.config_builder = self.config_builder.allow_restricted_countries(allow);
+        self
+    }
+
+    /// Redact phone numbers in logs.
+    ///
+    /// Default: false
+    pub fn redact_logs(mut self, enabled: bool) -> Self {
+        self.config_builder = self.config_builder.redact_logs(enabled);
+        self
+    }
+
+    /// Finish activations automatically after a code is received.
+    ///
+    /// Default: true
+    pub fn auto_finish(mut self, enabled: bool) -> Self {
+        self.config_builder = self.config_builder.auto_finish(enabled);
+        self
+    }
+
+    /// Set how many number requests run at once when racing countries.
+    ///
+    /// Default: 3
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.config_builder = self.config_builder.max_concurrent(max_concurrent);
+        self
+    }
+
+    /// Build the SmsSolverService.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration is invalid. Use [`Self::try_build`] to
+    /// handle the error instead.
+    pub fn build(self) -> SmsSolverService<P> {
+        match self.build_validated() {
+            Ok(service) => service,
+            Err(e) => panic!("Invalid SmsSolverService configuration: {e}"),
+        }
+    }
+
+    /// Build the SmsSolverService, validating the configuration.
+    pub fn build_validated(self) -> Result<SmsSolverService<P>, SmsSolverServiceBuildError> {
+        let config = self.config_builder.try_build()?;
+        Ok(SmsSolverService::new(self.provider, config))
+    }
+
+    /// Alias for [`Self::build_validated`], matching
+    /// [`SmsSolverServiceConfigBuilder::try_build`].
+    pub fn try_build(self) -> Result<SmsSolverService<P>, SmsSolverServiceBuildError> {
+        self.build_validated()
+    }
+
+    /// Build the SmsSolverService after validating the configuration and
+    /// warming up the provider.
+    pub async fn build_warmed_up(self) -> Result<SmsSolverService<P>, SmsSolverServiceBuildError> {
+        let service = self.build_validated()?;
+        service
+            .provider
+            .warmup()
+            .await
+            .map_err(|e| SmsSolverServiceBuildError::Warmup(Box::new(e)))?;
+        Ok(service)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::RetryableError;
+    use crate::service::ConfigError;
+    use crate::testing::{MockError, MockProvider, MockService};
+    use crate::types::{ActivationMetadata, ActivationState};
+    use keshvar::Alpha2;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_with_progress() {
+        let provider = MockProvider::builder().sms_after_polls(3, "123456").build();
+        let config = SmsSolverServiceConfig::builder()
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider, config);
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let code = service
+            .wait_for_sms_code_with_progress(&TaskId::from("task123"), |p| {
+                progress.lock().unwrap().push(p)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(code.as_ref(), "123456");
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(
+            progress.iter().map(|p| p.attempt).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(progress.iter().all(|p| p.remaining.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_polling_stops_on_terminal_activation_state() {
+        let provider = MockProvider::builder()
+            .sms_after_polls(10, "123456")
+            .activation_state(ActivationState::Cancelled)
+            .build();
+        let config = SmsSolverServiceConfig::builder()
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+
+        let result = service
+            .wait_for_sms_code_with_progress(&TaskId::from("task123"), |_| {})
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SmsSolverServiceError::ActivationEnded {
+                state: ActivationState::Cancelled,
+                ..
+            })
+        ));
+        assert_eq!(provider.call_count_get_sms_code(), STATUS_CHECK_POLLS);
+        assert_eq!(service.statistics().errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_activation_reports_every_empty_poll() {
+        let provider = MockProvider::builder()
+            .sms_after_polls(10, "123456")
+            .activation_state(ActivationState::Expired)
+            .build();
+        let config = SmsSolverServiceConfig::builder()
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let empty_polls = AtomicU32::new(0);
+
+        let result = service
+            .wait_for_sms_code_with_progress(&TaskId::from("task123"), |_| {
+                empty_polls.fetch_add(1, Ordering::SeqCst);
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SmsSolverServiceError::ActivationExpired { .. })
+        ));
+        assert_eq!(empty_polls.load(Ordering::SeqCst), STATUS_CHECK_POLLS);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_idempotency_key() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone())
+            .map_provider(crate::SmsRetryableProvider::new);
+
+        let task = service
+            .get_number_with_idempotency_key(Alpha2::UA.to_country(), MockService, "order-1")
+            .await
+            .unwrap();
+        service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        assert_eq!(task.task_id.as_ref(), "task123");
+        assert_eq!(provider.idempotency_keys(), vec!["order-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_map_provider_keeps_config() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("123456")
+            .build();
+        let service = SmsSolverService::builder(provider)
+            .timeout(Duration::from_secs(42))
+            .auto_finish(false)
+            .build()
+            .map_provider(crate::SmsRetryableProvider::new);
+
+        assert_eq!(service.config().timeout, Duration::from_secs(42));
+        assert!(!service.config().auto_finish);
+
+        let (task, code) = service
+            .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(task.task_id.as_ref(), "task123");
+        assert_eq!(code.as_ref(), "123456");
+        assert_eq!(service.statistics().codes_received, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_and_wait_finishes_activation() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("123456")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let (task, code) = service
+            .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        assert_eq!(task.task_id.as_ref(), "task123");
+        assert_eq!(code.as_ref(), "123456");
+        assert_eq!(provider.finished_tasks().len(), 1);
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_and_wait_without_auto_finish() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("123456")
+            .build();
+        let config = SmsSolverServiceConfig::default().with_auto_finish(false);
+        let service = SmsSolverService::new(provider.clone(), config);
+
+        service
+            .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.finished_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_number_and_wait_cancels_on_failure() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code_response(Err(MockError::Permanent("boom".into())))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let err = service
+            .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, SmsSolverServiceError::Provider { .. }));
+        // Polling already cancelled, so the activation is not cancelled again
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+        assert_eq!(provider.finished_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_activation_guard_cancels_on_drop() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let guard = service
+            .acquire_for_pipeline(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(guard.task().task_id.as_ref(), "task123");
+        drop(guard);
+
+        // Cleanup runs on a spawned task
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_activation_guard_does_not_cancel_after_finish() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("123456")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        {
+            let guard = service
+                .acquire_for_pipeline(Alpha2::UA.to_country(), MockService)
+                .await
+                .unwrap();
+            assert_eq!(guard.wait_for_code().await.unwrap().as_ref(), "123456");
+            guard.finish().await.unwrap();
+        }
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_activation_guard_does_not_cancel_after_provider_error() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code_response(Err(MockError::Permanent("boom".into())))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        {
+            let guard = service
+                .acquire_for_pipeline(Alpha2::UA.to_country(), MockService)
+                .await
+                .unwrap();
+            let err = guard.wait_for_code().await.unwrap_err();
+            assert!(matches!(err, SmsSolverServiceError::Provider { .. }));
+        }
+
+        // Polling already cancelled; the guard must not cancel again
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_activation_guard_explicit_cancel_runs_once() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let guard = service
+            .acquire_for_pipeline(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        guard.cancel().await.unwrap();
+        drop(guard);
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_guard_cancels_on_early_return() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code_response(Err(MockError::Permanent("boom".into())))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        async fn use_number(
+            service: &SmsSolverService<MockProvider>,
+        ) -> Result<(), SmsSolverServiceError> {
+            let result = service
+                .get_number(Alpha2::UA.to_country(), MockService)
+                .await?;
+            let _guard = service.guard(&result);
+            service
+                .provider()
+                .get_sms_code(&result.task_id)
+                .await
+                .map_err(|e| SmsSolverServiceError::Provider {
+                    source: Box::new(e),
+                    is_retryable: false,
+                    should_retry_operation: false,
+                })?;
+            Ok(())
+        }
+
+        assert!(use_number(&service).await.is_err());
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_guard_defuse_skips_cancel() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        {
+            let guard = service.guard(&result);
+            service.finish_activation(&result.task_id).await.unwrap();
+            guard.defuse();
+        }
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_owned_guard_cancels_on_drop_in_spawned_task() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        let guard = service.clone().into_guard(result);
+        assert_eq!(guard.task().task_id.as_ref(), "task123");
+
+        tokio::spawn(async move { drop(guard) }).await.unwrap();
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_owned_guard_does_not_cancel_after_finish() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        let guard = service.into_guard(result);
+        guard.finish().await.unwrap();
+        drop(guard);
+
+        tokio::task::yield_now().await;
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+        assert_eq!(provider.finished_tasks().len(), 1);
+    }
+
+    fn price(cost: f64, count: u32) -> PriceEntry {
+        PriceEntry { cost, count }
+    }
+
+    #[test]
+    fn test_cheapest_in_stock_picks_lowest_cost_candidate() {
+        let prices = vec![
+            (Alpha2::UA.to_country(), price(0.5, 10)),
+            (Alpha2::PL.to_country(), price(0.2, 3)),
+            (Alpha2::US.to_country(), price(0.1, 100)),
+        ];
+        let candidates = [Alpha2::UA.to_country(), Alpha2::PL.to_country()];
+
+        let country = cheapest_in_stock(&prices, &candidates).unwrap();
+        assert_eq!(country.alpha2(), Alpha2::PL);
+    }
+
+    #[test]
+    fn test_cheapest_in_stock_skips_out_of_stock_and_unpriced() {
+        let prices = vec![
+            (Alpha2::UA.to_country(), price(0.5, 10)),
+            (Alpha2::PL.to_country(), price(0.2, 0)),
+        ];
+        let candidates = [
+            Alpha2::PL.to_country(),
+            Alpha2::DE.to_country(),
+            Alpha2::UA.to_country(),
+        ];
+
+        let country = cheapest_in_stock(&prices, &candidates).unwrap();
+        assert_eq!(country.alpha2(), Alpha2::UA);
+    }
+
+    #[test]
+    fn test_cheapest_in_stock_prefers_first_candidate_on_tie() {
+        let prices = vec![
+            (Alpha2::UA.to_country(), price(0.3, 1)),
+            (Alpha2::PL.to_country(), price(0.3, 1)),
+        ];
+        let candidates = [Alpha2::PL.to_country(), Alpha2::UA.to_country()];
+
+        let country = cheapest_in_stock(&prices, &candidates).unwrap();
+        assert_eq!(country.alpha2(), Alpha2::PL);
+    }
+
+    #[test]
+    fn test_cheapest_in_stock_none_in_stock() {
+        let prices = vec![(Alpha2::UA.to_country(), price(0.3, 0))];
+
+        assert!(cheapest_in_stock(&prices, &[Alpha2::UA.to_country()]).is_none());
+        assert!(cheapest_in_stock(&prices, &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_number_cheapest_country_without_prices() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let err = service
+            .get_number_cheapest_country(MockService, &[Alpha2::UA.to_country()])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::CountryPricesUnsupported
+        ));
+        assert!(!err.should_retry_operation());
+        assert_eq!(provider.call_count_get_number(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_from_any_returns_fastest_country() {
+        let provider = MockProvider::builder()
+            .country_number(
+                Alpha2::UA.to_country(),
+                Duration::from_millis(300),
+                "ua",
+                "380501234567",
+            )
+            .country_number(
+                Alpha2::GB.to_country(),
+                Duration::from_millis(100),
+                "gb",
+                "447911123456",
+            )
+            .country_number(
+                Alpha2::DE.to_country(),
+                Duration::from_millis(200),
+                "de",
+                "4915123456789",
+            )
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+        let started = tokio::time::Instant::now();
+
+        let result = service
+            .get_number_from_any(
+                vec![
+                    (Alpha2::UA.to_country(), MockService),
+                    (Alpha2::GB.to_country(), MockService),
+                    (Alpha2::DE.to_country(), MockService),
+                ],
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.task_id.as_ref(), "gb");
+        assert_eq!(result.country.alpha2(), Alpha2::GB);
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+
+        // The slower requests finish in the background and are cancelled
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(provider.cancelled_tasks().len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_from_any_starts_next_country_after_failure() {
+        let provider = MockProvider::builder()
+            .country_number_response(
+                Alpha2::UA.to_country(),
+                Duration::from_millis(10),
+                Err(MockError::Permanent("No numbers".into())),
+            )
+            .country_number(
+                Alpha2::GB.to_country(),
+                Duration::from_millis(50),
+                "gb",
+                "447911123456",
+            )
+            .country_number(
+                Alpha2::DE.to_country(),
+                Duration::from_millis(10),
+                "de",
+                "4915123456789",
+            )
+            .build();
+        let config = SmsSolverServiceConfig::builder().max_concurrent(1).build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let started = tokio::time::Instant::now();
+
+        let result = service
+            .get_number_from_any(
+                vec![
+                    (Alpha2::UA.to_country(), MockService),
+                    (Alpha2::GB.to_country(), MockService),
+                    (Alpha2::DE.to_country(), MockService),
+                ],
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.task_id.as_ref(), "gb");
+        assert_eq!(started.elapsed(), Duration::from_millis(60));
+        assert_eq!(provider.call_count_get_number(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_from_any_caps_concurrent_requests() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .get_number_delay(Duration::from_millis(100))
+            .build();
+        let config = SmsSolverServiceConfig::builder().max_concurrent(2).build();
+        let service = SmsSolverService::new(provider.clone(), config);
+
+        service
+            .get_number_from_any(vec![(Alpha2::UA.to_country(), MockService); 4], false)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.call_count_get_number(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_from_any_all_countries_failed() {
+        let provider = MockProvider::builder()
+            .country_number_response(
+                Alpha2::UA.to_country(),
+                Duration::from_millis(20),
+                Err(MockError::Permanent("No numbers".into())),
+            )
+            .country_number_response(
+                Alpha2::GB.to_country(),
+                Duration::from_millis(10),
+                Err(MockError::Permanent("No numbers".into())),
+            )
+            .build();
+        let service = SmsSolverService::with_provider(provider);
+
+        let err = service
+            .get_number_from_any(
+                vec![
+                    (Alpha2::UA.to_country(), MockService),
+                    (Alpha2::GB.to_country(), MockService),
+                ],
+                true,
+            )
+            .await
+            .unwrap_err();
+
+        let SmsSolverServiceError::AllCountriesFailed { attempted, errors } = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(*attempted, 2);
+        assert_eq!(errors.len(), 2);
+        assert!(!err.is_retryable());
+
+        let err = service
+            .get_number_from_any(Vec::new(), true)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::AllCountriesFailed { attempted: 0, .. }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_from_any_keeps_losers_without_cancel_on_first() {
+        let provider = MockProvider::builder()
+            .country_number(
+                Alpha2::UA.to_country(),
+                Duration::from_millis(10),
+                "ua",
+                "380501234567",
+            )
+            .country_number(
+                Alpha2::GB.to_country(),
+                Duration::from_millis(50),
+                "gb",
+                "447911123456",
+            )
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let result = service
+            .get_number_from_any(
+                vec![
+                    (Alpha2::UA.to_country(), MockService),
+                    (Alpha2::GB.to_country(), MockService),
+                ],
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.task_id.as_ref(), "ua");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(provider.call_count_get_number(), 2);
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_fails_fast_for_dead_task() {
+        let provider = MockProvider::builder()
+            .sms_after_polls(0, "123456")
+            .task_alive_response(Ok(false))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let err = service
+            .wait_for_sms_code(&TaskId::from("task123"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::ActivationExpired { ref task_id } if task_id.as_ref() == "task123"
+        ));
+        assert!(err.should_retry_operation());
+        assert_eq!(provider.call_count_get_sms_code(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancellable_wait_fails_fast_for_dead_task() {
+        let provider = MockProvider::builder()
+            .sms_after_polls(0, "123456")
+            .task_alive_response(Ok(false))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let err = service
+            .wait_for_sms_code_cancellable(&TaskId::from("task123"), CancellationToken::new())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::ActivationExpired { .. }
+        ));
+        assert_eq!(provider.call_count_get_sms_code(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_polls_when_alive_check_fails() {
+        let provider = MockProvider::builder()
+            .sms_after_polls(0, "123456")
+            .task_alive_response(Err(MockError::Transient))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let code = service
+            .wait_for_sms_code(&TaskId::from("task123"))
+            .await
+            .unwrap();
+
+        assert_eq!(code.as_str(), "123456");
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_success() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_after_polls(2, "123456")
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider.clone(), config);
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(result.task_id.as_ref(), "task123");
+
+        let code = service.wait_for_sms_code(&result.task_id).await.unwrap();
+        assert_eq!(code.as_str(), "123456");
+
+        // Should have polled 3 times (2 None + 1 Some)
+        assert_eq!(provider.call_count_get_sms_code(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_backs_off_polling() {
+        tokio::time::pause();
+
+        let provider = MockProvider::builder()
+            .sms_after_polls(10, "123456")
+            .build();
+        let service = SmsSolverService::builder(provider.clone())
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_secs(1))
+            .poll_backoff_multiplier(2.0)
+            .max_poll_interval(Duration::from_secs(4))
+            .build();
+
+        let handle =
+            tokio::spawn(async move { service.wait_for_sms_code(&TaskId::from("task123")).await });
+
+        async fn settle() {
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+        }
+        let polls = || provider.call_count_get_sms_code();
+
+        settle().await;
+        assert_eq!(polls(), 1);
+
+        // Waits of 1s, 2s, then 4s twice since the interval is capped.
+        // The timer has millisecond resolution, so allow some slack.
+        let slack = Duration::from_millis(10);
+        for (expected, wait_secs) in [(2, 1), (3, 2), (4, 4), (5, 4)] {
+            tokio::time::advance(Duration::from_secs(wait_secs) - slack).await;
+            settle().await;
+            assert_eq!(polls(), expected - 1);
+
+            tokio::time::advance(slack * 2).await;
+            settle().await;
+            assert_eq!(polls(), expected);
+        }
+
+        handle.abort();
+    }
+
+    #[cfg(feature = "streams")]
+    #[tokio::test]
+    async fn test_wait_for_sms_code_stream_yields_each_poll() {
+        use futures::StreamExt;
+
+        let provider = MockProvider::builder().sms_after_polls(2, "123456").build();
+        let config = SmsSolverServiceConfig::builder()
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let task_id = TaskId::from("task123");
+
+        let polls: Vec<_> = service.wait_for_sms_code_stream(&task_id).collect().await;
+
+        let polls = polls
+            .into_iter()
+            .map(|poll| poll.unwrap().map(|code| code.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(polls, vec![None, None, Some("123456".to_string())]);
+        assert_eq!(provider.call_count_get_sms_code(), 3);
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+    }
+
+    #[cfg(feature = "streams")]
+    #[tokio::test]
+    async fn test_wait_for_sms_code_stream_cancels_on_timeout() {
+        use futures::StreamExt;
+
+        let provider = MockProvider::builder().cancel_response(Ok(())).build();
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let task_id = TaskId::from("task123");
+
+        let mut polls: Vec<_> = service.wait_for_sms_code_stream(&task_id).collect().await;
+
+        assert!(matches!(
+            polls.pop(),
+            Some(Err(SmsSolverServiceError::SmsTimeout { .. }))
+        ));
+        assert!(polls.iter().all(|poll| matches!(poll, Ok(None))));
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[cfg(feature = "streams")]
+    #[tokio::test]
+    async fn test_wait_for_sms_code_stream_polls_like_wait_for_sms_code() {
+        use futures::StreamExt;
+
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .all_sms_codes([])
+            .cancel_response(Ok(()))
+            .build();
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .country_timeout(Alpha2::UA.to_country(), Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .use_full_sms_list(true)
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+
+        let task = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        let mut polls: Vec<_> = service
+            .wait_for_sms_code_stream(&task.task_id)
+            .collect()
+            .await;
+
+        // The country timeout applies and the full SMS list is polled
+        assert!(matches!(
+            polls.pop(),
+            Some(Err(SmsSolverServiceError::SmsTimeout { timeout, .. }))
+                if timeout == Duration::from_millis(50)
+        ));
+        assert_eq!(provider.call_count_get_sms_code() as usize, polls.len());
+        assert!(service.task_context(&task.task_id).country.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_timeout() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .cancel_response(Ok(()))
+            .build();
+
+        // Very short timeout, SMS never arrives
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider, config);
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        let err = service
+            .wait_for_sms_code(&result.task_id)
+            .await
+            .unwrap_err();
+
+        match err {
+            SmsSolverServiceError::SmsTimeout {
+                timeout,
+                poll_count,
+                task_id,
+                ..
+            } => {
+                assert_eq!(timeout, Duration::from_millis(50));
+                assert!(poll_count > 0);
+                assert_eq!(task_id.as_ref(), "task123");
+            }
+            _ => panic!("Expected SmsTimeout error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_for_country_uses_country_timeout() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .cancel_response(Ok(()))
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .country_timeout(Alpha2::UA.to_country(), Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider, config);
+
+        let task = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        let started = Instant::now();
+        let err = service
+            .wait_for_sms_code_for_country(&task, CancellationToken::new())
+            .await
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        match err {
+            SmsSolverServiceError::SmsTimeout { timeout, .. } => {
+                assert_eq!(timeout, Duration::from_millis(50));
+            }
+            _ => panic!("Expected SmsTimeout error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_uses_country_of_acquired_number() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .cancel_response(Ok(()))
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .country_timeout(Alpha2::UA.to_country(), Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider, config);
+
+        let task = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        let err = service.wait_for_sms_code(&task.task_id).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::SmsTimeout { timeout, .. } if timeout == Duration::from_millis(50)
+        ));
+        // Polling failed, so the context is gone and the global timeout applies
+        assert!(service.task_context(&task.task_id).country.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finish_forgets_task_context() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+        let service = SmsSolverService::with_provider(provider);
+
+        let task = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.task_context(&task.task_id).country.as_deref(),
+            Some("UA")
+        );
+
+        service.finish_activation(&task.task_id).await.unwrap();
+        assert!(service.task_context(&task.task_id).country.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_cancellation() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .cancel_response(Ok(()))
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider, config);
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        let cancel_token = CancellationToken::new();
+        let token_clone = cancel_token.clone();
+
+        // Cancel immediately
+        token_clone.cancel();
+
+        let err = service
+            .wait_for_sms_code_cancellable(&result.task_id, cancel_token)
+            .await
+            .unwrap_err();
+
+        match err {
+            SmsSolverServiceError::Cancelled {
+                poll_count,
+                task_id,
+                ..
+            } => {
+                assert_eq!(poll_count, 0); // Cancelled before any polls
+                assert_eq!(task_id.as_ref(), "task123");
+            }
+            _ => panic!("Expected Cancelled error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_failure_on_timeout() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .cancel_response(Err(MockError::Permanent("Cancel failed".into())))
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider, config);
+
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        let err = service
+            .wait_for_sms_code(&result.task_id)
+            .await
+            .unwrap_err();
+
+        match err {
+            SmsSolverServiceError::CancelFailed { task_id, message } => {
+                assert_eq!(task_id.as_ref(), "task123");
+                assert!(message.contains("Cancel failed"));
+            }
+            _ => panic!("Expected CancelFailed error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_builder() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .build();
+
+        let service = SmsSolverService::builder(provider)
+            .timeout(Duration::from_secs(90))
+            .poll_interval(Duration::from_secs(5))
+            .build();
+
+        assert_eq!(service.config().timeout, Duration::from_secs(90));
+        assert_eq!(service.config().poll_interval, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_deadline_times_out() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .get_number_delay(Duration::from_millis(200))
+            .build();
+        let service = SmsSolverService::with_provider(provider);
+
+        let err = service
+            .get_number_with_deadline(
+                Alpha2::UA.to_country(),
+                MockService,
+                Instant::now() + Duration::from_millis(20),
+            )
+            .await
+            .unwrap_err();
+
+        match err {
+            SmsSolverServiceError::AcquisitionTimeout {
+                country,
+                service_name,
+                ..
+            } => {
+                assert_eq!(country.alpha2(), Alpha2::UA);
+                assert!(service_name.ends_with("MockService"));
+            }
+            _ => panic!("Expected AcquisitionTimeout error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_number_with_deadline_cancels_late_number() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .get_number_delay(Duration::from_millis(100))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+
+        let result = service
+            .get_number_with_deadline(
+                Alpha2::UA.to_country(),
+                MockService,
+                Instant::now() + Duration::from_millis(10),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(SmsSolverServiceError::AcquisitionTimeout { .. })
+        ));
+        assert_eq!(provider.cancelled_tasks().len(), 0);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_uses_default_deadline() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .get_number_delay(Duration::from_secs(120))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+        let started = tokio::time::Instant::now();
+
+        let err = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::AcquisitionTimeout { .. }
+        ));
+        assert!(started.elapsed() < Duration::from_secs(120));
+
+        tokio::time::sleep(Duration::from_secs(120)).await;
+        assert_eq!(provider.cancelled_tasks().len(), 1);
     }
 
-    /// Set the polling interval when waiting for SMS codes.
-    ///
-    /// Default: 3 seconds
-    pub fn poll_interval(mut self, interval: std::time::Duration) -> Self {
-        self.config_builder = self.config_builder.poll_interval(interval);
-        self
+    #[tokio::test]
+    async fn test_account_reputation_unsupported() {
+        let service = SmsSolverService::with_provider(MockProvider::new());
+        assert_eq!(service.account_reputation().await.unwrap(), None);
     }
 
-    /// Set the full configuration.
-    pub fn config(mut self, config: SmsSolverServiceConfig) -> Self {
-        self.config_builder = SmsSolverServiceConfigBuilder {
-            timeout: config.timeout,
-            poll_interval: config.poll_interval,
-        };
-        self
+    #[tokio::test]
+    async fn test_service_builder_validation() {
+        let err = SmsSolverService::builder(MockProvider::new())
+            .timeout(Duration::from_secs(1))
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            SmsSolverServiceBuildError::Config(ConfigError::TimeoutTooShort { .. })
+        ));
+
+        let err = SmsSolverService::builder(
+            MockProvider::builder()
+                .warmup_response(Err(MockError::Permanent("bad key".into())))
+                .build(),
+        )
+        .build_warmed_up()
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(err, SmsSolverServiceBuildError::Warmup(_)));
     }
 
-    /// Build the SmsSolverService.
-    pub fn build(self) -> SmsSolverService<P> {
-        SmsSolverService::new(self.provider, self.config_builder.build())
+    #[test]
+    #[should_panic(expected = "Invalid SmsSolverService configuration")]
+    fn test_service_builder_panics_on_invalid_config() {
+        SmsSolverService::builder(MockProvider::new())
+            .poll_interval(Duration::from_millis(1))
+            .build();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::errors::RetryableError;
-    use crate::types::FullNumber;
-    use keshvar::Alpha2;
-    use std::sync::Arc;
-    use std::sync::atomic::{AtomicU32, Ordering};
-    use std::time::Duration;
-    use thiserror::Error;
+    #[tokio::test]
+    async fn test_service_with_config_presets() {
+        let provider = MockProvider::new();
 
-    // Mock provider for testing
-    #[derive(Clone)]
-    #[allow(clippy::type_complexity)]
-    struct MockProvider {
-        get_number_result: Arc<std::sync::Mutex<Option<Result<(TaskId, FullNumber), MockError>>>>,
-        sms_code_results: Arc<std::sync::Mutex<Vec<Result<Option<SmsCode>, MockError>>>>,
-        cancel_result: Arc<std::sync::Mutex<Option<Result<(), MockError>>>>,
-        poll_count: Arc<AtomicU32>,
-    }
+        let fast_service = SmsSolverService::new(provider.clone(), SmsSolverServiceConfig::fast());
+        assert_eq!(fast_service.config().timeout, Duration::from_secs(60));
+        assert_eq!(fast_service.config().poll_interval, Duration::from_secs(1));
 
-    #[derive(Debug, Clone, Error)]
-    #[allow(dead_code)]
-    enum MockError {
-        #[error("Mock error: {0}")]
-        Generic(String),
-        #[error("Transient error")]
-        Transient,
+        let patient_service =
+            SmsSolverService::new(provider.clone(), SmsSolverServiceConfig::patient());
+        assert_eq!(patient_service.config().timeout, Duration::from_secs(300));
+        assert_eq!(
+            patient_service.config().poll_interval,
+            Duration::from_secs(5)
+        );
     }
 
-    impl RetryableError for MockError {
-        fn is_retryable(&self) -> bool {
-            matches!(self, MockError::Transient)
+    #[tokio::test]
+    async fn test_new_warmed_up() {
+        let service =
+            SmsSolverService::new_warmed_up(MockProvider::new(), SmsSolverServiceConfig::fast())
+                .await;
+        assert!(service.is_ok());
+
+        let provider = MockProvider::builder()
+            .warmup_response(Err(MockError::Permanent("not ready".into())))
+            .build();
+        match SmsSolverService::new_warmed_up(provider, SmsSolverServiceConfig::fast()).await {
+            Err(err) => assert!(err.to_string().contains("not ready")),
+            Ok(_) => panic!("Expected warmup error"),
         }
     }
 
-    #[derive(Clone)]
-    struct MockService;
-
-    impl MockProvider {
-        fn new() -> Self {
-            Self {
-                get_number_result: Arc::new(std::sync::Mutex::new(None)),
-                sms_code_results: Arc::new(std::sync::Mutex::new(Vec::new())),
-                cancel_result: Arc::new(std::sync::Mutex::new(None)),
-                poll_count: Arc::new(AtomicU32::new(0)),
-            }
-        }
+    #[tokio::test]
+    async fn test_wait_for_sms_code_with_validator_skips_invalid() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("PROMO")
+            .sms_code("PROMO")
+            .sms_code("654321")
+            .build();
 
-        fn with_number(self, task_id: &str, number: &str) -> Self {
-            *self.get_number_result.lock().unwrap() =
-                Some(Ok((TaskId::new(task_id), FullNumber::new(number))));
-            self
-        }
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_millis(10))
+            .build();
 
-        fn with_sms_after_polls(self, polls: u32, code: &str) -> Self {
-            {
-                let mut results = self.sms_code_results.lock().unwrap();
-                for _ in 0..polls {
-                    results.push(Ok(None));
-                }
-                results.push(Ok(Some(SmsCode::new(code))));
-            }
-            self
-        }
+        let service = SmsSolverService::new(provider.clone(), config);
 
-        fn with_cancel_success(self) -> Self {
-            *self.cancel_result.lock().unwrap() = Some(Ok(()));
-            self
-        }
+        let code = service
+            .wait_for_sms_code_with_validator(&TaskId::new("task123"), |code| {
+                code.as_str().chars().all(|c| c.is_ascii_digit())
+            })
+            .await
+            .unwrap();
 
-        fn with_cancel_error(self, msg: &str) -> Self {
-            *self.cancel_result.lock().unwrap() = Some(Err(MockError::Generic(msg.to_string())));
-            self
-        }
+        assert_eq!(code.as_str(), "654321");
+        assert_eq!(provider.call_count_get_sms_code(), 3);
     }
 
-    impl Provider for MockProvider {
-        type Error = MockError;
-        type Service = MockService;
+    #[tokio::test]
+    async fn test_wait_for_sms_code_with_validator_full_sms_list() {
+        // The latest SMS is a promo; the OTP arrived before it
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("PROMO")
+            .all_sms_codes([
+                (SmsCode::new("654321"), format!("Your code is {}", "654321")),
+                (SmsCode::new("PROMO"), format!("Your code is {}", "PROMO")),
+            ])
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_millis(10))
+            .use_full_sms_list(true)
+            .build();
 
-        async fn get_phone_number(
-            &self,
-            _country: Country,
-            _service: Self::Service,
-        ) -> Result<(TaskId, FullNumber), Self::Error> {
-            self.get_number_result
-                .lock()
-                .unwrap()
-                .clone()
-                .unwrap_or(Err(MockError::Generic("Not configured".to_string())))
-        }
+        let service = SmsSolverService::new(provider.clone(), config);
 
-        async fn get_sms_code(&self, _task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
-            let idx = self.poll_count.fetch_add(1, Ordering::SeqCst) as usize;
-            let results = self.sms_code_results.lock().unwrap();
-            results.get(idx).cloned().unwrap_or(Ok(None))
-        }
+        let code = service
+            .wait_for_sms_code_with_validator(&TaskId::new("task123"), |code| {
+                code.as_str().chars().all(|c| c.is_ascii_digit())
+            })
+            .await
+            .unwrap();
 
-        async fn finish_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
-            Ok(())
-        }
+        assert_eq!(code.as_str(), "654321");
+        assert_eq!(provider.call_count_get_sms_code(), 1);
 
-        async fn cancel_activation(&self, _task_id: &TaskId) -> Result<(), Self::Error> {
-            self.cancel_result.lock().unwrap().clone().unwrap_or(Ok(()))
-        }
+        let (code, text) = service
+            .wait_for_sms_code_with_text(&TaskId::new("task123"))
+            .await
+            .unwrap();
+        assert_eq!(code.as_str(), "PROMO");
+        assert_eq!(text, "Your code is PROMO");
     }
 
     #[tokio::test]
-    async fn test_wait_for_sms_code_success() {
-        let provider = MockProvider::new()
-            .with_number("task123", "380501234567")
-            .with_sms_after_polls(2, "123456");
+    async fn test_full_sms_list_falls_back_to_latest_sms() {
+        // The mock cannot list every SMS, like providers using the default
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_after_polls(2, "123456")
+            .build();
 
         let config = SmsSolverServiceConfig::builder()
             .timeout(Duration::from_secs(60))
             .poll_interval(Duration::from_millis(10))
+            .use_full_sms_list(true)
             .build();
 
-        let service = SmsSolverService::new(provider.clone(), config);
+        let service = SmsSolverService::new(provider, config);
+
+        let code = service
+            .wait_for_sms_code(&TaskId::new("task123"))
+            .await
+            .unwrap();
+        assert_eq!(code.as_str(), "123456");
+    }
+
+    #[tokio::test]
+    async fn test_get_number_rejects_sanctioned_country() {
+        let provider = MockProvider::builder()
+            .number("task123", "5351234567")
+            .build();
+
+        let service = SmsSolverService::with_provider(provider.clone());
+        let err = service
+            .get_number(Alpha2::CU.to_country(), MockService)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::CountryRestricted { ref country, .. }
+                if country.alpha2() == Alpha2::CU
+        ));
+        assert!(!err.should_retry_operation());
 
+        let service = SmsSolverService::builder(provider)
+            .allow_restricted_countries(true)
+            .build();
         let result = service
-            .get_number(Alpha2::UA.to_country(), MockService)
+            .get_number(Alpha2::CU.to_country(), MockService)
             .await
             .unwrap();
         assert_eq!(result.task_id.as_ref(), "task123");
+    }
 
-        let code = service.wait_for_sms_code(&result.task_id).await.unwrap();
-        assert_eq!(code.as_str(), "123456");
+    #[tokio::test]
+    async fn test_get_number_idempotent_shares_result() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .get_number_delay(Duration::from_millis(50))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
 
-        // Should have polled 3 times (2 None + 1 Some)
-        assert_eq!(provider.poll_count.load(Ordering::SeqCst), 3);
+        let (first, second, other) = tokio::join!(
+            service.get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua"),
+            service.get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua"),
+            service.get_number_idempotent(Alpha2::UA.to_country(), MockService, "other"),
+        );
+        assert_eq!(first.unwrap().task_id, second.unwrap().task_id);
+        assert!(other.is_ok());
+        assert_eq!(provider.call_count_get_number(), 2);
+
+        // The entry is removed once the call completes.
+        service
+            .get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua")
+            .await
+            .unwrap();
+        assert_eq!(provider.call_count_get_number(), 3);
     }
 
     #[tokio::test]
-    async fn test_wait_for_sms_code_timeout() {
-        let provider = MockProvider::new()
-            .with_number("task123", "380501234567")
-            .with_cancel_success();
+    async fn test_get_number_idempotent_shares_error() {
+        let provider = MockProvider::builder()
+            .get_number_response(Err(MockError::Permanent("NO_NUMBERS".into())))
+            .get_number_delay(Duration::from_millis(50))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
 
-        // Very short timeout, SMS never arrives
+        let (first, second) = tokio::join!(
+            service.get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua"),
+            service.get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua"),
+        );
+        let (first, second) = (first.unwrap_err(), second.unwrap_err());
+        assert!(matches!(first, SmsSolverServiceError::Shared(_)));
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(provider.call_count_get_number(), 1);
+
+        // A call nobody waited on fails with the same error shape.
+        let err = service
+            .get_number_idempotent(Alpha2::UA.to_country(), MockService, "ua")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SmsSolverServiceError::Shared(_)));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_any_sms_code_batch() {
+        let provider = MockProvider::builder()
+            .sms_code_response(Err(MockError::Permanent("NO_ACTIVATION".into())))
+            .sms_after_polls(2, "654321")
+            .build();
         let config = SmsSolverServiceConfig::builder()
-            .timeout(Duration::from_millis(50))
             .poll_interval(Duration::from_millis(10))
             .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let task_ids = [
+            TaskId::new("task1"),
+            TaskId::new("task2"),
+            TaskId::new("task3"),
+        ];
+
+        let (task_id, code) = service
+            .wait_for_any_sms_code_batch(&task_ids)
+            .await
+            .unwrap();
 
-        let service = SmsSolverService::new(provider, config);
+        // First poll: task1 fails, task2 and task3 have no SMS yet;
+        // second poll: task2 gets the code
+        assert_eq!(task_id, task_ids[1]);
+        assert_eq!(code.as_ref(), "654321");
+        assert_eq!(provider.call_count_get_sms_code(), 5);
+        assert_eq!(provider.cancelled_tasks(), vec![task_ids[0].clone()]);
+        assert_eq!(service.statistics().codes_received, 1);
+        assert_eq!(service.statistics().errors, 1);
+    }
 
-        let result = service
-            .get_number(Alpha2::UA.to_country(), MockService)
+    #[tokio::test]
+    async fn test_wait_for_any_sms_code_batch_all_failed() {
+        let provider = MockProvider::builder()
+            .sms_code_response(Err(MockError::Permanent("NO_ACTIVATION".into())))
+            .sms_code_response(Err(MockError::Permanent("BANNED".into())))
+            .build();
+        let service = SmsSolverService::with_provider(provider.clone());
+        let task_ids = [TaskId::new("task1"), TaskId::new("task2")];
+
+        let err = service
+            .wait_for_any_sms_code_batch(&task_ids)
             .await
-            .unwrap();
+            .unwrap_err();
+
+        assert!(matches!(err, SmsSolverServiceError::Provider { .. }));
+        assert!(err.to_string().contains("BANNED"));
+        assert_eq!(provider.cancelled_tasks(), task_ids.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_any_sms_code_batch_empty() {
+        let service = SmsSolverService::with_provider(MockProvider::new());
+
+        let err = service.wait_for_any_sms_code_batch(&[]).await.unwrap_err();
+
+        assert!(matches!(err, SmsSolverServiceError::InvalidArgument { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_any_sms_code_batch_timeout() {
+        let provider = MockProvider::builder()
+            .sms_code_response(Err(MockError::Transient))
+            .build();
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_millis(50))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        let service = SmsSolverService::new(provider.clone(), config);
+        let task_ids = [TaskId::new("task1"), TaskId::new("task2")];
 
         let err = service
-            .wait_for_sms_code(&result.task_id)
+            .wait_for_any_sms_code_batch(&task_ids)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            SmsSolverServiceError::SmsTimeout { ref task_id, .. } if *task_id == task_ids[0]
+        ));
+        assert_eq!(provider.cancelled_tasks(), task_ids.to_vec());
+        assert_eq!(service.statistics().timeouts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all() {
+        let service = SmsSolverService::with_provider(MockProvider::new());
+        let task_ids = [TaskId::new("task1"), TaskId::new("task2")];
+
+        let results = service.cancel_all(&task_ids).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].task_id, task_ids[0]);
+        assert_eq!(results[1].task_id, task_ids[1]);
+        assert!(results.iter().all(|r| r.result.is_ok()));
+
+        let service = SmsSolverService::with_provider(
+            MockProvider::builder()
+                .cancel_response(Err(MockError::Permanent("offline".into())))
+                .build(),
+        );
+        let results = service.cancel_all(&task_ids).await;
+        assert!(results.iter().all(|r| matches!(
+            &r.result,
+            Err(SmsSolverServiceError::CancelFailed { task_id, .. }) if *task_id == r.task_id
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_with_validator_no_valid_code() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_code("PROMO")
+            .sms_code("PROMO")
+            .sms_code("NEWS")
+            .cancel_response(Ok(()))
+            .build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_millis(100))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider, config);
+
+        let err = service
+            .wait_for_sms_code_with_validator(&TaskId::new("task123"), |code| {
+                code.as_str().chars().all(|c| c.is_ascii_digit())
+            })
             .await
             .unwrap_err();
 
         match err {
-            SmsSolverServiceError::SmsTimeout {
-                timeout,
-                poll_count,
+            SmsSolverServiceError::NoValidCodeReceived {
+                codes_seen,
                 task_id,
-                ..
             } => {
-                assert_eq!(timeout, Duration::from_millis(50));
-                assert!(poll_count > 0);
+                assert_eq!(codes_seen, 2);
                 assert_eq!(task_id.as_ref(), "task123");
             }
-            _ => panic!("Expected SmsTimeout error, got {:?}", err),
+            _ => panic!("Expected NoValidCodeReceived error, got {:?}", err),
         }
     }
 
     #[tokio::test]
-    async fn test_wait_for_sms_code_cancellation() {
-        let provider = MockProvider::new()
-            .with_number("task123", "380501234567")
-            .with_cancel_success();
+    async fn test_statistics() {
+        let provider = MockProvider::builder()
+            .number("task123", "380501234567")
+            .sms_after_polls(1, "123456")
+            .cancel_response(Ok(()))
+            .metadata(ActivationMetadata {
+                cost: Some(10.5),
+                ..Default::default()
+            })
+            .build();
 
         let config = SmsSolverServiceConfig::builder()
-            .timeout(Duration::from_secs(60))
+            .timeout(Duration::from_millis(200))
             .poll_interval(Duration::from_millis(10))
             .build();
 
         let service = SmsSolverService::new(provider, config);
+        assert_eq!(service.statistics(), ServiceStatistics::default());
+
+        // Successful session
+        let result = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        service.wait_for_sms_code(&result.task_id).await.unwrap();
 
+        // Second number that times out
         let result = service
             .get_number(Alpha2::UA.to_country(), MockService)
             .await
             .unwrap();
+        assert!(service.wait_for_sms_code(&result.task_id).await.is_err());
 
+        // Third number that gets cancelled
         let cancel_token = CancellationToken::new();
-        let token_clone = cancel_token.clone();
+        cancel_token.cancel();
+        assert!(
+            service
+                .wait_for_sms_code_cancellable(&result.task_id, cancel_token)
+                .await
+                .is_err()
+        );
 
-        // Cancel immediately
-        token_clone.cancel();
+        let stats = service.statistics();
+        assert_eq!(stats.numbers_acquired, 2);
+        assert_eq!(stats.codes_received, 1);
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.cancellations, 1);
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.total_spend, Some(21.0));
+        assert_eq!(service.statistics_snapshot(), stats);
+
+        service.reset_statistics();
+        assert_eq!(service.statistics(), ServiceStatistics::default());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sms_code_with_text() {
+        let provider = MockProvider::builder().sms_after_polls(1, "123456").build();
+
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(60))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+
+        let service = SmsSolverService::new(provider, config);
+
+        let (code, text) = service
+            .wait_for_sms_code_with_text(&TaskId::from("task123"))
+            .await
+            .unwrap();
+        assert_eq!(code.as_str(), "123456");
+        // The mock provider has no message text, so the default falls back to the code
+        assert_eq!(text, "123456");
+    }
+
+    #[tokio::test]
+    async fn test_live_polling_error_with_cancel_failure_keeps_task_id() {
+        let provider = MockProvider::builder()
+            .sms_code_response(Err(MockError::LiveTask(TaskId::from("task123"))))
+            .cancel_response(Err(MockError::Permanent("network down".into())))
+            .build();
+        let service = SmsSolverService::with_provider(provider);
 
         let err = service
-            .wait_for_sms_code_cancellable(&result.task_id, cancel_token)
+            .wait_for_sms_code(&TaskId::from("task123"))
             .await
             .unwrap_err();
 
         match err {
-            SmsSolverServiceError::Cancelled {
-                poll_count,
-                task_id,
+            SmsSolverServiceError::PhoneAcquiredButPollingFailed {
+                ref task_id,
+                ref source,
                 ..
             } => {
-                assert_eq!(poll_count, 0); // Cancelled before any polls
                 assert_eq!(task_id.as_ref(), "task123");
+                assert!(source.to_string().contains("live task"));
             }
-            _ => panic!("Expected Cancelled error, got {:?}", err),
+            _ => panic!(
+                "Expected PhoneAcquiredButPollingFailed error, got {:?}",
+                err
+            ),
         }
+        assert_eq!(err.live_task_id(), Some(&TaskId::from("task123")));
+        assert!(!err.is_retryable());
     }
 
     #[tokio::test]
-    async fn test_cancel_failure_on_timeout() {
-        let provider = MockProvider::new()
-            .with_number("task123", "380501234567")
-            .with_cancel_error("Cancel failed");
+    async fn test_polling_error_with_cancel_failure_reports_cancel_failed() {
+        let provider = MockProvider::builder()
+            .sms_code_response(Err(MockError::Permanent("NO_ACTIVATION".into())))
+            .cancel_response(Err(MockError::Permanent("network down".into())))
+            .build();
 
         let config = SmsSolverServiceConfig::builder()
-            .timeout(Duration::from_millis(50))
+            .timeout(Duration::from_secs(60))
             .poll_interval(Duration::from_millis(10))
             .build();
 
         let service = SmsSolverService::new(provider, config);
 
-        let result = service
-            .get_number(Alpha2::UA.to_country(), MockService)
-            .await
-            .unwrap();
-
         let err = service
-            .wait_for_sms_code(&result.task_id)
+            .wait_for_sms_code(&TaskId::from("task123"))
             .await
             .unwrap_err();
 
         match err {
-            SmsSolverServiceError::CancelFailed { task_id, message } => {
+            SmsSolverServiceError::CancelFailed {
+                ref task_id,
+                ref message,
+            } => {
                 assert_eq!(task_id.as_ref(), "task123");
-                assert!(message.contains("Cancel failed"));
+                assert!(message.contains("network down"));
             }
             _ => panic!("Expected CancelFailed error, got {:?}", err),
         }
-    }
-
-    #[tokio::test]
-    async fn test_service_builder() {
-        let provider = MockProvider::new().with_number("task123", "380501234567");
-
-        let service = SmsSolverService::builder(provider)
-            .timeout(Duration::from_secs(90))
-            .poll_interval(Duration::from_secs(5))
-            .build();
-
-        assert_eq!(service.config().timeout, Duration::from_secs(90));
-        assert_eq!(service.config().poll_interval, Duration::from_secs(5));
-    }
-
-    #[tokio::test]
-    async fn test_service_with_config_presets() {
-        let provider = MockProvider::new();
-
-        let fast_service = SmsSolverService::new(provider.clone(), SmsSolverServiceConfig::fast());
-        assert_eq!(fast_service.config().timeout, Duration::from_secs(60));
-        assert_eq!(fast_service.config().poll_interval, Duration::from_secs(1));
-
-        let patient_service =
-            SmsSolverService::new(provider.clone(), SmsSolverServiceConfig::patient());
-        assert_eq!(patient_service.config().timeout, Duration::from_secs(300));
-        assert_eq!(
-            patient_service.config().poll_interval,
-            Duration::from_secs(5)
-        );
+        assert!(!err.is_retryable());
     }
 }
@@ -1,12 +1,15 @@
 //! Service trait definition.
 
 use crate::errors::RetryableError;
-use crate::types::{SmsCode, SmsTaskResult, TaskId};
+use crate::types::{CountryAvailability, SmsCode, SmsTaskResult, TaskId};
 use keshvar::Country;
 use std::error::Error as StdError;
 use std::future::Future;
 use tokio_util::sync::CancellationToken;
 
+#[cfg(feature = "streams")]
+use futures::Stream;
+
 /// Trait for SMS verification service implementations.
 ///
 /// This trait abstracts the service interface, allowing different
@@ -40,6 +43,35 @@ pub trait SmsSolverServiceTrait: Send + Sync {
         service: Self::Service,
     ) -> impl Future<Output = Result<SmsTaskResult, Self::Error>> + Send;
 
+    /// Get a phone number, passing a client-chosen idempotency key to the
+    /// provider.
+    ///
+    /// Only providers implementing
+    /// [`SupportsIdempotencyKey`](crate::SupportsIdempotencyKey) use the key.
+    /// Default implementation ignores it and calls
+    /// [`get_number`](Self::get_number).
+    fn get_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> impl Future<Output = Result<SmsTaskResult, Self::Error>> + Send {
+        let _ = idempotency_key;
+        self.get_number(country, service)
+    }
+
+    /// Request numbers for several countries and return the first one
+    /// acquired.
+    ///
+    /// `countries` is in priority order. When `cancel_on_first` is true,
+    /// numbers acquired by the other requests are cancelled; otherwise they
+    /// stay active. Fails if `countries` is empty or every request fails.
+    fn get_number_from_any(
+        &self,
+        countries: Vec<(Country, Self::Service)>,
+        cancel_on_first: bool,
+    ) -> impl Future<Output = Result<SmsTaskResult, Self::Error>> + Send;
+
     /// Wait for an SMS code to be received.
     ///
     /// This method polls the provider until an SMS code is received
@@ -96,4 +128,159 @@ pub trait SmsSolverServiceTrait: Send + Sync {
         task_id: &TaskId,
         cancel_token: CancellationToken,
     ) -> impl Future<Output = Result<SmsCode, Self::Error>> + Send;
+
+    /// Wait for an SMS code and return it along with the full message text.
+    ///
+    /// Useful when OTP parsing rules are specific to the target service.
+    ///
+    /// Default implementation calls [`SmsSolverServiceTrait::wait_for_sms_code`]
+    /// and uses the code itself as the text.
+    fn wait_for_sms_code_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<(SmsCode, String), Self::Error>> + Send {
+        async move {
+            let code = self.wait_for_sms_code(task_id).await?;
+            let text = code.to_string();
+            Ok((code, text))
+        }
+    }
+
+    /// Poll for an SMS code, yielding the result of each poll.
+    ///
+    /// Yields `Ok(None)` while waiting and `Ok(Some(code))` once a code
+    /// arrives. The stream ends after the code, or after yielding an error
+    /// such as a timeout or permanent failure, in which case the activation
+    /// is cancelled first.
+    ///
+    /// Default implementation yields the result of
+    /// [`wait_for_sms_code`](Self::wait_for_sms_code) as a single item.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use futures::StreamExt;
+    ///
+    /// let mut polls = std::pin::pin!(service.wait_for_sms_code_stream(&task_id));
+    /// while let Some(poll) = polls.next().await {
+    ///     match poll? {
+    ///         Some(code) => println!("Got code: {}", code),
+    ///         None => println!("Still waiting..."),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "streams")]
+    fn wait_for_sms_code_stream<'a>(
+        &'a self,
+        task_id: &'a TaskId,
+    ) -> impl Stream<Item = Result<Option<SmsCode>, Self::Error>> + Send + 'a {
+        async_stream::stream! {
+            yield self.wait_for_sms_code(task_id).await.map(Some);
+        }
+    }
+
+    /// Mark the activation as successfully completed.
+    fn finish_activation(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Cancel the activation.
+    fn cancel_activation(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Whether `error`, returned while waiting for a code, means the
+    /// activation was already cancelled or has ended.
+    ///
+    /// [`get_number_and_wait`](Self::get_number_and_wait) skips its own
+    /// cancellation in that case. Default implementation returns `false`.
+    fn is_activation_released(&self, error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
+
+    /// Ask the provider to send another SMS code for the activation.
+    ///
+    /// Returns `Ok(false)` if the provider does not support it. Default
+    /// implementation returns `Ok(false)`.
+    fn request_another_sms_code(
+        &self,
+        task_id: &TaskId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        let _ = task_id;
+        async { Ok(false) }
+    }
+
+    /// Get the countries that currently have numbers for a service, with
+    /// their stock and lowest price.
+    ///
+    /// Useful to show the available countries before buying a number.
+    /// Default implementation returns an empty list.
+    fn get_available_countries(
+        &self,
+        service: &Self::Service,
+    ) -> impl Future<Output = Result<Vec<CountryAvailability>, Self::Error>> + Send {
+        let _ = service;
+        async { Ok(Vec::new()) }
+    }
+
+    /// Whether [`get_number_and_wait`](Self::get_number_and_wait) finishes
+    /// the activation after receiving a code.
+    ///
+    /// Default implementation returns `true`.
+    fn auto_finish(&self) -> bool {
+        true
+    }
+
+    /// Get a phone number and wait for its SMS code.
+    ///
+    /// If waiting fails, the activation is cancelled before the error is
+    /// returned, unless [`is_activation_released`](Self::is_activation_released)
+    /// says the wait already released it. On success the activation is finished if
+    /// [`auto_finish`](Self::auto_finish) is true.
+    fn get_number_and_wait(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> impl Future<Output = Result<(SmsTaskResult, SmsCode), Self::Error>> + Send {
+        self.get_number_and_wait_cancellable(country, service, CancellationToken::new())
+    }
+
+    /// Get a phone number and wait for its SMS code with cancellation support.
+    ///
+    /// Behaves like [`get_number_and_wait`](Self::get_number_and_wait), using
+    /// [`wait_for_sms_code_cancellable`](Self::wait_for_sms_code_cancellable)
+    /// to wait.
+    fn get_number_and_wait_cancellable(
+        &self,
+        country: Country,
+        service: Self::Service,
+        cancel_token: CancellationToken,
+    ) -> impl Future<Output = Result<(SmsTaskResult, SmsCode), Self::Error>> + Send {
+        async move {
+            let task = self.get_number(country, service).await?;
+
+            let code = match self
+                .wait_for_sms_code_cancellable(&task.task_id, cancel_token)
+                .await
+            {
+                Ok(code) => code,
+                Err(e) => {
+                    if !self.is_activation_released(&e) {
+                        // Best effort: the original error is more useful
+                        let _ = self.cancel_activation(&task.task_id).await;
+                    }
+                    return Err(e);
+                }
+            };
+
+            if self.auto_finish() {
+                self.finish_activation(&task.task_id).await?;
+            }
+
+            Ok((task, code))
+        }
+    }
 }
@@ -0,0 +1,606 @@
+//! Mock provider for testing code built on top of this crate.
+//!
+//! Enable the `testing` feature in your dev-dependencies:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! sms-solvers = { git = "https://github.com/rlgrpe/sms-solvers.git", features = ["testing"] }
+//! ```
+//!
+//! [`MockProvider`] implements [`Provider`] without any network access. By
+//! default every `get_phone_number` call succeeds with a new task ID and a
+//! number in the requested country, polls return no SMS, and finishing or
+//! cancelling succeeds. Responses can be changed up front with
+//! [`MockProviderBuilder`] or at any time through the `set_*` methods, and
+//! call counts can be checked afterwards. Clones share the same state.
+//!
+//! # Example
+//!
+//! In your own crate's tests:
+//!
+//! ```rust,ignore
+//! use sms_solvers::testing::{MockError, MockProvider, MockService};
+//! use sms_solvers::{Alpha2, SmsSolverService, SmsSolverServiceTrait};
+//!
+//! #[tokio::test]
+//! async fn signup_uses_received_code() {
+//!     let provider = MockProvider::builder()
+//!         .sms_after_polls(2, "482913")
+//!         .build();
+//!     let service = SmsSolverService::with_provider(provider.clone());
+//!
+//!     let (task, code) = service
+//!         .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+//!         .await
+//!         .unwrap();
+//!
+//!     assert_eq!(code.as_str(), "482913");
+//!     assert_eq!(provider.call_count_get_number(), 1);
+//! }
+//!
+//! #[tokio::test]
+//! async fn signup_reports_provider_errors() {
+//!     let provider = MockProvider::new();
+//!     provider.set_get_number_response(Err(MockError::Permanent("NO_BALANCE".into())));
+//!     let service = SmsSolverService::with_provider(provider);
+//!
+//!     assert!(service.get_number(Alpha2::UA.to_country(), MockService).await.is_err());
+//! }
+//! ```
+//!
+//! Without a test harness:
+//!
+//! ```rust
+//! use sms_solvers::testing::{MockProvider, MockService};
+//! use sms_solvers::{Alpha2, Provider, SmsCode, TaskId};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let provider = MockProvider::builder().sms_code("123456").build();
+//!
+//! let (task_id, full_number) = provider
+//!     .get_phone_number(Alpha2::UA.to_country(), MockService)
+//!     .await
+//!     .unwrap();
+//! assert_eq!(task_id, TaskId::new("mock-task-1"));
+//! assert!(full_number.as_str().starts_with("380"));
+//!
+//! assert_eq!(
+//!     provider.get_sms_code(&task_id).await.unwrap(),
+//!     Some(SmsCode::new("123456"))
+//! );
+//! assert_eq!(provider.call_count_get_sms_code(), 1);
+//! # }
+//! ```
+
+use crate::errors::RetryableError;
+use crate::providers::Provider;
+use crate::types::{ActivationMetadata, ActivationState, DialCode, FullNumber, SmsCode, TaskId};
+use keshvar::Country;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use thiserror::Error;
+
+/// National part of the numbers generated by default.
+const DEFAULT_NATIONAL_NUMBER: &str = "501234567";
+
+/// Result of a mocked `get_phone_number` call.
+pub type MockNumberResponse = Result<(TaskId, FullNumber), MockError>;
+
+/// Result of a mocked `get_sms_code` call.
+pub type MockSmsCodeResponse = Result<Option<SmsCode>, MockError>;
+
+/// Service type of [`MockProvider`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MockService;
+
+/// Errors returned by [`MockProvider`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MockError {
+    /// A permanent error with a custom message.
+    #[error("Mock error: {0}")]
+    Permanent(String),
+
+    /// A transient error that is worth retrying.
+    #[error("Mock transient error")]
+    Transient,
+
+    /// A permanent error that leaves the activation live.
+    #[error("Mock error with live task {0}")]
+    LiveTask(TaskId),
+}
+
+impl RetryableError for MockError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, MockError::Transient)
+    }
+
+    fn live_task_id(&self) -> Option<&TaskId> {
+        match self {
+            MockError::LiveTask(task_id) => Some(task_id),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    get_number_response: Option<MockNumberResponse>,
+    next_get_number_responses: VecDeque<MockNumberResponse>,
+    sms_code_responses: VecDeque<MockSmsCodeResponse>,
+    all_sms_codes: Option<Vec<(SmsCode, String)>>,
+    cancel_response: Option<Result<(), MockError>>,
+    finish_response: Option<Result<(), MockError>>,
+    warmup_response: Option<Result<(), MockError>>,
+    task_alive_response: Option<Result<bool, MockError>>,
+    activation_state: Option<ActivationState>,
+    metadata: Option<ActivationMetadata>,
+    get_number_delay: Duration,
+    /// Delay and response by country alpha-2 code, overriding the defaults.
+    country_numbers: HashMap<String, (Duration, MockNumberResponse)>,
+    get_number_calls: u32,
+    get_sms_code_calls: u32,
+    idempotency_keys: Vec<String>,
+    cancelled: Vec<TaskId>,
+    finished: Vec<TaskId>,
+}
+
+/// In-memory [`Provider`] with scripted responses.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, Default)]
+pub struct MockProvider {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockProvider {
+    /// Create a mock with the default responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder for a mock with custom responses.
+    pub fn builder() -> MockProviderBuilder {
+        MockProviderBuilder::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Return `response` from every following `get_phone_number` call.
+    pub fn set_get_number_response(&self, response: MockNumberResponse) {
+        self.lock().get_number_response = Some(response);
+    }
+
+    /// Wait `delay` before answering each following `get_phone_number` call.
+    pub fn set_get_number_delay(&self, delay: Duration) {
+        self.lock().get_number_delay = delay;
+    }
+
+    /// Return `metadata` along with every number requested from now on.
+    pub fn set_metadata(&self, metadata: Option<ActivationMetadata>) {
+        self.lock().metadata = metadata;
+    }
+
+    /// Return `responses` from the following `get_sms_code` calls, in order.
+    ///
+    /// Replaces responses not returned yet. Once they run out, polls
+    /// return `Ok(None)`.
+    pub fn set_sms_code_responses(&self, responses: Vec<MockSmsCodeResponse>) {
+        self.lock().sms_code_responses = responses.into();
+    }
+
+    /// Return `response` from every following `cancel_activation` call.
+    pub fn set_cancel_response(&self, response: Result<(), MockError>) {
+        self.lock().cancel_response = Some(response);
+    }
+
+    /// Return `response` from every following `finish_activation` call.
+    pub fn set_finish_response(&self, response: Result<(), MockError>) {
+        self.lock().finish_response = Some(response);
+    }
+
+    /// Number of `get_phone_number` calls so far.
+    pub fn call_count_get_number(&self) -> u32 {
+        self.lock().get_number_calls
+    }
+
+    /// Number of SMS polls so far.
+    ///
+    /// Counts `get_sms_code` calls, and `get_all_sms_codes_with_text` calls
+    /// answered from [`MockProviderBuilder::all_sms_codes`].
+    pub fn call_count_get_sms_code(&self) -> u32 {
+        self.lock().get_sms_code_calls
+    }
+
+    /// Idempotency keys passed to `get_phone_number_with_idempotency_key`,
+    /// in call order.
+    pub fn idempotency_keys(&self) -> Vec<String> {
+        self.lock().idempotency_keys.clone()
+    }
+
+    /// Task IDs passed to `cancel_activation`, in call order.
+    pub fn cancelled_tasks(&self) -> Vec<TaskId> {
+        self.lock().cancelled.clone()
+    }
+
+    /// Task IDs passed to `finish_activation`, in call order.
+    pub fn finished_tasks(&self) -> Vec<TaskId> {
+        self.lock().finished.clone()
+    }
+}
+
+impl Provider for MockProvider {
+    type Error = MockError;
+    type Service = MockService;
+
+    async fn get_phone_number(
+        &self,
+        country: Country,
+        _service: Self::Service,
+    ) -> Result<(TaskId, FullNumber), Self::Error> {
+        let (delay, response) = {
+            let mut state = self.lock();
+            state.get_number_calls += 1;
+            match state.country_numbers.get(&country.alpha2().to_string()) {
+                Some(country_number) => country_number.clone(),
+                None => {
+                    let queued = state.next_get_number_responses.pop_front();
+                    let response = queued.or_else(|| state.get_number_response.clone());
+                    let response = response.unwrap_or_else(|| {
+                        Ok((
+                            TaskId::new(format!("mock-task-{}", state.get_number_calls)),
+                            FullNumber::new(format!(
+                                "{}{DEFAULT_NATIONAL_NUMBER}",
+                                DialCode::from(&country).as_str()
+                            )),
+                        ))
+                    });
+                    (state.get_number_delay, response)
+                }
+            }
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        response
+    }
+
+    async fn get_phone_number_with_metadata(
+        &self,
+        country: Country,
+        service: Self::Service,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        // Captured up front, like a provider pricing the request
+        let metadata = self.lock().metadata.clone();
+        let (task_id, full_number) = self.get_phone_number(country, service).await?;
+        Ok((task_id, full_number, metadata))
+    }
+
+    async fn get_phone_number_with_idempotency_key(
+        &self,
+        country: Country,
+        service: Self::Service,
+        idempotency_key: &str,
+    ) -> Result<(TaskId, FullNumber, Option<ActivationMetadata>), Self::Error> {
+        self.lock()
+            .idempotency_keys
+            .push(idempotency_key.to_string());
+        self.get_phone_number_with_metadata(country, service).await
+    }
+
+    async fn get_sms_code(&self, _task_id: &TaskId) -> Result<Option<SmsCode>, Self::Error> {
+        let mut state = self.lock();
+        state.get_sms_code_calls += 1;
+        state.sms_code_responses.pop_front().unwrap_or(Ok(None))
+    }
+
+    async fn get_all_sms_codes_with_text(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Vec<(SmsCode, String)>, Self::Error> {
+        {
+            let mut state = self.lock();
+            if let Some(messages) = state.all_sms_codes.clone() {
+                state.get_sms_code_calls += 1;
+                return Ok(messages);
+            }
+        }
+        // Like the trait default: only the latest SMS
+        let latest = self.get_sms_code_with_text(task_id).await?;
+        Ok(latest.into_iter().collect())
+    }
+
+    async fn get_activation_status(
+        &self,
+        _task_id: &TaskId,
+    ) -> Result<Option<ActivationState>, Self::Error> {
+        Ok(self.lock().activation_state)
+    }
+
+    async fn finish_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        state.finished.push(task_id.clone());
+        state.finish_response.clone().unwrap_or(Ok(()))
+    }
+
+    async fn cancel_activation(&self, task_id: &TaskId) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        state.cancelled.push(task_id.clone());
+        state.cancel_response.clone().unwrap_or(Ok(()))
+    }
+
+    async fn is_task_alive(&self, _task_id: &TaskId) -> Result<bool, Self::Error> {
+        self.lock().task_alive_response.clone().unwrap_or(Ok(true))
+    }
+
+    async fn warmup(&self) -> Result<(), Self::Error> {
+        self.lock().warmup_response.clone().unwrap_or(Ok(()))
+    }
+}
+
+/// Fluent builder for [`MockProvider`].
+#[derive(Debug, Default)]
+pub struct MockProviderBuilder {
+    state: MockState,
+}
+
+impl MockProviderBuilder {
+    /// Return `response` from every `get_phone_number` call.
+    pub fn get_number_response(mut self, response: MockNumberResponse) -> Self {
+        self.state.get_number_response = Some(response);
+        self
+    }
+
+    /// Queue a response for the next `get_phone_number` call.
+    ///
+    /// Queued responses are returned first, in order; after them every call
+    /// gets the [`get_number_response`](Self::get_number_response) or the
+    /// default.
+    pub fn next_get_number_response(mut self, response: MockNumberResponse) -> Self {
+        self.state.next_get_number_responses.push_back(response);
+        self
+    }
+
+    /// Return this task ID and number from every `get_phone_number` call.
+    pub fn number(self, task_id: impl Into<TaskId>, full_number: impl Into<FullNumber>) -> Self {
+        self.get_number_response(Ok((task_id.into(), full_number.into())))
+    }
+
+    /// Wait `delay` before answering each `get_phone_number` call.
+    pub fn get_number_delay(mut self, delay: Duration) -> Self {
+        self.state.get_number_delay = delay;
+        self
+    }
+
+    /// Wait `delay`, then return `response` from every `get_phone_number`
+    /// call for `country`, instead of the defaults.
+    pub fn country_number_response(
+        mut self,
+        country: Country,
+        delay: Duration,
+        response: MockNumberResponse,
+    ) -> Self {
+        self.state
+            .country_numbers
+            .insert(country.alpha2().to_string(), (delay, response));
+        self
+    }
+
+    /// Wait `delay`, then return this task ID and number from every
+    /// `get_phone_number` call for `country`.
+    pub fn country_number(
+        self,
+        country: Country,
+        delay: Duration,
+        task_id: impl Into<TaskId>,
+        full_number: impl Into<FullNumber>,
+    ) -> Self {
+        self.country_number_response(country, delay, Ok((task_id.into(), full_number.into())))
+    }
+
+    /// Return `metadata` along with every requested number.
+    pub fn metadata(mut self, metadata: ActivationMetadata) -> Self {
+        self.state.metadata = Some(metadata);
+        self
+    }
+
+    /// Queue a `get_sms_code` response.
+    pub fn sms_code_response(mut self, response: MockSmsCodeResponse) -> Self {
+        self.state.sms_code_responses.push_back(response);
+        self
+    }
+
+    /// Queue a received SMS code.
+    pub fn sms_code(self, code: impl Into<SmsCode>) -> Self {
+        self.sms_code_response(Ok(Some(code.into())))
+    }
+
+    /// Queue `polls` empty polls followed by a received SMS code.
+    pub fn sms_after_polls(mut self, polls: u32, code: impl Into<SmsCode>) -> Self {
+        for _ in 0..polls {
+            self.state.sms_code_responses.push_back(Ok(None));
+        }
+        self.sms_code(code)
+    }
+
+    /// Return `messages`, oldest first, from every
+    /// `get_all_sms_codes_with_text` call.
+    ///
+    /// Without it, the full list holds only the latest SMS, like the trait
+    /// default.
+    pub fn all_sms_codes(mut self, messages: impl IntoIterator<Item = (SmsCode, String)>) -> Self {
+        self.state.all_sms_codes = Some(messages.into_iter().collect());
+        self
+    }
+
+    /// Report `state` from every `get_activation_status` call.
+    pub fn activation_state(mut self, state: ActivationState) -> Self {
+        self.state.activation_state = Some(state);
+        self
+    }
+
+    /// Return `response` from every `is_task_alive` call.
+    pub fn task_alive_response(mut self, response: Result<bool, MockError>) -> Self {
+        self.state.task_alive_response = Some(response);
+        self
+    }
+
+    /// Return `response` from every `warmup` call.
+    pub fn warmup_response(mut self, response: Result<(), MockError>) -> Self {
+        self.state.warmup_response = Some(response);
+        self
+    }
+
+    /// Return `response` from every `cancel_activation` call.
+    pub fn cancel_response(mut self, response: Result<(), MockError>) -> Self {
+        self.state.cancel_response = Some(response);
+        self
+    }
+
+    /// Return `response` from every `finish_activation` call.
+    pub fn finish_response(mut self, response: Result<(), MockError>) -> Self {
+        self.state.finish_response = Some(response);
+        self
+    }
+
+    /// Build the mock.
+    pub fn build(self) -> MockProvider {
+        MockProvider {
+            state: Arc::new(Mutex::new(self.state)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{SmsSolverService, SmsSolverServiceConfig, SmsSolverServiceTrait};
+    use keshvar::Alpha2;
+
+    fn fast_service(provider: MockProvider) -> SmsSolverService<MockProvider> {
+        let config = SmsSolverServiceConfig::builder()
+            .timeout(Duration::from_secs(1))
+            .poll_interval(Duration::from_millis(10))
+            .build();
+        SmsSolverService::new(provider, config)
+    }
+
+    #[tokio::test]
+    async fn test_default_responses() {
+        let provider = MockProvider::new();
+
+        let (task_id, full_number) = provider
+            .get_phone_number(Alpha2::TR.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "mock-task-1");
+        assert_eq!(full_number.as_str(), "90501234567");
+
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::TR.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "mock-task-2");
+
+        assert_eq!(provider.get_sms_code(&task_id).await.unwrap(), None);
+        provider.cancel_activation(&task_id).await.unwrap();
+        assert_eq!(provider.call_count_get_number(), 2);
+        assert_eq!(provider.call_count_get_sms_code(), 1);
+        assert_eq!(provider.cancelled_tasks(), vec![task_id]);
+    }
+
+    #[tokio::test]
+    async fn test_builder_responses_through_service() {
+        let provider = MockProvider::builder()
+            .number("task42", "380501234567")
+            .sms_after_polls(2, "482913")
+            .build();
+        let service = fast_service(provider.clone());
+
+        let (task, code) = service
+            .get_number_and_wait(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+
+        assert_eq!(task.task_id.as_ref(), "task42");
+        assert_eq!(code.as_str(), "482913");
+        assert_eq!(provider.call_count_get_sms_code(), 3);
+        assert_eq!(provider.finished_tasks(), vec![TaskId::new("task42")]);
+    }
+
+    #[tokio::test]
+    async fn test_setters_change_responses() {
+        let provider = MockProvider::new();
+        let service = fast_service(provider.clone());
+
+        provider.set_get_number_response(Err(MockError::Transient));
+        let err = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable());
+
+        provider
+            .set_get_number_response(Ok((TaskId::new("task1"), FullNumber::new("380501234567"))));
+        provider.set_sms_code_responses(vec![Err(MockError::Permanent("BANNED".into()))]);
+        provider.set_cancel_response(Err(MockError::Permanent("NO_ACTIVATION".into())));
+
+        let task = service
+            .get_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert!(service.wait_for_sms_code(&task.task_id).await.is_err());
+        assert_eq!(provider.cancelled_tasks(), vec![TaskId::new("task1")]);
+    }
+
+    #[tokio::test]
+    async fn test_queued_and_per_country_numbers() {
+        let provider = MockProvider::builder()
+            .next_get_number_response(Err(MockError::Transient))
+            .number("task1", "380501234567")
+            .country_number(
+                Alpha2::GB.to_country(),
+                Duration::ZERO,
+                "gb",
+                "447911123456",
+            )
+            .build();
+
+        assert_eq!(
+            provider
+                .get_phone_number(Alpha2::UA.to_country(), MockService)
+                .await,
+            Err(MockError::Transient)
+        );
+        let (task_id, _) = provider
+            .get_phone_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "task1");
+        let (task_id, full_number) = provider
+            .get_phone_number(Alpha2::GB.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(task_id.as_ref(), "gb");
+        assert_eq!(full_number.as_str(), "447911123456");
+        assert_eq!(provider.call_count_get_number(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_number_delay() {
+        let provider = MockProvider::builder()
+            .get_number_delay(Duration::from_millis(300))
+            .build();
+
+        let started = tokio::time::Instant::now();
+        provider
+            .get_phone_number(Alpha2::UA.to_country(), MockService)
+            .await
+            .unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(300));
+    }
+}
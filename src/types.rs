@@ -1,11 +1,16 @@
 //! Core types for SMS verification operations.
 
 use keshvar::Country;
+use once_cell::sync::Lazy;
 use rand::Rng;
+use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+
+pub(crate) mod serde_country;
 // =============================================================================
 // TaskId
 // =============================================================================
@@ -68,6 +73,123 @@ impl SmsCode {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Find all runs of 4 to 8 consecutive digits, in order of appearance.
+    ///
+    /// Useful when an SMS body contains several codes (e.g. a primary and a
+    /// backup code). Longer runs such as phone numbers are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::SmsCode;
+    ///
+    /// let sms = SmsCode::new("Your code: 123456. Backup PIN: 9876");
+    /// assert_eq!(sms.multi_extract(), vec!["123456", "9876"]);
+    /// ```
+    pub fn multi_extract(&self) -> Vec<String> {
+        self.digit_runs()
+            .filter(|run| (4..=8).contains(&run.len()))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Extract a verification code from a raw SMS message body.
+    ///
+    /// Looks for 4 to 8 digits, or two groups of 3 digits split by a dash or
+    /// space, that are not part of a longer number. Codes next to a keyword
+    /// such as "code", "OTP" or "verification" win over the first standalone
+    /// match. Separators are removed from split codes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::SmsCode;
+    ///
+    /// let code = SmsCode::extract_from_text("Your WhatsApp code: 123-456").unwrap();
+    /// assert_eq!(code.as_str(), "123456");
+    /// ```
+    pub fn extract_from_text(text: &str) -> Option<SmsCode> {
+        [&*OTP_AFTER_KEYWORD, &*OTP_BEFORE_IS_YOUR, &*OTP_ANY]
+            .into_iter()
+            .find_map(|pattern| standalone_codes(pattern, text).next())
+    }
+
+    /// Extract every standalone verification code from a raw SMS message
+    /// body, in order of appearance.
+    ///
+    /// Uses the same rules as [`SmsCode::extract_from_text`], ignoring
+    /// keywords.
+    pub fn extract_all_from_text(text: &str) -> Vec<SmsCode> {
+        standalone_codes(&OTP_ANY, text).collect()
+    }
+
+    /// Find the first run of consecutive digits at least `min_len` long.
+    pub fn extract_with_min_length(&self, min_len: usize) -> Option<String> {
+        self.digit_runs()
+            .find(|run| run.len() >= min_len)
+            .map(str::to_string)
+    }
+
+    /// Iterate over maximal runs of ASCII digits.
+    fn digit_runs(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|run| !run.is_empty())
+    }
+}
+
+/// A 4 to 8 digit code, or two 3 digit groups split by a dash or space.
+const OTP_CODE: &str = r"([0-9]{3}[- ][0-9]{3}|[0-9]{4,8})";
+
+/// Code after a keyword, e.g. "Telegram code: 12345".
+static OTP_AFTER_KEYWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i)\b(?:code|otp|pin|passcode|password|verification|код)\b[^0-9]{{0,20}}?{OTP_CODE}"
+    ))
+    .unwrap()
+});
+
+/// Code before "is your", e.g. "G-123456 is your Google verification code".
+static OTP_BEFORE_IS_YOUR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!(r"(?i){OTP_CODE}\s+is\s+your\b")).unwrap());
+
+/// Any code.
+static OTP_ANY: Lazy<Regex> = Lazy::new(|| Regex::new(OTP_CODE).unwrap());
+
+/// Iterate over matches of `pattern` whose first capture group is not
+/// adjacent to other digits, with separators removed.
+fn standalone_codes<'a>(pattern: &'a Regex, text: &'a str) -> impl Iterator<Item = SmsCode> + 'a {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        while let Some(captures) = pattern.captures_at(text, start) {
+            let whole = captures.get(0)?;
+            let code = captures.get(1)?;
+            let digit_before = text[..code.start()]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_ascii_digit());
+            let digit_after = text[code.end()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit());
+
+            if digit_before || digit_after {
+                // Retry from the next character so a shorter match inside
+                // this one is still found.
+                start = whole.start()
+                    + text[whole.start()..]
+                        .chars()
+                        .next()
+                        .map_or(1, char::len_utf8);
+                continue;
+            }
+
+            start = whole.end();
+            return Some(SmsCode::new(code.as_str().replace(['-', ' '], "")));
+        }
+        None
+    })
 }
 
 impl Display for SmsCode {
@@ -98,11 +220,22 @@ impl From<&str> for SmsCode {
 // FullNumber
 // =============================================================================
 
+/// Minimum number of digits in a parsed [`FullNumber`].
+const MIN_FULL_NUMBER_DIGITS: usize = 7;
+
+/// Error when parsing a full phone number.
+#[derive(Debug, Clone, Error)]
+pub enum FullNumberError {
+    /// Number is not at least 7 digits, optionally prefixed by '+'.
+    #[error("full number must be at least 7 digits, optionally prefixed by '+'")]
+    InvalidFormat,
+}
+
 /// Full phone number with country code (e.g., "905488242474").
 ///
 /// This represents the complete phone number including the country dial code,
 /// as returned by the SMS provider.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct FullNumber(String);
 
 impl FullNumber {
@@ -111,6 +244,47 @@ impl FullNumber {
         Self(number.into())
     }
 
+    /// Parse a full number, rejecting anything but digits with an optional
+    /// leading '+'.
+    ///
+    /// Unlike [`FullNumber::new`], which accepts any string as returned by a
+    /// provider, this requires at least 7 digits.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// assert_eq!(FullNumber::parse("+905488242474").unwrap().as_str(), "+905488242474");
+    /// assert!(FullNumber::parse("90 548 824").is_err());
+    /// assert!(FullNumber::parse("+12345").is_err());
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, FullNumberError> {
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.len() < MIN_FULL_NUMBER_DIGITS || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(FullNumberError::InvalidFormat);
+        }
+        Ok(Self(s.to_string()))
+    }
+
+    /// Join a dial code and a national number.
+    ///
+    /// Inverse of [`Number::from_full_number`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::{DialCode, FullNumber, Number};
+    ///
+    /// let dial_code = DialCode::new("90").unwrap();
+    /// let number = Number::new("5488242474").unwrap();
+    /// let full = FullNumber::from_number_and_dial_code(&number, &dial_code);
+    /// assert_eq!(full.as_str(), "905488242474");
+    /// ```
+    pub fn from_number_and_dial_code(number: &Number, dial_code: &DialCode) -> Self {
+        Self(format!("{}{}", dial_code.as_str(), number.as_str()))
+    }
+
     /// Get the number as a string slice.
     pub fn as_str(&self) -> &str {
         &self.0
@@ -134,6 +308,42 @@ impl FullNumber {
         }
     }
 
+    /// Mask the number for logging.
+    ///
+    /// The dial code is detected from the longest known prefix and the
+    /// national part is masked with [`Number::redact`]. If no dial code is
+    /// recognized, all but the first 4 and last 2 digits are masked.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// assert_eq!(FullNumber::new("+905488242474").redact(), "+90 54***74");
+    /// ```
+    pub fn redact(&self) -> String {
+        let digits: String = self.0.chars().filter(char::is_ascii_digit).collect();
+
+        for len in (1..=3).rev() {
+            let Some(prefix) = digits.get(..len) else {
+                continue;
+            };
+            if let Ok(dial_code) = DialCode::new(prefix)
+                && dial_code.to_country().is_ok()
+                && let Ok(number) = Number::new(&digits[len..])
+            {
+                return format!("+{} {}", dial_code.as_str(), number.redact());
+            }
+        }
+
+        match (digits.get(..4), digits.len().checked_sub(2)) {
+            (Some(head), Some(tail)) if digits.len() > 6 => {
+                format!("{head}***{}", &digits[tail..])
+            }
+            _ => "***".to_string(),
+        }
+    }
+
     /// Check if the number starts with the given dial code.
     ///
     /// # Example
@@ -152,6 +362,107 @@ impl FullNumber {
         let normalized = self.0.trim_start_matches('+');
         normalized.starts_with(dial_code.as_str())
     }
+
+    /// Get a `https://wa.me/` link to this number.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// let num = FullNumber::new("+90 548 824 2474");
+    /// assert_eq!(num.to_whatsapp_url(), "https://wa.me/905488242474");
+    /// ```
+    pub fn to_whatsapp_url(&self) -> String {
+        format!("https://wa.me/{}", &self.to_e164()[1..])
+    }
+
+    /// Get a `tel:` URI for this number (RFC 3966).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// let num = FullNumber::new("905488242474");
+    /// assert_eq!(num.to_tel_uri(), "tel:+905488242474");
+    /// ```
+    pub fn to_tel_uri(&self) -> String {
+        format!("tel:{}", self.to_e164())
+    }
+
+    /// Get a WhatsApp chat link, optionally pre-filled with `message`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// let num = FullNumber::new("905488242474");
+    /// assert_eq!(
+    ///     num.to_wa_chat_url(Some("Hi there!")),
+    ///     "https://wa.me/905488242474?text=Hi%20there%21"
+    /// );
+    /// ```
+    pub fn to_wa_chat_url(&self, message: Option<&str>) -> String {
+        let url = self.to_whatsapp_url();
+        match message {
+            Some(message) => format!("{url}?text={}", percent_encode(message)),
+            None => url,
+        }
+    }
+
+    /// Returns true if the number is valid E.164: an optional `+`, then up
+    /// to 15 digits with no leading zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// assert!(FullNumber::new("+380501234567").is_valid_e164());
+    /// assert!(!FullNumber::new("+38 050 123").is_valid_e164());
+    /// ```
+    pub fn is_valid_e164(&self) -> bool {
+        let number = self.with_plus_prefix();
+        let digits = &number[1..];
+        (2..=15).contains(&digits.len())
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && !digits.starts_with('0')
+    }
+
+    /// Get the number in E.164 form: `+` followed by digits only.
+    ///
+    /// Spaces, dashes and other separators are stripped. Unlike
+    /// [`is_valid_e164`](Self::is_valid_e164) this does not check the
+    /// length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::FullNumber;
+    ///
+    /// let num = FullNumber::new("90 548-824-2474");
+    /// assert_eq!(num.to_e164(), "+905488242474");
+    /// ```
+    pub fn to_e164(&self) -> String {
+        let digits: String = self.0.chars().filter(char::is_ascii_digit).collect();
+        FullNumber(digits).with_plus_prefix()
+    }
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
 }
 
 impl Display for FullNumber {
@@ -178,6 +489,14 @@ impl From<&str> for FullNumber {
     }
 }
 
+impl FromStr for FullNumber {
+    type Err = FullNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 // =============================================================================
 // DialCode
 // =============================================================================
@@ -203,11 +522,15 @@ pub enum DialCodeError {
 /// use sms_solvers::DialCode;
 ///
 /// let dc = DialCode::new("+380").unwrap();
-/// assert_eq!(dc.to_string(), "380");
+/// assert_eq!(dc.as_str(), "380");
+/// assert_eq!(dc.format_international(), "+380");
 ///
 /// let dc = DialCode::new("1").unwrap();
-/// assert_eq!(dc.to_string(), "1");
+/// assert_eq!(dc.as_str(), "1");
 /// ```
+///
+/// `Display` prints the bare digits, or the `+` prefixed form with the
+/// `display-plus` feature.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DialCode(String);
 
@@ -239,6 +562,23 @@ impl DialCode {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Format the dial code in E.123 international form, e.g. `"+380"`.
+    pub fn format_international(&self) -> String {
+        format!("+{}", self.0)
+    }
+
+    /// Format the dial code for display, separating the North American
+    /// country code from an area code that is part of the dial code.
+    ///
+    /// `"1684"` becomes `"+1-684"` with `'-'`; other codes such as `"1"` or
+    /// `"380"` are formatted as by [`format_international`](Self::format_international).
+    pub fn format_with_separator(&self, sep: char) -> String {
+        match self.0.strip_prefix('1') {
+            Some(area) if !area.is_empty() => format!("+1{sep}{area}"),
+            _ => self.format_international(),
+        }
+    }
 }
 
 impl FromStr for DialCode {
@@ -250,9 +590,15 @@ impl FromStr for DialCode {
 }
 
 impl Display for DialCode {
+    #[cfg(not(feature = "display-plus"))]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
+
+    #[cfg(feature = "display-plus")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format_international())
+    }
 }
 
 impl<'de> Deserialize<'de> for DialCode {
@@ -332,6 +678,25 @@ impl TryFrom<DialCode> for Country {
 }
 
 impl DialCode {
+    /// Resolve a user-typed country name such as `"germany"` or `"uk"`.
+    ///
+    /// Uses the same name matching as
+    /// [`SmsCountryExt::try_from_name`](crate::hero_sms::SmsCountryExt::try_from_name).
+    /// Returns `None` if no country matches.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::DialCode;
+    ///
+    /// assert_eq!(DialCode::try_from_country_name("germany").unwrap().as_str(), "49");
+    /// assert!(DialCode::try_from_country_name("atlantis").is_none());
+    /// ```
+    #[cfg(feature = "hero-sms")]
+    pub fn try_from_country_name(name: &str) -> Option<Self> {
+        crate::providers::hero_sms::countries::country_from_name(name).map(DialCode::from)
+    }
+
     /// Try to convert this dial code to a Country.
     ///
     /// Uses keshvar's built-in country code lookup.
@@ -422,6 +787,36 @@ impl Number {
         Self::new(number_part)
     }
 
+    /// Join this national number with `dial_code`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::{DialCode, Number};
+    ///
+    /// let number = Number::new("5488242474").unwrap();
+    /// let dial_code = DialCode::new("90").unwrap();
+    /// assert_eq!(number.to_full_number(&dial_code).as_str(), "905488242474");
+    /// ```
+    pub fn to_full_number(&self, dial_code: &DialCode) -> FullNumber {
+        FullNumber::from_number_and_dial_code(self, dial_code)
+    }
+
+    /// Get the number with `dial_code` in E.164 form.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::{DialCode, Number};
+    ///
+    /// let number = Number::new("5488242474").unwrap();
+    /// let dial_code = DialCode::new("90").unwrap();
+    /// assert_eq!(number.to_e164(&dial_code), "+905488242474");
+    /// ```
+    pub fn to_e164(&self, dial_code: &DialCode) -> String {
+        self.to_full_number(dial_code).to_e164()
+    }
+
     /// Generate a random valid Number.
     #[cfg(feature = "random")]
     pub fn generate() -> Result<Self, NumberError> {
@@ -435,6 +830,25 @@ impl Number {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Mask the middle digits for logging, keeping the first and last two.
+    ///
+    /// Numbers of 4 digits or less are masked entirely.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::Number;
+    ///
+    /// let num = Number::new("5488242474").unwrap();
+    /// assert_eq!(num.redact(), "54***74");
+    /// ```
+    pub fn redact(&self) -> String {
+        if self.0.len() <= 4 {
+            return "***".to_string();
+        }
+        format!("{}***{}", &self.0[..2], &self.0[self.0.len() - 2..])
+    }
 }
 
 impl FromStr for Number {
@@ -455,11 +869,142 @@ impl Display for Number {
 // SmsTaskResult
 // =============================================================================
 
+/// Provider-reported details about an activation.
+///
+/// All fields are optional since not every provider reports them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivationMetadata {
+    /// When the activation expires (`YYYY-MM-DD HH:MM:SS`, interpreted as UTC).
+    pub expires_at: Option<String>,
+    /// Cost of the activation in the provider's currency.
+    pub cost: Option<f64>,
+    /// Mobile operator of the number.
+    pub operator: Option<String>,
+}
+
+/// Price and stock of a service in one country.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceEntry {
+    /// Activation cost in the provider's currency.
+    pub cost: f64,
+    /// Number of numbers in stock.
+    #[serde(default)]
+    pub count: u32,
+}
+
+/// Stock and price of a service in one country.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountryAvailability {
+    /// Country the numbers belong to.
+    #[serde(with = "crate::types::serde_country")]
+    pub country: Country,
+    /// Number of numbers in stock across all operators.
+    pub count: u32,
+    /// Lowest activation cost across operators, if the provider reports it.
+    pub price: Option<f64>,
+}
+
+/// Account quality score reported by a provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReputationLevel {
+    /// Numeric reputation score.
+    pub score: u32,
+    /// Provider-specific name of the reputation tier.
+    pub level: String,
+    /// Activation limit per hour at this level, if the provider reports one.
+    pub max_activations_per_hour: Option<u32>,
+}
+
+/// Lifecycle state of an activation as reported by the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivationState {
+    /// Waiting for an SMS to arrive.
+    WaitingSms,
+    /// An SMS has been received.
+    SmsReceived,
+    /// The activation was completed.
+    Finished,
+    /// The activation was cancelled.
+    Cancelled,
+    /// The activation expired before completing.
+    Expired,
+}
+
+impl ActivationState {
+    /// Returns true if no further SMS can arrive for the activation.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Finished | Self::Cancelled | Self::Expired)
+    }
+}
+
+/// Combined health signals of a provider account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    /// Account balance, if it could be fetched.
+    pub balance: Option<f64>,
+    /// Number of activations currently in progress, if it could be fetched.
+    pub active_activations: Option<u32>,
+    /// Round-trip time of the balance request.
+    pub api_latency: Duration,
+    /// Whether every check succeeded and the balance is positive.
+    pub is_healthy: bool,
+    /// When the checks were run.
+    pub checked_at: SystemTime,
+}
+
+/// Parse a `YYYY-MM-DD HH:MM:SS` (or RFC 3339-like `YYYY-MM-DDTHH:MM:SSZ`) UTC timestamp.
+fn parse_utc_datetime(s: &str) -> Option<SystemTime> {
+    let s = s.trim().trim_end_matches('Z');
+    let (date, time) = s.split_once([' ', 'T'])?;
+
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+
+    let mut time_parts = time.splitn(3, ':').map(|p| p.parse::<u64>().ok());
+    let (hour, minute, second) = (
+        time_parts.next()??,
+        time_parts.next()??,
+        time_parts.next()??,
+    );
+
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // Days since the Unix epoch (Howard Hinnant's days_from_civil).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let days = u64::try_from(days).ok()?;
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
 /// Result of acquiring a phone number for SMS verification.
 ///
 /// Contains all information about the acquired phone number,
 /// including the task ID for tracking and the parsed number components.
-#[derive(Debug, Clone)]
+///
+/// Serializable so a task can be persisted and resumed later by passing
+/// its `task_id` back to `wait_for_sms_code`. The country is stored as its
+/// alpha-2 code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmsTaskResult {
     /// Unique identifier for this SMS task.
     pub task_id: TaskId,
@@ -470,7 +1015,31 @@ pub struct SmsTaskResult {
     /// Full phone number with dial code.
     pub full_number: FullNumber,
     /// Country.
+    #[serde(with = "crate::types::serde_country")]
     pub country: Country,
+    /// Provider-reported activation details, if available.
+    pub metadata: Option<ActivationMetadata>,
+}
+
+impl SmsTaskResult {
+    /// Time remaining until the activation expires.
+    ///
+    /// Returns `None` if the metadata or its expiry is absent or unparseable,
+    /// and `Some(Duration::ZERO)` if the activation has already expired.
+    pub fn expires_in(&self) -> Option<Duration> {
+        let expires_at = self.metadata.as_ref()?.expires_at.as_deref()?;
+        let expires_at = parse_utc_datetime(expires_at)?;
+        Some(
+            expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+
+    /// Returns true if the activation is known to have expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_in() == Some(Duration::ZERO)
+    }
 }
 
 #[cfg(test)]
@@ -501,6 +1070,38 @@ mod tests {
         assert_eq!(num.to_string(), "905488242474");
     }
 
+    #[test]
+    fn test_full_number_sort_dedup() {
+        let mut numbers = vec![
+            FullNumber::new("905488242474"),
+            FullNumber::new("380501234567"),
+            FullNumber::new("905488242474"),
+            FullNumber::new("12025550123"),
+            FullNumber::new("380501234567"),
+        ];
+        numbers.sort();
+        numbers.dedup();
+
+        assert_eq!(
+            numbers,
+            vec![
+                FullNumber::new("12025550123"),
+                FullNumber::new("380501234567"),
+                FullNumber::new("905488242474"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_number_from_number_and_dial_code() {
+        let dial_code = DialCode::new("380").unwrap();
+        let number = Number::new("501234567").unwrap();
+        let full = FullNumber::from_number_and_dial_code(&number, &dial_code);
+
+        assert_eq!(full.as_str(), "380501234567");
+        assert_eq!(Number::from_full_number(&full, &dial_code).unwrap(), number);
+    }
+
     #[test]
     fn test_full_number_with_plus_prefix() {
         let num = FullNumber::new("905488242474");
@@ -511,6 +1112,86 @@ mod tests {
         assert_eq!(num_with_plus.with_plus_prefix(), "+905488242474");
     }
 
+    #[test]
+    fn test_full_number_to_e164() {
+        assert_eq!(FullNumber::new("905488242474").to_e164(), "+905488242474");
+        assert_eq!(
+            FullNumber::new("+90 548-824-2474").to_e164(),
+            "+905488242474"
+        );
+        assert_eq!(
+            FullNumber::new("(1) 202.555.0123").to_e164(),
+            "+12025550123"
+        );
+    }
+
+    #[test]
+    fn test_number_to_full_number_and_e164() {
+        let dial_code = DialCode::new("380").unwrap();
+        let number = Number::new("501234567").unwrap();
+
+        assert_eq!(number.to_full_number(&dial_code).as_str(), "380501234567");
+        assert_eq!(number.to_e164(&dial_code), "+380501234567");
+    }
+
+    #[test]
+    fn test_full_number_parse() {
+        assert_eq!(
+            FullNumber::parse("380501234567").unwrap().as_str(),
+            "380501234567"
+        );
+        assert_eq!(
+            "+1234567".parse::<FullNumber>().unwrap().as_str(),
+            "+1234567"
+        );
+
+        for invalid in [
+            "",
+            "+",
+            "123456",
+            "+123456",
+            "+38 050 123 4567",
+            "380-50-1234567",
+            "++380501234567",
+            "38050123456a",
+        ] {
+            assert!(
+                matches!(
+                    FullNumber::parse(invalid),
+                    Err(FullNumberError::InvalidFormat)
+                ),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_number_uris() {
+        let num = FullNumber::new("+90 548-824-2474");
+        assert_eq!(num.to_whatsapp_url(), "https://wa.me/905488242474");
+        assert_eq!(num.to_tel_uri(), "tel:+905488242474");
+        assert_eq!(num.to_wa_chat_url(None), "https://wa.me/905488242474");
+        assert_eq!(
+            num.to_wa_chat_url(Some("Code: 123 & ok?")),
+            "https://wa.me/905488242474?text=Code%3A%20123%20%26%20ok%3F"
+        );
+        assert_eq!(
+            FullNumber::new("380501234567").to_wa_chat_url(Some("Привіт")),
+            "https://wa.me/380501234567?text=%D0%9F%D1%80%D0%B8%D0%B2%D1%96%D1%82"
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(Number::new("5488242474").unwrap().redact(), "54***74");
+        assert_eq!(Number::new("1234").unwrap().redact(), "***");
+
+        assert_eq!(FullNumber::new("380501234567").redact(), "+380 50***67");
+        assert_eq!(FullNumber::new("+1 202 555 0123").redact(), "+1 20***23");
+        assert_eq!(FullNumber::new("0001234567").redact(), "0001***67");
+        assert_eq!(FullNumber::new("12").redact(), "***");
+    }
+
     #[test]
     fn test_full_number_starts_with_dial_code() {
         let num = FullNumber::new("905488242474");
@@ -545,6 +1226,20 @@ mod tests {
         assert_eq!(dc.as_str(), "7");
     }
 
+    #[test]
+    fn test_dial_code_format_international() {
+        assert_eq!(DialCode::new("380").unwrap().format_international(), "+380");
+        assert_eq!(DialCode::new("1").unwrap().format_with_separator('-'), "+1");
+        assert_eq!(
+            DialCode::new("380").unwrap().format_with_separator('-'),
+            "+380"
+        );
+        assert_eq!(
+            DialCode::new("1684").unwrap().format_with_separator('-'),
+            "+1-684"
+        );
+    }
+
     #[test]
     fn test_dial_code_empty() {
         assert!(matches!(DialCode::new(""), Err(DialCodeError::Empty)));
@@ -619,19 +1314,196 @@ mod tests {
 
     use keshvar::Alpha2;
 
+    // SmsCode extraction tests
+    #[test]
+    fn test_extract_from_text_whatsapp() {
+        let text = "Your WhatsApp code: 482-913\nDon't share this code with others";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "482913");
+
+        let text = "<#> Your WhatsApp Business code 739 104\nYou can also tap on this link to verify your phone: v.whatsapp.com/739104";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "739104");
+    }
+
+    #[test]
+    fn test_extract_from_text_instagram() {
+        let text = "123 456 is your Instagram code. Don't share it.";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "123456");
+
+        let text = "Use 557201 to verify your Instagram account.";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "557201");
+    }
+
+    #[test]
+    fn test_extract_from_text_google() {
+        let text = "G-804512 is your Google verification code.";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "804512");
+    }
+
+    #[test]
+    fn test_extract_from_text_telegram() {
+        let text = "Telegram code: 61823\n\nYou can also tap on this link to log in:\nhttps://t.me/login/61823\n\nhHbJ8yQ0dKf";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "61823");
+
+        let text = "Код подтверждения Telegram: 40917. Никому не сообщайте его.";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "40917");
+    }
+
+    #[test]
+    fn test_extract_from_text_prefers_keyword() {
+        let text = "Order 2024 confirmed. Your verification code is 9315.";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "9315");
+    }
+
+    #[test]
+    fn test_extract_from_text_skips_longer_numbers() {
+        let text = "Call +1 4155552671 for help. OTP 88213";
+        assert_eq!(SmsCode::extract_from_text(text).unwrap().as_str(), "88213");
+
+        assert!(SmsCode::extract_from_text("Call 4155552671").is_none());
+        assert!(SmsCode::extract_from_text("Your code is 12").is_none());
+        assert!(SmsCode::extract_from_text("").is_none());
+    }
+
+    #[test]
+    fn test_extract_all_from_text() {
+        let text = "Your code: 123456. Backup codes: 7788, 4410. Call 4155552671";
+        let codes: Vec<String> = SmsCode::extract_all_from_text(text)
+            .into_iter()
+            .map(|code| code.0)
+            .collect();
+        assert_eq!(codes, vec!["123456", "7788", "4410"]);
+
+        let text = "Telegram code: 61823. Link: https://t.me/login/61823";
+        assert_eq!(SmsCode::extract_all_from_text(text).len(), 2);
+
+        assert!(SmsCode::extract_all_from_text("no digits here").is_empty());
+    }
+
+    #[test]
+    fn test_sms_code_multi_extract() {
+        let sms = SmsCode::new("Your code is 482913. Backup code: 5521");
+        assert_eq!(sms.multi_extract(), vec!["482913", "5521"]);
+
+        let sms = SmsCode::new("code:1234 PIN:98765432");
+        assert_eq!(sms.multi_extract(), vec!["1234", "98765432"]);
+
+        // Hyphen-separated halves are separate runs
+        let sms = SmsCode::new("Use 1234-5678 to sign in");
+        assert_eq!(sms.multi_extract(), vec!["1234", "5678"]);
+        assert!(SmsCode::new("123-456").multi_extract().is_empty());
+
+        // Too short and too long runs are ignored
+        let sms = SmsCode::new("Call +380501234567 in 24h, code 7788");
+        assert_eq!(sms.multi_extract(), vec!["7788"]);
+
+        assert!(SmsCode::new("no digits here!").multi_extract().is_empty());
+        assert!(SmsCode::new("").multi_extract().is_empty());
+    }
+
+    #[test]
+    fn test_sms_code_extract_with_min_length() {
+        let sms = SmsCode::new("Order 42, PIN: 7391, code: 123456");
+        assert_eq!(sms.extract_with_min_length(4), Some("7391".to_string()));
+        assert_eq!(sms.extract_with_min_length(5), Some("123456".to_string()));
+        assert_eq!(sms.extract_with_min_length(7), None);
+        assert_eq!(sms.extract_with_min_length(0), Some("42".to_string()));
+        assert_eq!(SmsCode::new("-- noise --").extract_with_min_length(1), None);
+    }
+
+    // SmsTaskResult tests
+    fn task_result_with_expiry(expires_at: Option<&str>) -> SmsTaskResult {
+        SmsTaskResult {
+            task_id: TaskId::from("1"),
+            dial_code: DialCode::new("380").unwrap(),
+            number: Number::new("501234567").unwrap(),
+            full_number: FullNumber::new("380501234567"),
+            country: Alpha2::UA.to_country(),
+            metadata: Some(ActivationMetadata {
+                expires_at: expires_at.map(str::to_string),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_parse_utc_datetime() {
+        assert_eq!(parse_utc_datetime("1970-01-01 00:00:00"), Some(UNIX_EPOCH));
+        assert_eq!(
+            parse_utc_datetime("2025-01-01 12:20:00"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_735_734_000))
+        );
+        assert_eq!(
+            parse_utc_datetime("2024-02-29T23:59:59Z"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_251_199))
+        );
+        assert_eq!(parse_utc_datetime("2025-13-01 00:00:00"), None);
+        assert_eq!(parse_utc_datetime("2025-02-31 00:00:00"), None);
+        assert_eq!(parse_utc_datetime("2025-04-31 00:00:00"), None);
+        assert_eq!(parse_utc_datetime("2025-02-29 00:00:00"), None);
+        assert_eq!(parse_utc_datetime("1900-02-29 00:00:00"), None);
+        assert!(parse_utc_datetime("2000-02-29 00:00:00").is_some());
+        assert_eq!(parse_utc_datetime("not a date"), None);
+    }
+
+    #[test]
+    fn test_task_result_expires_in() {
+        let result = task_result_with_expiry(Some("2999-01-01 00:00:00"));
+        assert!(result.expires_in().unwrap() > Duration::ZERO);
+        assert!(!result.is_expired());
+
+        let result = task_result_with_expiry(Some("2000-01-01 00:00:00"));
+        assert_eq!(result.expires_in(), Some(Duration::ZERO));
+        assert!(result.is_expired());
+
+        let result = task_result_with_expiry(Some("garbage"));
+        assert_eq!(result.expires_in(), None);
+        assert!(!result.is_expired());
+
+        let mut result = task_result_with_expiry(None);
+        assert_eq!(result.expires_in(), None);
+        result.metadata = None;
+        assert_eq!(result.expires_in(), None);
+    }
+
+    #[test]
+    fn test_task_result_json_roundtrip() {
+        let result = task_result_with_expiry(Some("2999-01-01 00:00:00"));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["country"], "UA");
+        assert_eq!(json["dial_code"], "380");
+
+        let restored: SmsTaskResult = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.task_id, result.task_id);
+        assert_eq!(restored.dial_code, result.dial_code);
+        assert_eq!(restored.number, result.number);
+        assert_eq!(restored.full_number, result.full_number);
+        assert_eq!(restored.country.alpha2(), Alpha2::UA);
+        assert_eq!(restored.metadata, result.metadata);
+    }
+
+    #[test]
+    fn test_task_result_rejects_unknown_country() {
+        let mut json = serde_json::to_value(task_result_with_expiry(None)).unwrap();
+        json["country"] = "XX".into();
+
+        let err = serde_json::from_value::<SmsTaskResult>(json).unwrap_err();
+        assert!(err.to_string().contains("XX"));
+    }
+
     #[test]
     fn test_country_to_dial_code() {
-        assert_eq!(DialCode::from(Alpha2::US.to_country()).to_string(), "1");
-        assert_eq!(DialCode::from(Alpha2::UA.to_country()).to_string(), "380");
-        assert_eq!(DialCode::from(Alpha2::GB.to_country()).to_string(), "44");
-        assert_eq!(DialCode::from(Alpha2::TR.to_country()).to_string(), "90");
+        assert_eq!(DialCode::from(Alpha2::US.to_country()).as_str(), "1");
+        assert_eq!(DialCode::from(Alpha2::UA.to_country()).as_str(), "380");
+        assert_eq!(DialCode::from(Alpha2::GB.to_country()).as_str(), "44");
+        assert_eq!(DialCode::from(Alpha2::TR.to_country()).as_str(), "90");
     }
 
     #[test]
     fn test_country_ref_to_dial_code() {
         let country = Alpha2::DE.to_country();
         let dial_code = DialCode::from(&country);
-        assert_eq!(dial_code.to_string(), "49");
+        assert_eq!(dial_code.as_str(), "49");
     }
 
     #[test]
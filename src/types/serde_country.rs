@@ -0,0 +1,19 @@
+//! Serde support for [`Country`] as its ISO 3166-1 alpha-2 code.
+//!
+//! `keshvar::Country` has no serde impls, so fields use
+//! `#[serde(with = "crate::types::serde_country")]`.
+
+use keshvar::Country;
+use serde::{Deserialize, Deserializer, Serializer, de};
+
+/// Serialize a country as its alpha-2 code, e.g. `"UA"`.
+pub(crate) fn serialize<S: Serializer>(country: &Country, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&country.alpha2().to_string())
+}
+
+/// Deserialize a country from its alpha-2 code.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Country, D::Error> {
+    let raw = String::deserialize(d)?;
+    Country::try_from(raw.as_str())
+        .map_err(|_| de::Error::custom(format!("unknown alpha-2 country code '{raw}'")))
+}
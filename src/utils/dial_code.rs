@@ -0,0 +1,47 @@
+//! Country to dial code conversions.
+
+use crate::types::DialCode;
+use keshvar::{Alpha2, Country};
+
+/// Look up the dial code of a country.
+///
+/// Returns `None` if the country has no valid calling code.
+///
+/// # Example
+///
+/// ```rust
+/// use sms_solvers::{Alpha2, keshvar_country_to_dial_code};
+///
+/// let dial_code = keshvar_country_to_dial_code(&Alpha2::UA.to_country()).unwrap();
+/// assert_eq!(dial_code.as_str(), "380");
+/// ```
+pub fn keshvar_country_to_dial_code(country: &Country) -> Option<DialCode> {
+    DialCode::new(country.country_code().to_string()).ok()
+}
+
+/// Types that identify a country and can be resolved to its dial code.
+///
+/// # Example
+///
+/// ```rust
+/// use sms_solvers::{Alpha2, CountryDialCode};
+///
+/// assert_eq!(Alpha2::GB.to_dial_code().unwrap().as_str(), "44");
+/// assert_eq!(Alpha2::US.to_country().to_dial_code().unwrap().as_str(), "1");
+/// ```
+pub trait CountryDialCode {
+    /// Get the dial code of this country, if known.
+    fn to_dial_code(&self) -> Option<DialCode>;
+}
+
+impl CountryDialCode for Country {
+    fn to_dial_code(&self) -> Option<DialCode> {
+        keshvar_country_to_dial_code(self)
+    }
+}
+
+impl CountryDialCode for Alpha2 {
+    fn to_dial_code(&self) -> Option<DialCode> {
+        keshvar_country_to_dial_code(&self.to_country())
+    }
+}
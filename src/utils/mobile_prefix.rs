@@ -0,0 +1,195 @@
+//! Mobile number prefix heuristics.
+
+use crate::types::{DialCode, Number};
+
+/// National number prefixes used by mobile operators in a country.
+struct MobilePrefixes {
+    dial_code: &'static str,
+    mobile_prefixes: &'static [&'static str],
+}
+
+/// Known mobile prefixes, keyed by dial code.
+///
+/// Countries with no prefix distinction between mobile and landline numbers
+/// (e.g. the US and Canada under `1`) are intentionally absent.
+static MOBILE_PREFIXES: &[MobilePrefixes] = &[
+    MobilePrefixes {
+        dial_code: "44",
+        mobile_prefixes: &["7"],
+    },
+    MobilePrefixes {
+        dial_code: "380",
+        mobile_prefixes: &["5", "6", "73", "9"],
+    },
+    MobilePrefixes {
+        dial_code: "90",
+        mobile_prefixes: &["5"],
+    },
+    MobilePrefixes {
+        dial_code: "49",
+        mobile_prefixes: &["15", "16", "17"],
+    },
+    MobilePrefixes {
+        dial_code: "33",
+        mobile_prefixes: &["6", "7"],
+    },
+    MobilePrefixes {
+        dial_code: "34",
+        mobile_prefixes: &["6", "7"],
+    },
+    MobilePrefixes {
+        dial_code: "39",
+        mobile_prefixes: &["3"],
+    },
+    MobilePrefixes {
+        dial_code: "48",
+        mobile_prefixes: &[
+            "45", "50", "51", "53", "57", "60", "66", "69", "72", "73", "78", "79", "88",
+        ],
+    },
+    MobilePrefixes {
+        dial_code: "7",
+        mobile_prefixes: &["9", "70", "77"],
+    },
+    MobilePrefixes {
+        dial_code: "91",
+        mobile_prefixes: &["6", "7", "8", "9"],
+    },
+    MobilePrefixes {
+        dial_code: "86",
+        mobile_prefixes: &["1"],
+    },
+    MobilePrefixes {
+        dial_code: "62",
+        mobile_prefixes: &["8"],
+    },
+    MobilePrefixes {
+        dial_code: "81",
+        mobile_prefixes: &["70", "80", "90"],
+    },
+    MobilePrefixes {
+        dial_code: "61",
+        mobile_prefixes: &["4"],
+    },
+    MobilePrefixes {
+        dial_code: "31",
+        mobile_prefixes: &["6"],
+    },
+    MobilePrefixes {
+        dial_code: "63",
+        mobile_prefixes: &["9"],
+    },
+];
+
+impl Number {
+    /// Guess whether this national number is a mobile number.
+    ///
+    /// This is a heuristic based on well-known mobile prefixes, not an
+    /// authoritative lookup: numbering plans change and ported or special
+    /// numbers may be misclassified.
+    ///
+    /// Returns `None` when the dial code is not in the prefix table, including
+    /// countries such as the US where mobile and landline numbers share prefixes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::{DialCode, Number};
+    ///
+    /// let uk = DialCode::new("44").unwrap();
+    /// assert_eq!(Number::new("7700900123").unwrap().is_mobile_heuristic(&uk), Some(true));
+    /// assert_eq!(Number::new("2079460123").unwrap().is_mobile_heuristic(&uk), Some(false));
+    /// ```
+    pub fn is_mobile_heuristic(&self, dial_code: &DialCode) -> Option<bool> {
+        let entry = MOBILE_PREFIXES
+            .iter()
+            .find(|entry| entry.dial_code == dial_code.as_str())?;
+
+        Some(
+            entry
+                .mobile_prefixes
+                .iter()
+                .any(|prefix| self.as_str().starts_with(prefix)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(dial_code: &str, number: &str) -> Option<bool> {
+        Number::new(number)
+            .unwrap()
+            .is_mobile_heuristic(&DialCode::new(dial_code).unwrap())
+    }
+
+    #[test]
+    fn test_mobile_numbers() {
+        let cases = [
+            ("44", "7700900123"),
+            ("380", "501234567"),
+            ("380", "671234567"),
+            ("380", "931234567"),
+            ("380", "731234567"),
+            ("90", "5321234567"),
+            ("49", "15123456789"),
+            ("49", "1712345678"),
+            ("33", "612345678"),
+            ("34", "712345678"),
+            ("39", "3123456789"),
+            ("48", "501234567"),
+            ("7", "9161234567"),
+            ("91", "9876543210"),
+            ("86", "13812345678"),
+            ("62", "81234567890"),
+            ("81", "9012345678"),
+            ("61", "412345678"),
+            ("31", "612345678"),
+            ("63", "9171234567"),
+        ];
+
+        for (dial_code, number) in cases {
+            assert_eq!(
+                check(dial_code, number),
+                Some(true),
+                "+{dial_code} {number} should be mobile"
+            );
+        }
+    }
+
+    #[test]
+    fn test_landline_numbers() {
+        let cases = [
+            ("44", "2079460123"),
+            ("380", "441234567"),
+            ("90", "2121234567"),
+            ("49", "301234567"),
+            ("33", "112345678"),
+            ("34", "912345678"),
+            ("39", "612345678"),
+            ("48", "221234567"),
+            ("7", "4951234567"),
+            ("91", "1123456789"),
+            ("86", "2012345678"),
+            ("81", "312345678"),
+            ("61", "212345678"),
+            ("31", "201234567"),
+        ];
+
+        for (dial_code, number) in cases {
+            assert_eq!(
+                check(dial_code, number),
+                Some(false),
+                "+{dial_code} {number} should not be mobile"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_dial_codes() {
+        assert_eq!(check("1", "2025550123"), None);
+        assert_eq!(check("55", "11912345678"), None);
+        assert_eq!(check("999", "12345678"), None);
+    }
+}
@@ -1,5 +1,12 @@
 //! Internal utilities.
 
+pub(crate) mod dial_code;
+pub(crate) mod mobile_prefix;
+pub(crate) mod number_validation;
+pub(crate) mod restrictions;
 pub(crate) mod retry;
 
-pub use retry::RetryConfig;
+pub use dial_code::{CountryDialCode, keshvar_country_to_dial_code};
+pub use number_validation::NumberValidationError;
+pub use restrictions::{ProviderKind, is_sanctioned_country};
+pub use retry::{RetryBudget, RetryConfig};
@@ -0,0 +1,202 @@
+//! Country-specific phone number validation.
+
+use crate::types::{DialCode, Number};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Error when a national number does not match its country's numbering plan.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NumberValidationError {
+    /// Number length is outside the country's allowed range.
+    #[error("number has {len} digits, +{} numbers have {min}-{max}", dial_code.as_str())]
+    WrongLength {
+        /// The dial code the number was checked against.
+        dial_code: DialCode,
+        /// Actual number of digits.
+        len: usize,
+        /// Minimum allowed number of digits.
+        min: u8,
+        /// Maximum allowed number of digits.
+        max: u8,
+    },
+    /// Number does not start with any prefix valid for the country.
+    #[error("number does not start with a valid prefix for +{}", dial_code.as_str())]
+    InvalidPrefix {
+        /// The dial code the number was checked against.
+        dial_code: DialCode,
+    },
+}
+
+/// Length and prefix rules for national numbers of one dial code.
+struct NumberRule {
+    min_len: u8,
+    max_len: u8,
+    /// Allowed leading digits; empty means any.
+    valid_prefixes: &'static [&'static str],
+}
+
+impl NumberRule {
+    const fn new(min_len: u8, max_len: u8, valid_prefixes: &'static [&'static str]) -> Self {
+        Self {
+            min_len,
+            max_len,
+            valid_prefixes,
+        }
+    }
+}
+
+/// Numbering plan rules, keyed by dial code.
+///
+/// Lengths are for the national significant number, i.e. without the dial
+/// code and trunk prefix.
+static NUMBER_RULES: Lazy<HashMap<&'static str, NumberRule>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            "1",
+            NumberRule::new(10, 10, &["2", "3", "4", "5", "6", "7", "8", "9"]),
+        ),
+        ("7", NumberRule::new(10, 10, &["3", "4", "7", "8", "9"])),
+        ("20", NumberRule::new(8, 10, &[])),
+        ("27", NumberRule::new(9, 9, &[])),
+        ("31", NumberRule::new(9, 9, &[])),
+        ("33", NumberRule::new(9, 9, &[])),
+        ("34", NumberRule::new(9, 9, &["6", "7", "8", "9"])),
+        ("39", NumberRule::new(6, 11, &[])),
+        (
+            "44",
+            NumberRule::new(9, 10, &["1", "2", "3", "5", "7", "8", "9"]),
+        ),
+        ("48", NumberRule::new(9, 9, &[])),
+        ("49", NumberRule::new(6, 13, &[])),
+        ("52", NumberRule::new(10, 10, &[])),
+        ("55", NumberRule::new(10, 11, &[])),
+        ("61", NumberRule::new(9, 9, &["2", "3", "4", "7", "8"])),
+        ("62", NumberRule::new(8, 12, &[])),
+        ("63", NumberRule::new(8, 10, &[])),
+        ("66", NumberRule::new(8, 9, &[])),
+        ("81", NumberRule::new(9, 10, &[])),
+        ("82", NumberRule::new(8, 10, &[])),
+        ("84", NumberRule::new(9, 10, &[])),
+        ("86", NumberRule::new(10, 11, &[])),
+        ("90", NumberRule::new(10, 10, &["2", "3", "4", "5", "8"])),
+        (
+            "91",
+            NumberRule::new(10, 10, &["1", "2", "3", "4", "5", "6", "7", "8", "9"]),
+        ),
+        ("234", NumberRule::new(8, 10, &[])),
+        ("375", NumberRule::new(9, 9, &["1", "2", "3", "4"])),
+        (
+            "380",
+            NumberRule::new(9, 9, &["3", "4", "5", "6", "7", "9"]),
+        ),
+        ("998", NumberRule::new(9, 9, &[])),
+    ])
+});
+
+impl Number {
+    /// Check this national number against the numbering plan of a country.
+    ///
+    /// Validates the length and, where known, the leading digits. Returns
+    /// `Ok(())` for dial codes without a rule.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::{DialCode, Number};
+    ///
+    /// let ua = DialCode::new("380").unwrap();
+    /// assert!(Number::new("501234567").unwrap().validate_for_country(&ua).is_ok());
+    /// assert!(Number::new("5012345678").unwrap().validate_for_country(&ua).is_err());
+    /// ```
+    pub fn validate_for_country(&self, dial_code: &DialCode) -> Result<(), NumberValidationError> {
+        let Some(rule) = NUMBER_RULES.get(dial_code.as_str()) else {
+            return Ok(());
+        };
+
+        let len = self.as_str().len();
+        if !(rule.min_len as usize..=rule.max_len as usize).contains(&len) {
+            return Err(NumberValidationError::WrongLength {
+                dial_code: dial_code.clone(),
+                len,
+                min: rule.min_len,
+                max: rule.max_len,
+            });
+        }
+
+        if !rule.valid_prefixes.is_empty()
+            && !rule
+                .valid_prefixes
+                .iter()
+                .any(|prefix| self.as_str().starts_with(prefix))
+        {
+            return Err(NumberValidationError::InvalidPrefix {
+                dial_code: dial_code.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(dial_code: &str, number: &str) -> Result<(), NumberValidationError> {
+        Number::new(number)
+            .unwrap()
+            .validate_for_country(&DialCode::new(dial_code).unwrap())
+    }
+
+    #[test]
+    fn test_valid_numbers() {
+        let cases = [
+            ("1", "2025550123"),
+            ("7", "9161234567"),
+            ("44", "7700900123"),
+            ("49", "15123456789"),
+            ("380", "501234567"),
+            ("90", "5321234567"),
+            ("86", "13812345678"),
+        ];
+
+        for (dial_code, number) in cases {
+            assert_eq!(check(dial_code, number), Ok(()), "+{dial_code} {number}");
+        }
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        assert!(matches!(
+            check("380", "50123456"),
+            Err(NumberValidationError::WrongLength {
+                len: 8,
+                min: 9,
+                max: 9,
+                ..
+            })
+        ));
+        assert!(matches!(
+            check("1", "20255501234"),
+            Err(NumberValidationError::WrongLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!(matches!(
+            check("1", "1025550123"),
+            Err(NumberValidationError::InvalidPrefix { .. })
+        ));
+        assert!(matches!(
+            check("380", "801234567"),
+            Err(NumberValidationError::InvalidPrefix { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_dial_code() {
+        assert_eq!(check("999", "12345678"), Ok(()));
+    }
+}
@@ -0,0 +1,60 @@
+//! Country restrictions for SMS verification.
+
+use keshvar::Country;
+
+/// Alpha-2 codes of countries under sanctions that rule out SMS verification.
+static SANCTIONED_COUNTRIES: &[&str] = &["KP", "IR", "SY", "CU"];
+
+/// Known SMS providers, for provider-specific country restrictions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ProviderKind {
+    /// Hero SMS API.
+    HeroSms,
+    /// In-memory [`PhoneBookProvider`](crate::PhoneBookProvider).
+    PhoneBook,
+    /// 5sim API.
+    FiveSim,
+    /// OnlineSim API.
+    OnlineSim,
+    /// A user-defined provider.
+    Custom,
+}
+
+impl ProviderKind {
+    /// Returns a stable snake_case label for logs and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HeroSms => "hero_sms",
+            Self::PhoneBook => "phone_book",
+            Self::FiveSim => "five_sim",
+            Self::OnlineSim => "online_sim",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+/// Returns true if the country is on the sanctions list.
+pub fn is_sanctioned_country(country: &Country) -> bool {
+    let alpha2 = country.alpha2().to_string();
+    SANCTIONED_COUNTRIES.contains(&alpha2.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keshvar::Alpha2;
+
+    #[test]
+    fn test_is_sanctioned_country() {
+        assert!(is_sanctioned_country(&Alpha2::KP.to_country()));
+        assert!(is_sanctioned_country(&Alpha2::CU.to_country()));
+        assert!(!is_sanctioned_country(&Alpha2::UA.to_country()));
+    }
+
+    #[test]
+    fn test_provider_kind_as_str() {
+        assert_eq!(ProviderKind::HeroSms.as_str(), "hero_sms");
+        assert_eq!(ProviderKind::Custom.as_str(), "custom");
+    }
+}
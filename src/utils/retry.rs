@@ -1,7 +1,16 @@
 //! Retry configuration for SMS operations.
 
+use super::restrictions::ProviderKind;
 use backon::ExponentialBuilder;
-use std::time::Duration;
+use std::error::Error as StdError;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Callback invoked when the retry policy gives up.
+///
+/// Receives the last error, which is propagated to the caller afterwards.
+pub type OnGiveUpCallback = Arc<dyn Fn(&dyn StdError) + Send + Sync>;
 
 /// Configuration for retry behavior.
 ///
@@ -17,7 +26,7 @@ use std::time::Duration;
 ///     .with_factor(1.5)
 ///     .with_max_retries(5);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct RetryConfig {
     /// Minimum delay between retries (default: 1 second).
     pub min_delay: Duration,
@@ -27,6 +36,27 @@ pub struct RetryConfig {
     pub factor: f32,
     /// Maximum number of retry attempts (default: 3).
     pub max_retries: usize,
+    /// Add random jitter to each delay (default: false).
+    pub jitter: bool,
+    /// Provider the defaults were tuned for, if built with
+    /// [`RetryConfig::for_provider`] (default: none).
+    pub provider_kind: Option<ProviderKind>,
+    /// Callback invoked when all retry attempts are exhausted (default: none).
+    pub on_give_up: Option<OnGiveUpCallback>,
+}
+
+impl Debug for RetryConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryConfig")
+            .field("min_delay", &self.min_delay)
+            .field("max_delay", &self.max_delay)
+            .field("factor", &self.factor)
+            .field("max_retries", &self.max_retries)
+            .field("jitter", &self.jitter)
+            .field("provider_kind", &self.provider_kind)
+            .field("on_give_up", &self.on_give_up.as_ref().map(|_| "..."))
+            .finish()
+    }
 }
 
 impl Default for RetryConfig {
@@ -36,11 +66,57 @@ impl Default for RetryConfig {
             max_delay: Duration::from_secs(30),
             factor: 2.0,
             max_retries: 3,
+            jitter: false,
+            provider_kind: None,
+            on_give_up: None,
         }
     }
 }
 
 impl RetryConfig {
+    /// Retry settings tuned for a provider.
+    ///
+    /// ```rust
+    /// use sms_solvers::{ProviderKind, RetryConfig};
+    /// use std::time::Duration;
+    ///
+    /// let config = RetryConfig::for_provider(ProviderKind::HeroSms);
+    /// assert_eq!(config.max_retries, 3);
+    /// assert_eq!(config.min_delay, Duration::from_secs(2));
+    /// assert_eq!(config.provider_kind(), Some(ProviderKind::HeroSms));
+    /// ```
+    pub fn for_provider(provider_kind: ProviderKind) -> Self {
+        let config = match provider_kind {
+            // Hero SMS answers NO_NUMBERS and ERROR_SQL for short bursts and
+            // tolerates quick retries, but CHANNELS_LIMIT kicks in if many
+            // clients sharing a key retry in lockstep; jitter spreads them.
+            ProviderKind::HeroSms => Self::default()
+                .with_min_delay(Duration::from_secs(2))
+                .with_max_delay(Duration::from_secs(8))
+                .with_jitter(true),
+            // 5sim and OnlineSim temporarily ban keys after about ten failed
+            // requests in a row, so retry less often and back off further.
+            ProviderKind::FiveSim | ProviderKind::OnlineSim => Self::default()
+                .with_min_delay(Duration::from_secs(5))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_retries(2)
+                .with_jitter(true),
+            // Lookups are in memory; a retry cannot change the outcome.
+            ProviderKind::PhoneBook => Self::default().with_max_retries(0),
+            ProviderKind::Custom => Self::default(),
+        };
+
+        Self {
+            provider_kind: Some(provider_kind),
+            ..config
+        }
+    }
+
+    /// The provider this configuration was tuned for, if any.
+    pub fn provider_kind(&self) -> Option<ProviderKind> {
+        self.provider_kind
+    }
+
     /// Set the minimum delay between retries.
     pub fn with_min_delay(mut self, delay: Duration) -> Self {
         self.min_delay = delay;
@@ -65,12 +141,111 @@ impl RetryConfig {
         self
     }
 
+    /// Add random jitter to each delay.
+    ///
+    /// Keeps clients that fail at the same time from retrying in lockstep.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set a callback to be invoked when all retry attempts are exhausted.
+    ///
+    /// Not called for errors that are not retried in the first place.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sms_solvers::RetryConfig;
+    ///
+    /// let config = RetryConfig::default().with_on_give_up(|error| {
+    ///     eprintln!("Giving up: {}", error);
+    /// });
+    /// ```
+    pub fn with_on_give_up<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&dyn StdError) + Send + Sync + 'static,
+    {
+        self.on_give_up = Some(Arc::new(callback));
+        self
+    }
+
     /// Build a backoff strategy from this configuration.
     pub fn build_strategy(&self) -> ExponentialBuilder {
-        ExponentialBuilder::default()
+        let strategy = ExponentialBuilder::default()
             .with_min_delay(self.min_delay)
             .with_max_delay(self.max_delay)
             .with_factor(self.factor)
-            .with_max_times(self.max_retries)
+            .with_max_times(self.max_retries);
+
+        if self.jitter {
+            strategy.with_jitter()
+        } else {
+            strategy
+        }
+    }
+}
+
+/// Budget limiting the total number of retries within a time window.
+///
+/// Clones share the same counter, so one budget can be attached to several
+/// providers that use the same API key.
+///
+/// ```rust
+/// use sms_solvers::RetryBudget;
+/// use std::time::Duration;
+///
+/// let budget = RetryBudget::new(2, Duration::from_secs(60));
+/// assert!(budget.try_acquire());
+/// assert!(budget.try_acquire());
+/// assert!(!budget.try_acquire());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    max_retries_per_window: u32,
+    window: Duration,
+    /// Retries used in the current window and when the window started.
+    counter: Arc<Mutex<(u32, Instant)>>,
+}
+
+impl RetryBudget {
+    /// Allow at most `max` retries per `window`.
+    pub fn new(max: u32, window: Duration) -> Self {
+        Self {
+            max_retries_per_window: max,
+            window,
+            counter: Arc::new(Mutex::new((0, Instant::now()))),
+        }
+    }
+
+    /// Take one retry from the budget.
+    ///
+    /// Returns false, without using anything, if the current window is exhausted.
+    pub fn try_acquire(&self) -> bool {
+        let mut counter = self.lock();
+        if counter.0 >= self.max_retries_per_window {
+            return false;
+        }
+        counter.0 += 1;
+        true
+    }
+
+    /// Retries left in the current window.
+    pub fn remaining(&self) -> u32 {
+        self.max_retries_per_window.saturating_sub(self.lock().0)
+    }
+
+    /// When the current window ends and the budget is refilled.
+    pub fn resets_at(&self) -> Instant {
+        self.lock().1 + self.window
+    }
+
+    /// Lock the counter, starting a new window if the current one has ended.
+    fn lock(&self) -> std::sync::MutexGuard<'_, (u32, Instant)> {
+        let mut counter = self.counter.lock().unwrap_or_else(PoisonError::into_inner);
+        if counter.1.elapsed() >= self.window {
+            *counter = (0, Instant::now());
+        }
+        counter
     }
 }
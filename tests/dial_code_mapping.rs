@@ -3,7 +3,7 @@
 //! These tests verify that the dial code mapping from
 //! countries_with_dial_code.json works correctly.
 
-use sms_solvers::DialCode;
+use sms_solvers::{Alpha2, CountryDialCode, DialCode, keshvar_country_to_dial_code};
 
 /// Helper function to get dial code for a country using the service module's function.
 /// Since the function is private, we test it indirectly through the public API.
@@ -69,6 +69,33 @@ fn test_dial_code_json_has_popular_countries() {
     }
 }
 
+/// Test dial code lookup for keshvar countries and alpha-2 codes.
+#[test]
+fn test_country_to_dial_code() {
+    let expected_dial_codes = [
+        (Alpha2::US, "1"),
+        (Alpha2::CA, "1"),
+        (Alpha2::GB, "44"),
+        (Alpha2::UA, "380"),
+        (Alpha2::RU, "7"),
+        (Alpha2::KZ, "7"),
+        (Alpha2::TR, "90"),
+        (Alpha2::AE, "971"),
+    ];
+
+    for (alpha2, dial_code) in expected_dial_codes {
+        let country = alpha2.to_country();
+        assert_eq!(
+            keshvar_country_to_dial_code(&country).unwrap().as_str(),
+            dial_code,
+            "Dial code for {:?} should be '{}'",
+            alpha2,
+            dial_code
+        );
+        assert_eq!(country.to_dial_code(), alpha2.to_dial_code());
+    }
+}
+
 /// Test that dial codes are parsed correctly with various formats.
 #[test]
 fn test_dial_code_parsing() {
@@ -106,8 +133,10 @@ fn test_dial_code_parsing() {
 #[test]
 fn test_dial_code_display() {
     let dc = DialCode::new("+380").unwrap();
+    #[cfg(not(feature = "display-plus"))]
     assert_eq!(format!("{}", dc), "380");
-    assert_eq!(dc.to_string(), "380");
+    #[cfg(feature = "display-plus")]
+    assert_eq!(format!("{}", dc), "+380");
     assert_eq!(dc.as_str(), "380");
 }
 